@@ -0,0 +1,694 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use serde::Serialize;
+use uniffi_bindgen::{
+    interface::{AsType, Callable, Enum, Field, Literal, Object, Type},
+    BindingGenerator, Component, ComponentInterface, GenerationSettings,
+};
+
+#[derive(Args, Debug)]
+pub struct DiffApiArgs {
+    /// Only compare the crate with this name.
+    #[clap(long = "crate")]
+    crate_name: Option<String>,
+
+    /// Print the changes and the recommendation as JSON.
+    #[clap(long)]
+    json: bool,
+
+    /// The library built from the old version of the crate.
+    old: Utf8PathBuf,
+
+    /// The library built from the new version of the crate.
+    new: Utf8PathBuf,
+}
+
+impl DiffApiArgs {
+    pub fn run(&self) -> Result<()> {
+        let old = load_library(&self.old, self.crate_name.clone())?;
+        let new = load_library(&self.new, self.crate_name.clone())?;
+        let diff = ApiDiff::between(&old, &new);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            diff.print();
+        }
+        Ok(())
+    }
+}
+
+/// Extract the component interfaces from the library, in the same way as
+/// `BindingsArgs::run` does, but without writing any bindings.
+fn load_library(library: &Utf8Path, crate_name: Option<String>) -> Result<Vec<ComponentInterface>> {
+    let dummy_dir = Utf8PathBuf::from_str(".")?;
+    let components = uniffi_bindgen::library_mode::generate_bindings(
+        library,
+        crate_name,
+        &InterfaceCollector,
+        None,
+        &dummy_dir,
+        false,
+    )?;
+    Ok(components.into_iter().map(|c| c.ci).collect())
+}
+
+struct InterfaceCollector;
+
+impl BindingGenerator for InterfaceCollector {
+    type Config = ();
+
+    fn new_config(&self, _root_toml: &toml::value::Value) -> Result<Self::Config> {
+        Ok(())
+    }
+
+    fn update_component_configs(
+        &self,
+        _settings: &GenerationSettings,
+        _components: &mut Vec<Component<Self::Config>>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn write_bindings(
+        &self,
+        _settings: &GenerationSettings,
+        _components: &[Component<Self::Config>],
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ChangeKind {
+    /// Existing Javascript callers may stop compiling or working.
+    Breaking,
+    /// New API which existing callers do not use.
+    Additive,
+    /// No change to the Javascript API, e.g. only a checksum changed.
+    Internal,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Recommendation {
+    Major,
+    Minor,
+    Patch,
+    None,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Change {
+    kind: ChangeKind,
+    module: String,
+    item: String,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ApiDiff {
+    recommendation: Recommendation,
+    changes: Vec<Change>,
+}
+
+impl ApiDiff {
+    pub(crate) fn between(old: &[ComponentInterface], new: &[ComponentInterface]) -> Self {
+        let old = by_name(old, |ci| ci.crate_name());
+        let new = by_name(new, |ci| ci.crate_name());
+        let mut differ = Differ::default();
+        for (name, o) in &old {
+            match new.get(name) {
+                Some(n) => differ.module(o, n),
+                None => {
+                    differ.module = o.namespace().to_string();
+                    differ.push(ChangeKind::Breaking, format!("crate `{name}`"), "removed");
+                }
+            }
+        }
+        for (name, n) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+            differ.module = n.namespace().to_string();
+            differ.push(ChangeKind::Additive, format!("crate `{name}`"), "added");
+        }
+        let mut changes = differ.changes;
+        changes.sort_by(|a, b| (a.kind, &a.module, &a.item).cmp(&(b.kind, &b.module, &b.item)));
+        let recommendation = match changes.first().map(|c| c.kind) {
+            Some(ChangeKind::Breaking) => Recommendation::Major,
+            Some(ChangeKind::Additive) => Recommendation::Minor,
+            Some(ChangeKind::Internal) => Recommendation::Patch,
+            None => Recommendation::None,
+        };
+        Self {
+            recommendation,
+            changes,
+        }
+    }
+
+    pub(crate) fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    fn print(&self) {
+        for c in &self.changes {
+            let kind = format!("{:?}", c.kind).to_lowercase();
+            println!("{kind:>8}: {}: {}: {}", c.module, c.item, c.message);
+        }
+        let summary = format!(
+            "{} breaking, {} additive and {} internal changes",
+            self.count(ChangeKind::Breaking),
+            self.count(ChangeKind::Additive),
+            self.count(ChangeKind::Internal)
+        );
+        match self.recommendation {
+            Recommendation::None => println!("No changes to the API"),
+            r => println!(
+                "{summary}: a {} version bump is recommended",
+                format!("{r:?}").to_lowercase()
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Differ {
+    module: String,
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn push(&mut self, kind: ChangeKind, item: impl Display, message: impl Into<String>) {
+        self.changes.push(Change {
+            kind,
+            module: self.module.clone(),
+            item: item.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Compare two maps of named items, reporting the items which were removed or
+    /// added as the given kinds of change, and comparing the rest with `f`.
+    fn compare<T>(
+        &mut self,
+        prefix: &str,
+        what: &str,
+        old: &BTreeMap<&str, T>,
+        new: &BTreeMap<&str, T>,
+        (removed, added): (ChangeKind, ChangeKind),
+        f: impl Fn(&mut Self, &str, &T, &T),
+    ) {
+        for (name, o) in old {
+            let item = format!("{prefix}{what} `{name}`");
+            match new.get(name) {
+                Some(n) => f(self, &item, o, n),
+                None => self.push(removed, item, "removed"),
+            }
+        }
+        for name in new.keys().filter(|name| !old.contains_key(*name)) {
+            self.push(added, format!("{prefix}{what} `{name}`"), "added");
+        }
+    }
+
+    fn module(&mut self, old: &ComponentInterface, new: &ComponentInterface) {
+        self.module = new.namespace().to_string();
+        if old.uniffi_contract_version() != new.uniffi_contract_version() {
+            self.push(
+                ChangeKind::Internal,
+                "module",
+                format!(
+                    "uniffi contract version changed from {} to {}",
+                    old.uniffi_contract_version(),
+                    new.uniffi_contract_version()
+                ),
+            );
+        }
+        let breaking_additive = (ChangeKind::Breaking, ChangeKind::Additive);
+
+        self.compare(
+            "",
+            "function",
+            &by_name(old.function_definitions(), |f| f.name()),
+            &by_name(new.function_definitions(), |f| f.name()),
+            breaking_additive,
+            |d, item, o, n| d.callable(item, *o, *n, o.checksum() != n.checksum()),
+        );
+        self.compare(
+            "",
+            "record",
+            &by_name(old.record_definitions(), |r| r.name()),
+            &by_name(new.record_definitions(), |r| r.name()),
+            breaking_additive,
+            |d, item, o, n| d.fields(item, o.fields(), n.fields()),
+        );
+        self.compare(
+            "",
+            "enum",
+            &by_name(old.enum_definitions(), |e| e.name()),
+            &by_name(new.enum_definitions(), |e| e.name()),
+            breaking_additive,
+            |d, item, o, n| {
+                let was_error = old.is_name_used_as_error(o.name());
+                if was_error != new.is_name_used_as_error(n.name()) {
+                    d.push(ChangeKind::Breaking, item, error_message(was_error));
+                }
+                d.enum_(item, o, n)
+            },
+        );
+        self.compare(
+            "",
+            "object",
+            &by_name(old.object_definitions(), |o| o.name()),
+            &by_name(new.object_definitions(), |o| o.name()),
+            breaking_additive,
+            |d, item, o, n| {
+                let was_error = old.is_name_used_as_error(o.name());
+                if was_error != new.is_name_used_as_error(n.name()) {
+                    d.push(ChangeKind::Breaking, item, error_message(was_error));
+                }
+                d.object(item, o, n)
+            },
+        );
+        self.compare(
+            "",
+            "callback interface",
+            &by_name(old.callback_interface_definitions(), |c| c.name()),
+            &by_name(new.callback_interface_definitions(), |c| c.name()),
+            breaking_additive,
+            |d, item, o, n| {
+                // Javascript implements callback interfaces, so a new method breaks
+                // existing implementations, but a removed method is harmless.
+                d.compare(
+                    &format!("{item} "),
+                    "method",
+                    &by_name(o.methods(), |m| m.name()),
+                    &by_name(n.methods(), |m| m.name()),
+                    (ChangeKind::Additive, ChangeKind::Breaking),
+                    |d, item, o, n| d.callable(item, *o, *n, o.checksum() != n.checksum()),
+                )
+            },
+        );
+        self.compare(
+            "",
+            "custom type",
+            &custom_types(old),
+            &custom_types(new),
+            breaking_additive,
+            |d, item, o, n| {
+                if describe(o) != describe(n) {
+                    d.push(
+                        ChangeKind::Breaking,
+                        item,
+                        format!("changed from {} to {}", describe(o), describe(n)),
+                    );
+                }
+            },
+        );
+    }
+
+    fn callable(&mut self, item: &str, old: &impl Callable, new: &impl Callable, checksum: bool) {
+        let count = self.changes.len();
+        if old.is_async() != new.is_async() {
+            let message = if new.is_async() {
+                "became async"
+            } else {
+                "is no longer async"
+            };
+            self.push(ChangeKind::Breaking, item, message);
+        }
+        self.types(
+            item,
+            "return type",
+            old.return_type().as_ref(),
+            new.return_type().as_ref(),
+        );
+        match (old.throws_type(), new.throws_type()) {
+            (None, Some(t)) => self.push(
+                ChangeKind::Breaking,
+                item,
+                format!("now throws {}", describe(&t)),
+            ),
+            (Some(_), None) => self.push(ChangeKind::Additive, item, "no longer throws"),
+            (o, n) => self.types(item, "error type", o.as_ref(), n.as_ref()),
+        }
+
+        // Arguments are positional in Typescript, so they are compared by position.
+        let (old_args, new_args) = (old.arguments(), new.arguments());
+        for (index, o) in old_args.iter().enumerate() {
+            let Some(n) = new_args.get(index) else {
+                let message = format!("argument `{}` removed", o.name());
+                self.push(ChangeKind::Breaking, item, message);
+                continue;
+            };
+            let what = format!("argument `{}`", n.name());
+            if o.name() != n.name() {
+                let message = format!("argument `{}` renamed to `{}`", o.name(), n.name());
+                self.push(ChangeKind::Internal, item, message);
+            }
+            self.types(item, &what, Some(&o.as_type()), Some(&n.as_type()));
+            self.default_value(item, &what, o.default_value(), n.default_value());
+        }
+        for n in new_args.iter().skip(old_args.len()) {
+            let (kind, message) = match n.default_value() {
+                Some(_) => (ChangeKind::Additive, "with a default value"),
+                None => (ChangeKind::Breaking, "without a default value"),
+            };
+            self.push(
+                kind,
+                item,
+                format!("argument `{}` added {message}", n.name()),
+            );
+        }
+
+        if checksum && count == self.changes.len() {
+            self.push(ChangeKind::Internal, item, "checksum changed");
+        }
+    }
+
+    fn types(&mut self, item: &str, what: &str, old: Option<&Type>, new: Option<&Type>) {
+        let describe = |t: Option<&Type>| t.map(describe).unwrap_or_else(|| "nothing".to_string());
+        let (old, new) = (describe(old), describe(new));
+        if old != new {
+            let message = format!("{what} changed from {old} to {new}");
+            self.push(ChangeKind::Breaking, item, message);
+        }
+    }
+
+    fn default_value(
+        &mut self,
+        item: &str,
+        what: &str,
+        old: Option<&Literal>,
+        new: Option<&Literal>,
+    ) {
+        match (old, new) {
+            (Some(_), None) => self.push(
+                ChangeKind::Breaking,
+                item,
+                format!("{what} lost its default"),
+            ),
+            (None, Some(_)) => self.push(
+                ChangeKind::Additive,
+                item,
+                format!("{what} gained a default"),
+            ),
+            (Some(o), Some(n)) if o != n => self.push(
+                ChangeKind::Breaking,
+                item,
+                format!("{what} changed its default"),
+            ),
+            _ => {}
+        }
+    }
+
+    fn fields(&mut self, item: &str, old: &[Field], new: &[Field]) {
+        let old_fields = by_name(old.iter().enumerate(), field_key);
+        let new_fields = by_name(new.iter().enumerate(), field_key);
+        for (name, (index, o)) in &old_fields {
+            let what = format!("field `{name}`");
+            let Some((new_index, n)) = new_fields.get(name) else {
+                self.push(ChangeKind::Breaking, item, format!("{what} removed"));
+                continue;
+            };
+            self.types(item, &what, Some(&o.as_type()), Some(&n.as_type()));
+            self.default_value(item, &what, o.default_value(), n.default_value());
+            if index != new_index {
+                // Records are objects in Typescript, so the order is only visible
+                // to the generated FfiConverters.
+                self.push(ChangeKind::Internal, item, format!("{what} moved"));
+            }
+        }
+        for (name, (_, n)) in new_fields
+            .iter()
+            .filter(|(k, _)| !old_fields.contains_key(*k))
+        {
+            let (kind, message) = match n.default_value() {
+                Some(_) => (ChangeKind::Additive, "with a default value"),
+                None => (ChangeKind::Breaking, "without a default value"),
+            };
+            self.push(kind, item, format!("field `{name}` added {message}"));
+        }
+    }
+
+    fn enum_(&mut self, item: &str, old: &Enum, new: &Enum) {
+        if old.is_flat() != new.is_flat() {
+            let message = if new.is_flat() {
+                "changed from a tagged to a flat enum"
+            } else {
+                "changed from a flat to a tagged enum"
+            };
+            self.push(ChangeKind::Breaking, item, message);
+            return;
+        }
+        let old_variants = by_name(old.variants().iter().enumerate(), |(_, v)| v.name());
+        let new_variants = by_name(new.variants().iter().enumerate(), |(_, v)| v.name());
+        for (name, (index, o)) in &old_variants {
+            let what = format!("{item} variant `{name}`");
+            let Some((new_index, n)) = new_variants.get(name) else {
+                self.push(ChangeKind::Breaking, what, "removed");
+                continue;
+            };
+            // Flat enums become Typescript enums, whose values are the discriminants,
+            // which default to the position of the variant.
+            if old.is_flat() && old.variant_discr(*index).ok() != new.variant_discr(*new_index).ok()
+            {
+                self.push(ChangeKind::Breaking, &what, "value changed");
+            } else if index != new_index {
+                self.push(ChangeKind::Internal, &what, "moved");
+            }
+            self.fields(&what, o.fields(), n.fields());
+        }
+        for name in new_variants
+            .keys()
+            .filter(|k| !old_variants.contains_key(*k))
+        {
+            // A new variant breaks exhaustive `switch` statements, unless the
+            // enum is marked `#[non_exhaustive]`.
+            let kind = if new.is_non_exhaustive() {
+                ChangeKind::Additive
+            } else {
+                ChangeKind::Breaking
+            };
+            self.push(kind, format!("{item} variant `{name}`"), "added");
+        }
+    }
+
+    fn object(&mut self, item: &str, old: &Object, new: &Object) {
+        if old.imp() != new.imp() {
+            let message = format!("changed from {:?} to {:?}", old.imp(), new.imp());
+            self.push(ChangeKind::Breaking, item, message);
+        }
+        let breaking_additive = (ChangeKind::Breaking, ChangeKind::Additive);
+        let prefix = format!("{item} ");
+        self.compare(
+            &prefix,
+            "constructor",
+            &by_name(old.constructors(), |c| c.name()),
+            &by_name(new.constructors(), |c| c.name()),
+            breaking_additive,
+            |d, item, o, n| d.callable(item, *o, *n, o.checksum() != n.checksum()),
+        );
+        self.compare(
+            &prefix,
+            "method",
+            &by_name(old.methods(), |m| m.name()),
+            &by_name(new.methods(), |m| m.name()),
+            breaking_additive,
+            |d, item, o, n| d.callable(item, *o, *n, o.checksum() != n.checksum()),
+        );
+        self.compare(
+            &prefix,
+            "trait",
+            &uniffi_traits(old),
+            &uniffi_traits(new),
+            breaking_additive,
+            |_, _, _, _| {},
+        );
+    }
+}
+
+fn by_name<'a, T>(
+    items: impl IntoIterator<Item = T>,
+    name: impl Fn(&T) -> &'a str,
+) -> BTreeMap<&'a str, T> {
+    items.into_iter().map(|t| (name(&t), t)).collect()
+}
+
+/// Fields of tuple variants have no names, so are identified by their position.
+fn field_key<'a>((index, field): &(usize, &'a Field)) -> &'a str {
+    const POSITIONS: [&str; 16] = [
+        "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15",
+    ];
+    match field.name() {
+        "" => POSITIONS.get(*index).copied().unwrap_or("?"),
+        name => name,
+    }
+}
+
+fn custom_types(ci: &ComponentInterface) -> BTreeMap<&str, Type> {
+    ci.iter_types()
+        .filter_map(|t| match t {
+            Type::Custom { name, builtin, .. } => Some((name.as_str(), builtin.as_ref().clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn uniffi_traits(obj: &Object) -> BTreeMap<&'static str, ()> {
+    use uniffi_bindgen::interface::UniffiTrait;
+    obj.uniffi_traits()
+        .into_iter()
+        .map(|t| match t {
+            UniffiTrait::Debug { .. } => "Debug",
+            UniffiTrait::Display { .. } => "Display",
+            UniffiTrait::Eq { .. } => "Eq",
+            UniffiTrait::Hash { .. } => "Hash",
+        })
+        .map(|name| (name, ()))
+        .collect()
+}
+
+fn error_message(was_error: bool) -> &'static str {
+    if was_error {
+        "is no longer used as an error"
+    } else {
+        "is now used as an error"
+    }
+}
+
+/// A Rust-like description of the type, ignoring where the type is defined.
+fn describe(type_: &Type) -> String {
+    match type_ {
+        Type::UInt8 => "u8".into(),
+        Type::Int8 => "i8".into(),
+        Type::UInt16 => "u16".into(),
+        Type::Int16 => "i16".into(),
+        Type::UInt32 => "u32".into(),
+        Type::Int32 => "i32".into(),
+        Type::UInt64 => "u64".into(),
+        Type::Int64 => "i64".into(),
+        Type::Float32 => "f32".into(),
+        Type::Float64 => "f64".into(),
+        Type::Boolean => "bool".into(),
+        Type::String => "String".into(),
+        Type::Bytes => "Vec<u8>".into(),
+        Type::Timestamp => "SystemTime".into(),
+        Type::Duration => "Duration".into(),
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => name.clone(),
+        Type::Optional { inner_type } => format!("Option<{}>", describe(inner_type)),
+        Type::Sequence { inner_type } => format!("Vec<{}>", describe(inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!("HashMap<{}, {}>", describe(key_type), describe(value_type)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> ApiDiff {
+        let old = ComponentInterface::from_webidl(old, "crate_name").unwrap();
+        let new = ComponentInterface::from_webidl(new, "crate_name").unwrap();
+        ApiDiff::between(&[old], &[new])
+    }
+
+    fn messages(diff: &ApiDiff) -> Vec<String> {
+        diff.changes
+            .iter()
+            .map(|c| format!("{:?} {}: {}", c.kind, c.item, c.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let udl = r#"
+            namespace example {
+                u32 add(u32 a, u32 b);
+            };
+        "#;
+        let diff = diff(udl, udl);
+        assert!(diff.changes.is_empty());
+        assert_eq!(diff.recommendation, Recommendation::None);
+    }
+
+    #[test]
+    fn test_functions() {
+        let old = r#"
+            namespace example {
+                u32 add(u32 a, u32 b);
+                void remove_me();
+                string greet(string name);
+            };
+        "#;
+        let new = r#"
+            namespace example {
+                u64 add(u32 a, u32 b);
+                string greet(string name, optional boolean shout = false);
+                void new_one();
+            };
+        "#;
+        let diff = diff(old, new);
+        assert_eq!(
+            messages(&diff),
+            vec![
+                "Breaking function `add`: return type changed from u32 to u64",
+                "Breaking function `remove_me`: removed",
+                "Additive function `greet`: argument `shout` added with a default value",
+                "Additive function `new_one`: added",
+            ]
+        );
+        assert_eq!(diff.recommendation, Recommendation::Major);
+    }
+
+    #[test]
+    fn test_records_and_enums() {
+        let old = r#"
+            namespace example {};
+            dictionary Point {
+                double x;
+                double y;
+            };
+            enum Direction { "North", "South" };
+            [Error]
+            enum MathError { "Overflow" };
+        "#;
+        let new = r#"
+            namespace example {};
+            dictionary Point {
+                double y;
+                double x;
+                double z = 0.0;
+            };
+            enum Direction { "South", "North" };
+            [Error]
+            enum MathError { "Overflow", "DivideByZero" };
+        "#;
+        let diff = diff(old, new);
+        assert_eq!(
+            messages(&diff),
+            vec![
+                "Breaking enum `Direction` variant `North`: value changed",
+                "Breaking enum `Direction` variant `South`: value changed",
+                "Breaking enum `MathError` variant `DivideByZero`: added",
+                "Additive record `Point`: field `z` added with a default value",
+                "Internal record `Point`: field `x` moved",
+                "Internal record `Point`: field `y` moved",
+            ]
+        );
+        assert_eq!(diff.recommendation, Recommendation::Major);
+        assert_eq!(diff.count(ChangeKind::Internal), 2);
+    }
+}
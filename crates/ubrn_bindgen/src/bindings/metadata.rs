@@ -38,6 +38,10 @@ impl ModuleMetadata {
         format!("{}.ts", self.ts())
     }
 
+    pub fn ts_api_filename(&self) -> String {
+        format!("{}.api.d.ts", self.ts())
+    }
+
     pub fn ts_ffi(&self) -> String {
         format!("{}-ffi", self.namespace)
     }
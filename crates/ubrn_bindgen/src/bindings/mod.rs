@@ -4,17 +4,18 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 
+pub(crate) mod api_diff;
 pub mod metadata;
 pub(crate) mod react_native;
 pub(crate) mod type_map;
 
-use std::{fs, str::FromStr};
+use std::{collections::BTreeSet, fs, str::FromStr};
 
 use anyhow::Result;
 use askama::Template;
 use camino::{Utf8Path, Utf8PathBuf};
-use clap::{command, Args};
-use ubrn_common::mk_dir;
+use clap::Args;
+use ubrn_common::{mk_dir, timed, GeneratedFiles};
 
 use crate::ModuleMetadata;
 
@@ -26,11 +27,33 @@ pub struct BindingsArgs {
     source: SourceArgs,
     #[command(flatten)]
     output: OutputArgs,
+
+    /// Watch the source and the uniffi.toml file, and regenerate the bindings
+    /// whenever they change.
+    #[clap(long)]
+    watch: bool,
 }
 
 impl BindingsArgs {
     pub fn new(source: SourceArgs, output: OutputArgs) -> Self {
-        Self { source, output }
+        Self {
+            source,
+            output,
+            watch: false,
+        }
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watch
+    }
+
+    /// The files which, if changed, mean the bindings need regenerating.
+    pub fn watched_paths(&self) -> Vec<Utf8PathBuf> {
+        let source = &self.source;
+        let mut paths = vec![source.source.clone()];
+        paths.extend(source.lib_file.clone());
+        paths.extend(source.config.clone());
+        paths
     }
 
     pub fn ts_dir(&self) -> &Utf8Path {
@@ -55,6 +78,16 @@ pub struct OutputArgs {
     /// The directory in which to put the generated C++.
     #[clap(long)]
     cpp_dir: Utf8PathBuf,
+
+    /// Do not write any files, but exit with an error if any generated file
+    /// is out of date.
+    #[clap(long)]
+    check: bool,
+
+    /// The modules which have already been generated, e.g. for another crate with
+    /// the same dependency, and so are not generated again.
+    #[clap(skip)]
+    generated_modules: BTreeSet<String>,
 }
 
 impl OutputArgs {
@@ -63,8 +96,26 @@ impl OutputArgs {
             ts_dir: ts_dir.to_owned(),
             cpp_dir: cpp_dir.to_owned(),
             no_format,
+            check: false,
+            generated_modules: Default::default(),
         }
     }
+
+    pub fn with_check(self, check: bool) -> Self {
+        Self { check, ..self }
+    }
+
+    pub fn with_generated_modules(self, modules: &[ModuleMetadata]) -> Self {
+        let generated_modules = modules.iter().map(ModuleMetadata::ts).collect();
+        Self {
+            generated_modules,
+            ..self
+        }
+    }
+
+    pub(crate) fn is_generated(&self, module: &ModuleMetadata) -> bool {
+        self.generated_modules.contains(&module.ts())
+    }
 }
 
 #[derive(Args, Clone, Debug, Default)]
@@ -112,6 +163,13 @@ impl SourceArgs {
 
 impl BindingsArgs {
     pub fn run(&self) -> Result<Vec<ModuleMetadata>> {
+        let (modules, files) = self.generate()?;
+        files.finish()?;
+        Ok(modules)
+    }
+
+    /// Generate the bindings, returning the modules and which files were written.
+    pub fn generate(&self) -> Result<(Vec<ModuleMetadata>, GeneratedFiles)> {
         let input = &self.source;
         let out = &self.output;
 
@@ -123,32 +181,34 @@ impl BindingsArgs {
 
         let try_format_code = !out.no_format;
 
-        let configs: Vec<ModuleMetadata> = if input.library_mode {
-            uniffi_bindgen::library_mode::generate_bindings(
-                &input.source,
-                input.crate_name.clone(),
-                &generator,
-                input.config.as_deref(),
-                &dummy_dir,
-                try_format_code,
-            )?
-            .iter()
-            .map(|s| s.into())
-            .collect()
-        } else {
-            uniffi_bindgen::generate_external_bindings(
-                &generator,
-                input.source.clone(),
-                input.config.as_deref(),
-                Some(&dummy_dir),
-                input.lib_file.clone(),
-                input.crate_name.as_deref(),
-                try_format_code,
-            )?;
-            Default::default()
-        };
-
-        Ok(configs)
+        let configs: Vec<ModuleMetadata> = timed("bindgen", || -> Result<_> {
+            Ok(if input.library_mode {
+                uniffi_bindgen::library_mode::generate_bindings(
+                    &input.source,
+                    input.crate_name.clone(),
+                    &generator,
+                    input.config.as_deref(),
+                    &dummy_dir,
+                    try_format_code,
+                )?
+                .iter()
+                .map(|s| s.into())
+                .collect()
+            } else {
+                uniffi_bindgen::generate_external_bindings(
+                    &generator,
+                    input.source.clone(),
+                    input.config.as_deref(),
+                    Some(&dummy_dir),
+                    input.lib_file.clone(),
+                    input.crate_name.as_deref(),
+                    try_format_code,
+                )?;
+                Default::default()
+            })
+        })?;
+
+        Ok((configs, generator.into_files()))
     }
 
     pub fn render_entrypoint(&self, path: &Utf8Path, modules: &Vec<ModuleMetadata>) -> Result<()> {
@@ -3,7 +3,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
-use super::oracle::{AsCodeType, CodeOracle, CodeType};
+use super::oracle::{CodeOracle, CodeType, Representation};
+use crate::bindings::react_native::uniffi_toml::Int64Type;
 use uniffi_bindgen::{
     backend::{Literal, Type},
     ComponentInterface,
@@ -12,33 +13,31 @@ use uniffi_bindgen::{
 #[derive(Debug)]
 pub struct OptionalCodeType {
     inner: Type,
+    repr: Representation,
 }
 
 impl OptionalCodeType {
-    pub fn new(inner: Type) -> Self {
-        Self { inner }
+    pub fn new(inner: Type, repr: Representation) -> Self {
+        Self { inner, repr }
     }
-    fn inner(&self) -> &Type {
-        &self.inner
+    fn inner(&self) -> Box<dyn CodeType> {
+        CodeOracle.find_as(&self.inner, self.repr)
     }
 }
 
 impl CodeType for OptionalCodeType {
     fn type_label(&self, ci: &ComponentInterface) -> String {
-        format!(
-            "{} | undefined",
-            CodeOracle.find(self.inner()).type_label(ci)
-        )
+        format!("{} | undefined", self.inner().type_label(ci))
     }
 
     fn canonical_name(&self) -> String {
-        format!("Optional{}", CodeOracle.find(self.inner()).canonical_name())
+        format!("Optional{}", self.inner().canonical_name())
     }
 
     fn literal(&self, literal: &Literal, ci: &ComponentInterface) -> String {
         match literal {
             Literal::None => "undefined".into(),
-            Literal::Some { inner } => CodeOracle.find(&self.inner).literal(inner, ci),
+            Literal::Some { inner } => self.inner().literal(inner, ci),
             _ => panic!("Invalid literal for Optional type: {literal:?}"),
         }
     }
@@ -47,24 +46,25 @@ impl CodeType for OptionalCodeType {
 #[derive(Debug)]
 pub struct SequenceCodeType {
     inner: Type,
+    repr: Representation,
 }
 
 impl SequenceCodeType {
-    pub fn new(inner: Type) -> Self {
-        Self { inner }
+    pub fn new(inner: Type, repr: Representation) -> Self {
+        Self { inner, repr }
     }
-    fn inner(&self) -> &Type {
-        &self.inner
+    fn inner(&self) -> Box<dyn CodeType> {
+        CodeOracle.find_as(&self.inner, self.repr)
     }
 }
 
 impl CodeType for SequenceCodeType {
     fn type_label(&self, ci: &ComponentInterface) -> String {
-        format!("Array<{}>", CodeOracle.find(self.inner()).type_label(ci))
+        format!("Array<{}>", self.inner().type_label(ci))
     }
 
     fn canonical_name(&self) -> String {
-        format!("Array{}", CodeOracle.find(self.inner()).canonical_name())
+        format!("Array{}", self.inner().canonical_name())
     }
 
     fn literal(&self, literal: &Literal, _ci: &ComponentInterface) -> String {
@@ -75,23 +75,75 @@ impl CodeType for SequenceCodeType {
     }
 }
 
+/// The typed array for a sequence of fixed-width numbers, if `typedArrays` is on.
+///
+/// 64-bit integers represented as numbers are left as arrays, because the typed arrays
+/// for them can only hold bigints.
+pub(crate) fn typed_array(inner: &Type, repr: Representation) -> Option<&'static str> {
+    if !repr.typed_arrays {
+        return None;
+    }
+    Some(match (inner, repr.int64) {
+        (Type::Int8, _) => "Int8Array",
+        (Type::UInt8, _) => "Uint8Array",
+        (Type::Int16, _) => "Int16Array",
+        (Type::UInt16, _) => "Uint16Array",
+        (Type::Int32, _) => "Int32Array",
+        (Type::UInt32, _) => "Uint32Array",
+        (Type::Int64, Int64Type::Bigint) => "BigInt64Array",
+        (Type::UInt64, Int64Type::Bigint) => "BigUint64Array",
+        (Type::Float32, _) => "Float32Array",
+        (Type::Float64, _) => "Float64Array",
+        _ => return None,
+    })
+}
+
+#[derive(Debug)]
+pub struct TypedArrayCodeType {
+    name: &'static str,
+}
+
+impl TypedArrayCodeType {
+    pub fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+}
+
+impl CodeType for TypedArrayCodeType {
+    fn type_label(&self, _ci: &ComponentInterface) -> String {
+        self.name.into()
+    }
+
+    fn canonical_name(&self) -> String {
+        self.name.into()
+    }
+
+    fn literal(&self, literal: &Literal, _ci: &ComponentInterface) -> String {
+        match literal {
+            Literal::EmptySequence => format!("new {}()", self.name),
+            _ => panic!("Invalid literal for List type: {literal:?}"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MapCodeType {
     key: Type,
     value: Type,
+    repr: Representation,
 }
 
 impl MapCodeType {
-    pub fn new(key: Type, value: Type) -> Self {
-        Self { key, value }
+    pub fn new(key: Type, value: Type, repr: Representation) -> Self {
+        Self { key, value, repr }
     }
 
-    fn key(&self) -> &Type {
-        &self.key
+    fn key(&self) -> Box<dyn CodeType> {
+        CodeOracle.find_as(&self.key, self.repr)
     }
 
-    fn value(&self) -> &Type {
-        &self.value
+    fn value(&self) -> Box<dyn CodeType> {
+        CodeOracle.find_as(&self.value, self.repr)
     }
 }
 
@@ -99,16 +151,16 @@ impl CodeType for MapCodeType {
     fn type_label(&self, ci: &ComponentInterface) -> String {
         format!(
             "Map<{}, {}>",
-            CodeOracle.find(self.key()).type_label(ci),
-            CodeOracle.find(self.value()).type_label(ci),
+            self.key().type_label(ci),
+            self.value().type_label(ci),
         )
     }
 
     fn canonical_name(&self) -> String {
         format!(
             "Map{}{}",
-            self.key().as_codetype().canonical_name(),
-            self.value().as_codetype().canonical_name(),
+            self.key().canonical_name(),
+            self.value().canonical_name(),
         )
     }
 
@@ -4,38 +4,100 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 use super::{
-    oracle::{AsCodeType, CodeOracle},
-    TypeRenderer,
+    compounds::typed_array,
+    oracle::{AsCodeType, CodeOracle, Representation},
+    Scoped, TypeRenderer,
 };
+use crate::bindings::react_native::uniffi_toml::Int64Type;
 pub(crate) use uniffi_bindgen::backend::filters::*;
 use uniffi_bindgen::{
     backend::{Literal, Type},
-    interface::{AsType, Enum, FfiType, Variant},
+    interface::{
+        Argument, AsType, CallbackInterface, Enum, FfiType, Field, Object, Record, Variant,
+    },
     ComponentInterface,
 };
 
-pub(super) fn type_name(
-    as_type: &impl AsType,
-    types: &TypeRenderer,
-) -> Result<String, askama::Error> {
-    let type_ = types.as_type(as_type);
-    Ok(type_.as_codetype().type_label(types.ci))
+/// Somewhere a type is used, which may have its own entry in `int64Overrides`.
+///
+/// Fields and arguments are named within the item being rendered, e.g. `MyRecord.my_field`;
+/// other types take the representation of the item itself.
+pub(super) trait TypeSite: AsType {
+    fn site_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl TypeSite for Type {}
+impl TypeSite for Object {}
+impl TypeSite for Record {}
+impl TypeSite for Enum {}
+impl TypeSite for CallbackInterface {}
+
+impl TypeSite for Field {
+    fn site_name(&self) -> Option<&str> {
+        Some(self.name())
+    }
+}
+
+impl TypeSite for Argument {
+    fn site_name(&self) -> Option<&str> {
+        Some(self.name())
+    }
+}
+
+impl<T: TypeSite> TypeSite for Box<T> {
+    fn site_name(&self) -> Option<&str> {
+        self.as_ref().site_name()
+    }
+}
+
+impl<T: TypeSite> TypeSite for &T {
+    fn site_name(&self) -> Option<&str> {
+        (*self).site_name()
+    }
+}
+
+pub(super) fn type_name(site: &impl TypeSite, types: &Scoped) -> Result<String, askama::Error> {
+    Ok(types.code_type(site).type_label(types.ci))
 }
 
 pub(super) fn decl_type_name(
+    site: &impl TypeSite,
+    types: &Scoped,
+) -> Result<String, askama::Error> {
+    Ok(types.code_type(site).decl_type_label(types.ci))
+}
+
+pub(super) fn ffi_converter_name(
+    site: &impl TypeSite,
+    types: &Scoped,
+) -> Result<String, askama::Error> {
+    Ok(types.code_type(site).ffi_converter_name())
+}
+
+/// The type name, with any 64-bit integers represented as `int64`, wherever it is used.
+pub(super) fn type_name_as(
     as_type: &impl AsType,
+    int64: &Int64Type,
     types: &TypeRenderer,
 ) -> Result<String, askama::Error> {
     let type_ = types.as_type(as_type);
-    Ok(type_.as_codetype().decl_type_label(types.ci))
+    Ok(CodeOracle
+        .find_as(&type_, types.representation(*int64))
+        .type_label(types.ci))
 }
 
-pub(super) fn ffi_converter_name(
+/// The converter name, with any 64-bit integers represented as `int64`, wherever it is used.
+pub(super) fn ffi_converter_name_as(
     as_type: &impl AsType,
+    int64: &Int64Type,
     types: &TypeRenderer,
 ) -> Result<String, askama::Error> {
     let type_ = types.as_type(as_type);
-    Ok(type_.as_codetype().ffi_converter_name())
+    Ok(CodeOracle
+        .find_as(&type_, types.representation(*int64))
+        .ffi_converter_name())
 }
 
 pub(super) fn ffi_error_converter_name(
@@ -74,22 +136,93 @@ pub(super) fn lift_error_fn(
     ))
 }
 
-pub(super) fn lift_fn(
-    as_type: &impl AsType,
-    types: &TypeRenderer,
-) -> Result<String, askama::Error> {
+pub(super) fn lift_fn(site: &impl TypeSite, types: &Scoped) -> Result<String, askama::Error> {
     Ok(format!(
         "{ct}.lift.bind({ct})",
-        ct = ffi_converter_name(as_type, types)?
+        ct = ffi_converter_name(site, types)?
+    ))
+}
+
+/// Wrap the value of an argument or field being passed to Rust in a check that its integers
+/// fit into their Rust types, if `checkArguments` is on. The integers may be the value
+/// itself, or be in an optional, sequence or map. The `item` is the function or record that
+/// the value belongs to.
+///
+/// Anything else is passed through unchanged.
+pub(super) fn check_integer(
+    value: &str,
+    site: &impl TypeSite,
+    item: &str,
+    types: &Scoped,
+) -> Result<String, askama::Error> {
+    if !types.config.checks_arguments() {
+        return Ok(value.to_owned());
+    }
+    let repr = types.representation(types.int64_type(site.site_name()));
+    let Some(check) = integer_check(&types.as_type(site), repr, types) else {
+        return Ok(value.to_owned());
+    };
+    types.import_infra("uniffiCheckInteger", "ffi-converters");
+    let name = var_name(site.site_name().unwrap_or_default())?;
+    Ok(format!(
+        "uniffiCheckInteger({value}, {check}, \"{item}\", \"{name}\")"
     ))
 }
 
-pub fn render_literal(
+// The `UniffiIntegerCheck` for the integers in the type, if it has any to check.
+fn integer_check(type_: &Type, repr: Representation, types: &TypeRenderer) -> Option<String> {
+    Some(match type_ {
+        Type::Int8 => "\"i8\"".into(),
+        Type::Int16 => "\"i16\"".into(),
+        Type::Int32 => "\"i32\"".into(),
+        Type::Int64 => "\"i64\"".into(),
+        Type::UInt8 => "\"u8\"".into(),
+        Type::UInt16 => "\"u16\"".into(),
+        Type::UInt32 => "\"u32\"".into(),
+        Type::UInt64 => "\"u64\"".into(),
+        Type::Optional { inner_type } => {
+            format!(
+                "{{ optional: {} }}",
+                integer_check(inner_type, repr, types)?
+            )
+        }
+        // Typed arrays already wrap their items to fit.
+        Type::Sequence { inner_type } if typed_array(inner_type, repr).is_none() => {
+            format!(
+                "{{ sequence: {} }}",
+                integer_check(inner_type, repr, types)?
+            )
+        }
+        Type::Map {
+            key_type,
+            value_type,
+        } => {
+            let key = integer_check(key_type, repr, types).map(|k| format!("mapKey: {k}"));
+            let value = integer_check(value_type, repr, types).map(|v| format!("mapValue: {v}"));
+            let entries: Vec<_> = key.into_iter().chain(value).collect();
+            if entries.is_empty() {
+                return None;
+            }
+            format!("{{ {} }}", entries.join(", "))
+        }
+        Type::External { .. } => return integer_check(&types.as_type(type_), repr, types),
+        _ => return None,
+    })
+}
+
+pub(super) fn json_converter(
+    site: &impl TypeSite,
+    types: &Scoped,
+) -> Result<String, askama::Error> {
+    Ok(types.json_converter(site))
+}
+
+pub(super) fn render_literal(
     literal: &Literal,
-    as_ct: &impl AsType,
-    ci: &ComponentInterface,
+    site: &impl TypeSite,
+    types: &Scoped,
 ) -> Result<String, askama::Error> {
-    Ok(as_ct.as_codetype().literal(literal, ci))
+    Ok(types.code_type(site).literal(literal, types.ci))
 }
 
 pub fn variant_discr_literal(
@@ -27,5 +27,26 @@ macro_rules! impl_code_type_for_miscellany {
 }
 
 impl_code_type_for_miscellany!(TimestampCodeType, "UniffiTimestamp", "Timestamp");
+impl_code_type_for_miscellany!(
+    TimestampRoundedCodeType,
+    "UniffiTimestamp",
+    "TimestampRounded"
+);
+impl_code_type_for_miscellany!(TimestampExactCodeType, "UniffiTimestamp", "TimestampExact");
+impl_code_type_for_miscellany!(
+    TimestampMillisCodeType,
+    "UniffiTimestampMillis",
+    "TimestampMillis"
+);
+impl_code_type_for_miscellany!(
+    TimestampObjectCodeType,
+    "UniffiTimestampObject",
+    "TimestampObject"
+);
 
 impl_code_type_for_miscellany!(DurationCodeType, "UniffiDuration", "Duration");
+impl_code_type_for_miscellany!(
+    DurationObjectCodeType,
+    "UniffiDurationObject",
+    "DurationObject"
+);
@@ -17,16 +17,21 @@ mod record;
 
 use anyhow::{Context, Result};
 use askama::Template;
-use filters::{ffi_converter_name, type_name};
+use filters::{ffi_converter_name, type_name, TypeSite};
 use heck::ToUpperCamelCase;
-use oracle::CodeOracle;
+use oracle::{CodeOracle, CodeType, Representation};
 use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
-use uniffi_bindgen::interface::{AsType, Callable, FfiDefinition, FfiType, Type, UniffiTrait};
+use std::ops::Deref;
+use uniffi_bindgen::interface::{
+    AsType, Callable, CallbackInterface, Constructor, Enum, FfiDefinition, FfiType, Function,
+    Method, Object, Record, Type, UniffiTrait,
+};
 use uniffi_bindgen::ComponentInterface;
 
 use crate::bindings::metadata::ModuleMetadata;
+use crate::bindings::react_native::uniffi_toml::{DurationType, Int64Type, TimestampType};
 use crate::bindings::react_native::{
     ComponentInterfaceExt, FfiCallbackFunctionExt, FfiFunctionExt, FfiStructExt, ObjectExt,
 };
@@ -36,6 +41,7 @@ use crate::bindings::type_map::TypeMap;
 pub(crate) struct TsBindings {
     pub(crate) codegen: String,
     pub(crate) frontend: String,
+    pub(crate) api: String,
 }
 
 type Config = crate::bindings::react_native::uniffi_toml::TsConfig;
@@ -52,8 +58,15 @@ pub(crate) fn generate_bindings(
     let frontend = FrontendWrapper::new(ci, config, module, type_map)
         .render()
         .context("generating frontend javascript failed")?;
+    let api = ApiReport::new(ci, config, module, type_map)
+        .render_with_imports()
+        .context("generating API report failed")?;
 
-    Ok(TsBindings { codegen, frontend })
+    Ok(TsBindings {
+        codegen,
+        frontend,
+        api,
+    })
 }
 
 #[derive(Template)]
@@ -71,6 +84,159 @@ impl<'a> CodegenWrapper<'a> {
     }
 }
 
+/// Renders a declarations-only summary of the public API of the module.
+///
+/// Everything is sorted by name, so the output is stable between runs and
+/// diffs only when the API changes.
+#[derive(Template)]
+#[template(syntax = "ts", escape = "none", path = "ApiReport.d.ts")]
+struct ApiReport<'a> {
+    ci: &'a ComponentInterface,
+    config: &'a Config,
+    module: &'a ModuleMetadata,
+    renderer: TypeRenderer<'a>,
+    // The names used in the declarations, so that only those are imported.
+    used_names: BTreeSet<String>,
+}
+
+impl<'a> ApiReport<'a> {
+    fn new(
+        ci: &'a ComponentInterface,
+        config: &'a Config,
+        module: &'a ModuleMetadata,
+        type_map: &'a TypeMap,
+    ) -> Self {
+        let renderer = TypeRenderer::new(ci, config, module, type_map);
+        Self {
+            ci,
+            config,
+            module,
+            renderer,
+            used_names: Default::default(),
+        }
+    }
+
+    /// Render the report, importing the names from other modules which it uses, e.g. the
+    /// time types of the runtime, or the types of other crates.
+    ///
+    /// These are only known once the declarations have been rendered, so this renders twice.
+    fn render_with_imports(mut self) -> Result<String> {
+        // Rendering the helper code collects everything the generated bindings import.
+        self.renderer.render()?;
+        let declarations = self.render()?;
+        self.used_names = declarations
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Ok(self.render()?)
+    }
+
+    /// The imports of the generated bindings which the declarations use, by module.
+    fn imports(&self) -> Vec<(String, Vec<String>)> {
+        self.renderer
+            .imports
+            .borrow()
+            .iter()
+            .filter_map(|(file, things)| {
+                let things: Vec<_> = things
+                    .iter()
+                    .filter_map(|thing| match thing {
+                        Imported::TSType(name) if self.used_names.contains(name) => {
+                            Some(format!("type {name}"))
+                        }
+                        Imported::JSType(name) if self.used_names.contains(name) => {
+                            Some(name.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                (!things.is_empty()).then(|| (file.clone(), things))
+            })
+            .collect()
+    }
+
+    fn functions(&self) -> Vec<&Function> {
+        let mut functions: Vec<_> = self.ci.function_definitions().iter().collect();
+        functions.sort_by_key(|f| f.name());
+        functions
+    }
+
+    fn records(&self) -> Vec<&Record> {
+        let mut records: Vec<_> = self.ci.record_definitions().collect();
+        records.sort_by_key(|r| r.name());
+        records
+    }
+
+    fn enums(&self) -> Vec<&Enum> {
+        let mut enums: Vec<_> = self
+            .ci
+            .enum_definitions()
+            .filter(|e| !self.ci.is_name_used_as_error(e.name()))
+            .collect();
+        enums.sort_by_key(|e| e.name());
+        enums
+    }
+
+    fn errors(&self) -> Vec<&Enum> {
+        let mut errors: Vec<_> = self
+            .ci
+            .enum_definitions()
+            .filter(|e| self.ci.is_name_used_as_error(e.name()))
+            .collect();
+        errors.sort_by_key(|e| e.name());
+        errors
+    }
+
+    fn objects(&self) -> Vec<&Object> {
+        let mut objects: Vec<_> = self.ci.object_definitions().iter().collect();
+        objects.sort_by_key(|o| o.name());
+        objects
+    }
+
+    fn callback_interfaces(&self) -> Vec<&CallbackInterface> {
+        let mut callbacks: Vec<_> = self.ci.callback_interface_definitions().iter().collect();
+        callbacks.sort_by_key(|c| c.name());
+        callbacks
+    }
+
+    /// The name and Typescript type of each custom type, taking into account the
+    /// `type_name` from `uniffi.toml`.
+    fn custom_types(&self) -> Vec<(String, String)> {
+        let mut custom_types: Vec<_> = self
+            .ci
+            .iter_types()
+            .filter_map(|t| match t {
+                Type::Custom { name, builtin, .. } => {
+                    let types = self.renderer.scope(name);
+                    let concrete = self
+                        .config
+                        .custom_types
+                        .get(name.as_str())
+                        .and_then(|c| c.type_name.clone())
+                        .or_else(|| type_name(builtin.as_ref(), &types).ok())?;
+                    Some((type_name(t, &types).ok()?, concrete))
+                }
+                _ => None,
+            })
+            .collect();
+        custom_types.sort();
+        custom_types
+    }
+
+    fn sorted_methods<'m>(&self, methods: Vec<&'m Method>) -> Vec<&'m Method> {
+        let mut methods = methods;
+        methods.sort_by_key(|m| m.name());
+        methods
+    }
+
+    fn sorted_constructors<'c>(&self, constructors: Vec<&'c Constructor>) -> Vec<&'c Constructor> {
+        let mut constructors = constructors;
+        constructors.sort_by_key(|c| c.name());
+        constructors
+    }
+}
+
 #[derive(Template)]
 #[template(syntax = "ts", escape = "none", path = "wrapper.ts")]
 struct FrontendWrapper<'a> {
@@ -130,6 +296,9 @@ pub struct TypeRenderer<'a> {
 
     // The universe of types outside of this module. For tracking external types.
     type_map: &'a TypeMap,
+
+    // The representations of 64-bit integers used for each type which contains them.
+    int64_uses: BTreeMap<Type, BTreeSet<Int64Type>>,
 }
 
 impl<'a> TypeRenderer<'a> {
@@ -147,6 +316,7 @@ impl<'a> TypeRenderer<'a> {
             exported_converters: RefCell::new(Default::default()),
             imported_converters: RefCell::new(Default::default()),
             type_map,
+            int64_uses: int64_uses(ci, config),
         }
     }
 
@@ -194,6 +364,16 @@ impl<'a> TypeRenderer<'a> {
     fn add_import(&self, what: Imported, from: &str) -> &str {
         let mut map = self.imports.borrow_mut();
         let set = map.entry(from.to_owned()).or_default();
+        // Importing the value also imports the type of the same name.
+        match &what {
+            Imported::JSType(name) => {
+                set.remove(&Imported::TSType(name.clone()));
+            }
+            Imported::TSType(name) if set.contains(&Imported::JSType(name.clone())) => {
+                return "";
+            }
+            _ => (),
+        }
         set.insert(what);
         ""
     }
@@ -202,7 +382,9 @@ impl<'a> TypeRenderer<'a> {
         match external.as_type() {
             Type::External { namespace, .. } => {
                 let type_ = self.as_type(external);
-                let name = type_name(&type_, self).expect("External types should have type names");
+                let types = self.type_scope(&type_);
+                let name =
+                    type_name(&type_, &types).expect("External types should have type names");
                 match &type_ {
                     Type::Enum { .. } => self.import_ext(&name, &namespace),
                     Type::CallbackInterface { .. }
@@ -211,7 +393,7 @@ impl<'a> TypeRenderer<'a> {
                     | Type::Record { .. } => self.import_ext_type(&name, &namespace),
                     _ => unreachable!(),
                 };
-                let ffi_converter_name = ffi_converter_name(&type_, self)
+                let ffi_converter_name = ffi_converter_name(&type_, &types)
                     .expect("FfiConverter for External type will always exist");
                 self.import_converter(ffi_converter_name, &namespace)
             }
@@ -248,6 +430,297 @@ impl<'a> TypeRenderer<'a> {
     pub(crate) fn as_type(&self, as_type: &impl AsType) -> Type {
         self.type_map.as_type(as_type)
     }
+
+    // The renderer for the item being rendered, so that the fields, arguments and return
+    // values within it get the right representation of 64-bit integers, e.g.
+    // `{%- let types = self.member_scope(name, meth.name()) %}`.
+    fn scope(&self, item: &str) -> Scoped<'_, 'a> {
+        Scoped::new(self, vec![item.to_owned()])
+    }
+
+    fn member_scope(&self, item: &str, member: &str) -> Scoped<'_, 'a> {
+        Scoped::new(self, vec![item.to_owned(), member.to_owned()])
+    }
+
+    // The renderer for the named type, e.g. a record or custom type, or one without a scope
+    // for anything else.
+    fn type_scope(&self, type_: &Type) -> Scoped<'_, 'a> {
+        let scope = match type_ {
+            Type::Record { name, .. }
+            | Type::Enum { name, .. }
+            | Type::Object { name, .. }
+            | Type::CallbackInterface { name, .. }
+            | Type::Custom { name, .. } => vec![name.clone()],
+            _ => Vec::new(),
+        };
+        Scoped::new(self, scope)
+    }
+
+    fn representation(&self, int64: Int64Type) -> Representation {
+        Representation {
+            int64,
+            typed_arrays: self.config.typed_arrays,
+            timestamp: self.config.timestamp,
+            timestamp_precision: self.config.timestamp_precision,
+            duration: self.config.duration,
+        }
+    }
+
+    // Typed arrays have their converters in the runtime, instead of in the generated code.
+    fn is_typed_array(&self, type_: &Type, int64: &Int64Type) -> bool {
+        match type_ {
+            Type::Sequence { inner_type } => {
+                compounds::typed_array(inner_type, self.representation(*int64)).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn json_converter_as(&self, type_: &Type, repr: Representation) -> String {
+        let ci = self.ci;
+        match type_ {
+            Type::UInt8
+            | Type::Int8
+            | Type::UInt16
+            | Type::Int16
+            | Type::UInt32
+            | Type::Int32
+            | Type::Boolean
+            | Type::String => "uniffiJson.identity".into(),
+            Type::Float32 | Type::Float64 => "uniffiJson.number".into(),
+            Type::UInt64 | Type::Int64 => match repr.int64 {
+                Int64Type::Bigint => "uniffiJson.bigint".into(),
+                Int64Type::Number => "uniffiJson.identity".into(),
+            },
+            Type::Bytes => "uniffiJson.bytes".into(),
+            Type::Timestamp => match repr.timestamp {
+                TimestampType::Date => "uniffiJson.date".into(),
+                TimestampType::Millis => "uniffiJson.number".into(),
+                TimestampType::Object => "uniffiJson.time".into(),
+            },
+            Type::Duration => match repr.duration {
+                DurationType::Millis => "uniffiJson.number".into(),
+                DurationType::Object => "uniffiJson.time".into(),
+            },
+            Type::Optional { inner_type } => format!(
+                "uniffiJson.optional({})",
+                self.json_converter_as(inner_type, repr)
+            ),
+            Type::Sequence { inner_type } => {
+                let item = self.json_converter_as(inner_type, repr);
+                match compounds::typed_array(inner_type, repr) {
+                    Some(array_type) => format!("uniffiJson.typedArray({array_type}, {item})"),
+                    None => format!("uniffiJson.array({item})"),
+                }
+            }
+            Type::Map {
+                key_type,
+                value_type,
+            } => format!(
+                "uniffiJson.map({}, {})",
+                self.json_converter_as(key_type, repr),
+                self.json_converter_as(value_type, repr)
+            ),
+            // The converters for the fields of each record and tagged enum are declared
+            // in the order of the types, which doesn't order them by their fields.
+            Type::Record { .. } => format!(
+                "uniffiJson.lazy(() => {})",
+                CodeOracle.find(type_).decl_type_label(ci)
+            ),
+            Type::Enum { name, .. } => {
+                let decl_type_name = CodeOracle.find(type_).decl_type_label(ci);
+                match ci.get_enum_definition(name) {
+                    Some(e) if !e.is_flat() => format!("uniffiJson.lazy(() => {decl_type_name})"),
+                    Some(_) if ci.is_name_used_as_error(name) => {
+                        format!("uniffiJson.unsupported(\"{decl_type_name}\")")
+                    }
+                    Some(_) => "uniffiJson.identity".into(),
+                    // From another crate, so whether it is flat is only known at runtime.
+                    None => format!("uniffiJson.enumeration({decl_type_name})"),
+                }
+            }
+            Type::Object { .. } | Type::CallbackInterface { .. } => format!(
+                "uniffiJson.unsupported(\"{}\")",
+                CodeOracle.find(type_).type_label(ci)
+            ),
+            Type::Custom { name, builtin, .. } => {
+                let builtin = self.json_converter_as(builtin, repr);
+                match self.config.custom_types.get(name) {
+                    None => builtin,
+                    Some(config) => format!(
+                        "uniffiJson.custom({builtin}, (intermediate) => {}, (value) => {})",
+                        config.into_custom.render("intermediate"),
+                        config.from_custom.render("value"),
+                    ),
+                }
+            }
+            Type::External { namespace, .. } => {
+                let type_ = self.as_type(type_);
+                if matches!(type_, Type::Record { .. } | Type::Enum { .. }) {
+                    // Only the type of a record is imported for its signatures.
+                    let decl_type_name = CodeOracle.find(&type_).decl_type_label(ci);
+                    self.import_ext(&decl_type_name, namespace);
+                }
+                self.json_converter_as(&type_, repr)
+            }
+        }
+    }
+
+    // The representations of 64-bit integers that the converters for this type are needed in.
+    //
+    // The converters for optionals, sequences and maps of 64-bit integers are declared once
+    // for each representation that the fields, arguments and return values of that type
+    // resolve to; everything else only has one converter.
+    fn int64_types(&self, type_: &Type) -> Vec<Int64Type> {
+        match self.int64_uses.get(type_) {
+            Some(uses) => uses.iter().copied().collect(),
+            None => vec![self.config.int64],
+        }
+    }
+}
+
+// Which representations of 64-bit integers each type containing them is used in, found by
+// resolving every field, argument and return value in its scope.
+fn int64_uses(ci: &ComponentInterface, config: &Config) -> BTreeMap<Type, BTreeSet<Int64Type>> {
+    fn add(uses: &mut BTreeMap<Type, BTreeSet<Int64Type>>, type_: &Type, int64: Int64Type) {
+        let inner: Vec<&Type> = match type_ {
+            Type::Int64 | Type::UInt64 => vec![],
+            Type::Optional { inner_type } | Type::Sequence { inner_type } => vec![inner_type],
+            Type::Map {
+                key_type,
+                value_type,
+            } => vec![key_type, value_type],
+            _ => return,
+        };
+        for t in inner {
+            add(uses, t, int64);
+        }
+        if has_int64(type_) {
+            uses.entry(type_.clone()).or_default().insert(int64);
+        }
+    }
+    fn has_int64(type_: &Type) -> bool {
+        match type_ {
+            Type::Int64 | Type::UInt64 => true,
+            Type::Optional { inner_type } | Type::Sequence { inner_type } => has_int64(inner_type),
+            Type::Map {
+                key_type,
+                value_type,
+            } => has_int64(key_type) || has_int64(value_type),
+            _ => false,
+        }
+    }
+
+    let mut uses = BTreeMap::new();
+    let mut add_site = |path: &[&str], site: Option<&str>, type_: &Type| {
+        let path: Vec<_> = path.iter().copied().chain(site).collect();
+        add(&mut uses, type_, config.int64_type(&path));
+    };
+    let callables = ci
+        .function_definitions()
+        .iter()
+        .map(|func| (vec![func.name()], func as &dyn Callable))
+        .chain(ci.object_definitions().iter().flat_map(|obj| {
+            let constructors = obj
+                .constructors()
+                .into_iter()
+                .map(|cons| (vec![obj.name(), cons.name()], cons as &dyn Callable));
+            let methods = obj
+                .methods()
+                .into_iter()
+                .map(|meth| (vec![obj.name(), meth.name()], meth as &dyn Callable));
+            let hash = obj.uniffi_traits().into_iter().filter_map(|tm| match tm {
+                UniffiTrait::Hash { hash } => Some((vec![obj.name()], hash as &dyn Callable)),
+                _ => None,
+            });
+            constructors.chain(methods).chain(hash)
+        }))
+        .chain(ci.callback_interface_definitions().iter().flat_map(|cbi| {
+            cbi.methods()
+                .into_iter()
+                .map(|meth| (vec![cbi.name(), meth.name()], meth as &dyn Callable))
+        }));
+    for (path, callable) in callables {
+        for arg in callable.arguments() {
+            add_site(&path, Some(arg.name()), &arg.as_type());
+        }
+        if let Some(return_type) = callable.return_type() {
+            add_site(&path, None, &return_type);
+        }
+    }
+    for rec in ci.record_definitions() {
+        for field in rec.fields() {
+            add_site(&[rec.name()], Some(field.name()), &field.as_type());
+        }
+    }
+    for e in ci.enum_definitions() {
+        for variant in e.variants() {
+            for field in variant.fields() {
+                add_site(&[e.name()], Some(field.name()), &field.as_type());
+            }
+        }
+    }
+    for type_ in ci.iter_types() {
+        if let Type::Custom { name, builtin, .. } = type_ {
+            add_site(&[name], None, builtin);
+        }
+    }
+    uses
+}
+
+/// The [`TypeRenderer`] for one item being rendered, e.g. a function, method or record.
+///
+/// The fields, arguments and return values within the item are looked up in
+/// `int64Overrides` under its path, e.g. `["MyObject", "my_method"]`.
+pub(crate) struct Scoped<'r, 'a> {
+    types: &'r TypeRenderer<'a>,
+    path: Vec<String>,
+}
+
+impl<'r, 'a> Scoped<'r, 'a> {
+    fn new(types: &'r TypeRenderer<'a>, path: Vec<String>) -> Self {
+        Self { types, path }
+    }
+
+    // The name of the function or method being rendered, as it is in Typescript, e.g.
+    // `myFunction` or `MyObject.myMethod`.
+    fn callable_label(&self, callable: &str) -> String {
+        let name = CodeOracle.fn_name(callable);
+        match self.path.as_slice() {
+            [item, _] => format!("{}.{name}", CodeOracle.class_name(self.ci, item)),
+            _ => name,
+        }
+    }
+
+    fn int64_type(&self, site: Option<&str>) -> Int64Type {
+        let path: Vec<_> = self.path.iter().map(String::as_str).chain(site).collect();
+        self.config.int64_type(&path)
+    }
+
+    fn code_type(&self, site: &impl TypeSite) -> Box<dyn CodeType> {
+        let type_ = self.as_type(site);
+        let int64 = self.int64_type(site.site_name());
+        CodeOracle.find_as(&type_, self.representation(int64))
+    }
+
+    // The expression for the `UniffiJsonConverter` of the type at this site, used by the
+    // `toJSON`, `fromJSON` and `equals` of records and tagged enums.
+    //
+    // These refer to the factories of other records and enums, so must only be evaluated
+    // once everything has been declared, i.e. inside a function.
+    fn json_converter(&self, site: &impl TypeSite) -> String {
+        let int64 = self.int64_type(site.site_name());
+        self.import_infra("uniffiJson", "json");
+        self.json_converter_as(&site.as_type(), self.representation(int64))
+    }
+}
+
+impl<'a> Deref for Scoped<'_, 'a> {
+    type Target = TypeRenderer<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.types
+    }
 }
 
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -255,3 +728,82 @@ enum Imported {
     TSType(String),
     JSType(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int64_uses() -> Result<()> {
+        let ci = ComponentInterface::from_webidl(
+            r#"
+            namespace my_crate {
+                sequence<i64>? my_function(i64? my_arg);
+                i64 my_bigint_function(i64 my_arg);
+            };
+            dictionary MyRecord {
+                sequence<i64> my_field;
+            };
+            "#,
+            "my_crate",
+        )?;
+        let config: Config = toml::from_str(
+            r#"
+            int64 = "number"
+
+            [int64Overrides]
+            my_bigint_function = "bigint"
+            "MyRecord.my_field" = "bigint"
+            "#,
+        )?;
+        let uses = int64_uses(&ci, &config);
+
+        let i64 = Type::Int64;
+        let optional = Type::Optional {
+            inner_type: Box::new(i64.clone()),
+        };
+        let sequence = Type::Sequence {
+            inner_type: Box::new(i64.clone()),
+        };
+        let optional_sequence = Type::Optional {
+            inner_type: Box::new(sequence.clone()),
+        };
+        let number = BTreeSet::from([Int64Type::Number]);
+        let both = BTreeSet::from([Int64Type::Bigint, Int64Type::Number]);
+        assert_eq!(uses.get(&i64), Some(&both));
+        assert_eq!(uses.get(&optional), Some(&number));
+        assert_eq!(uses.get(&sequence), Some(&both));
+        assert_eq!(uses.get(&optional_sequence), Some(&number));
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_report_imports() -> Result<()> {
+        let ci = ComponentInterface::from_webidl(
+            r#"
+            namespace my_crate {
+                timestamp now();
+                MyEnum my_function(duration my_arg);
+            };
+            [Enum]
+            interface MyEnum {
+                A(u32 value);
+                B();
+            };
+            "#,
+            "my_crate",
+        )?;
+        let config: Config = toml::from_str(r#"timestamp = "millis""#)?;
+        let module = ModuleMetadata::new("my_crate");
+        let type_map = TypeMap::default();
+        let api = ApiReport::new(&ci, &config, &module, &type_map).render_with_imports()?;
+
+        // Only the names used by the declarations are imported, not the converters.
+        assert!(api.contains(
+            "import { type UniffiDuration, type UniffiTimestampMillis, UniffiEnum } \
+            from \"uniffi-bindgen-react-native\";"
+        ));
+        assert!(!api.contains("FfiConverter"));
+        Ok(())
+    }
+}
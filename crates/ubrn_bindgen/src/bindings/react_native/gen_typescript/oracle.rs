@@ -11,12 +11,98 @@ use uniffi_bindgen::{
 };
 
 use super::*;
+use crate::bindings::react_native::uniffi_toml::{
+    DurationType, Int64Type, TimestampPrecision, TimestampType,
+};
 
 pub(crate) struct CodeOracle;
 
+/// The choices made in `uniffi.toml` about how types are represented in Typescript.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Representation {
+    pub(crate) int64: Int64Type,
+    /// Sequences of fixed-width numbers are typed arrays, e.g. `Float32Array`.
+    pub(crate) typed_arrays: bool,
+    pub(crate) timestamp: TimestampType,
+    pub(crate) timestamp_precision: TimestampPrecision,
+    pub(crate) duration: DurationType,
+}
+
 impl CodeOracle {
     pub(crate) fn find(&self, type_: &Type) -> Box<dyn CodeType> {
-        type_.clone().as_type().as_codetype()
+        self.find_as(type_, Representation::default())
+    }
+
+    /// Find the code type, with the types in it represented as `repr`.
+    pub(crate) fn find_as(&self, type_: &Type, repr: Representation) -> Box<dyn CodeType> {
+        // Map `Type` instances to a `Box<dyn CodeType>` for that type.
+        //
+        // There is a companion match in `templates/Types.kt` which performs a similar function for the
+        // template code.
+        //
+        //   - When adding additional types here, make sure to also add a match arm to the `Types.kt` template.
+        //   - To keep things manageable, let's try to limit ourselves to these 2 mega-matches
+        match type_.clone() {
+            Type::UInt8 => Box::new(primitives::UInt8CodeType),
+            Type::Int8 => Box::new(primitives::Int8CodeType),
+            Type::UInt16 => Box::new(primitives::UInt16CodeType),
+            Type::Int16 => Box::new(primitives::Int16CodeType),
+            Type::UInt32 => Box::new(primitives::UInt32CodeType),
+            Type::Int32 => Box::new(primitives::Int32CodeType),
+            Type::UInt64 => match repr.int64 {
+                Int64Type::Bigint => Box::new(primitives::UInt64CodeType),
+                Int64Type::Number => Box::new(primitives::UInt64AsNumberCodeType),
+            },
+            Type::Int64 => match repr.int64 {
+                Int64Type::Bigint => Box::new(primitives::Int64CodeType),
+                Int64Type::Number => Box::new(primitives::Int64AsNumberCodeType),
+            },
+            Type::Float32 => Box::new(primitives::Float32CodeType),
+            Type::Float64 => Box::new(primitives::Float64CodeType),
+            Type::Boolean => Box::new(primitives::BooleanCodeType),
+            Type::String => Box::new(primitives::StringCodeType),
+            Type::Bytes => Box::new(primitives::BytesCodeType),
+
+            Type::Timestamp => match (repr.timestamp, repr.timestamp_precision) {
+                (TimestampType::Date, TimestampPrecision::Truncate) => {
+                    Box::new(miscellany::TimestampCodeType)
+                }
+                (TimestampType::Date, TimestampPrecision::Round) => {
+                    Box::new(miscellany::TimestampRoundedCodeType)
+                }
+                (TimestampType::Date, TimestampPrecision::Error) => {
+                    Box::new(miscellany::TimestampExactCodeType)
+                }
+                (TimestampType::Millis, _) => Box::new(miscellany::TimestampMillisCodeType),
+                (TimestampType::Object, _) => Box::new(miscellany::TimestampObjectCodeType),
+            },
+            Type::Duration => match repr.duration {
+                DurationType::Millis => Box::new(miscellany::DurationCodeType),
+                DurationType::Object => Box::new(miscellany::DurationObjectCodeType),
+            },
+
+            Type::Enum { name, .. } => Box::new(enum_::EnumCodeType::new(name)),
+            Type::Object { name, imp, .. } => Box::new(object::ObjectCodeType::new(name, imp)),
+            Type::Record { name, .. } => Box::new(record::RecordCodeType::new(name)),
+            Type::CallbackInterface { name, .. } => {
+                Box::new(callback_interface::CallbackInterfaceCodeType::new(name))
+            }
+            Type::Optional { inner_type } => {
+                Box::new(compounds::OptionalCodeType::new(*inner_type, repr))
+            }
+            Type::Sequence { inner_type } => match compounds::typed_array(&inner_type, repr) {
+                Some(name) => Box::new(compounds::TypedArrayCodeType::new(name)),
+                None => Box::new(compounds::SequenceCodeType::new(*inner_type, repr)),
+            },
+            Type::Map {
+                key_type,
+                value_type,
+            } => Box::new(compounds::MapCodeType::new(*key_type, *value_type, repr)),
+            Type::External { .. } => unreachable!(
+                "External types should have been elimintated by going through the TypeRenderer::as_type() method"
+            ),
+            Type::Custom { name, .. } => Box::new(custom::CustomCodeType::new(name)),
+        }
     }
 
     /// Get the idiomatic Typescript rendering of a class name (for enums, records, errors, etc).
@@ -140,52 +226,7 @@ pub(crate) trait AsCodeType {
 
 impl<T: AsType> AsCodeType for T {
     fn as_codetype(&self) -> Box<dyn CodeType> {
-        // Map `Type` instances to a `Box<dyn CodeType>` for that type.
-        //
-        // There is a companion match in `templates/Types.kt` which performs a similar function for the
-        // template code.
-        //
-        //   - When adding additional types here, make sure to also add a match arm to the `Types.kt` template.
-        //   - To keep things manageable, let's try to limit ourselves to these 2 mega-matches
-        match self.as_type() {
-            Type::UInt8 => Box::new(primitives::UInt8CodeType),
-            Type::Int8 => Box::new(primitives::Int8CodeType),
-            Type::UInt16 => Box::new(primitives::UInt16CodeType),
-            Type::Int16 => Box::new(primitives::Int16CodeType),
-            Type::UInt32 => Box::new(primitives::UInt32CodeType),
-            Type::Int32 => Box::new(primitives::Int32CodeType),
-            Type::UInt64 => Box::new(primitives::UInt64CodeType),
-            Type::Int64 => Box::new(primitives::Int64CodeType),
-            Type::Float32 => Box::new(primitives::Float32CodeType),
-            Type::Float64 => Box::new(primitives::Float64CodeType),
-            Type::Boolean => Box::new(primitives::BooleanCodeType),
-            Type::String => Box::new(primitives::StringCodeType),
-            Type::Bytes => Box::new(primitives::BytesCodeType),
-
-            Type::Timestamp => Box::new(miscellany::TimestampCodeType),
-            Type::Duration => Box::new(miscellany::DurationCodeType),
-
-            Type::Enum { name, .. } => Box::new(enum_::EnumCodeType::new(name)),
-            Type::Object { name, imp, .. } => Box::new(object::ObjectCodeType::new(name, imp)),
-            Type::Record { name, .. } => Box::new(record::RecordCodeType::new(name)),
-            Type::CallbackInterface { name, .. } => {
-                Box::new(callback_interface::CallbackInterfaceCodeType::new(name))
-            }
-            Type::Optional { inner_type } => {
-                Box::new(compounds::OptionalCodeType::new(*inner_type))
-            }
-            Type::Sequence { inner_type } => {
-                Box::new(compounds::SequenceCodeType::new(*inner_type))
-            }
-            Type::Map {
-                key_type,
-                value_type,
-            } => Box::new(compounds::MapCodeType::new(*key_type, *value_type)),
-            Type::External { .. } => unreachable!(
-                "External types should have been elimintated by going through the TypeRenderer::as_type() method"
-            ),
-            Type::Custom { name, .. } => Box::new(custom::CustomCodeType::new(name)),
-        }
+        CodeOracle.find(&self.as_type())
     }
 }
 
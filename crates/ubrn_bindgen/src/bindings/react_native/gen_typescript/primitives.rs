@@ -4,6 +4,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 use super::oracle::CodeType;
+use crate::bindings::react_native::uniffi_toml::Int64Type;
 use paste::paste;
 use uniffi_bindgen::{
     backend::{Literal, Type},
@@ -11,8 +12,8 @@ use uniffi_bindgen::{
     ComponentInterface,
 };
 
-fn render_literal(literal: &Literal, _ci: &ComponentInterface) -> String {
-    fn typed_number(type_: &Type, num_str: String) -> String {
+fn render_literal(literal: &Literal, int64: Int64Type, _ci: &ComponentInterface) -> String {
+    let typed_number = |type_: &Type, num_str: String| -> String {
         let unwrapped_type = match type_ {
             Type::Optional { inner_type } => inner_type,
             t => t,
@@ -20,6 +21,7 @@ fn render_literal(literal: &Literal, _ci: &ComponentInterface) -> String {
         match unwrapped_type {
             // Bytes, Shorts and Ints can all be inferred from the type.
             Type::Int8 | Type::Int16 | Type::Int32 => num_str,
+            Type::Int64 | Type::UInt64 if int64 == Int64Type::Number => num_str,
             Type::Int64 => format!("BigInt(\"{num_str}\")"),
 
             Type::UInt8 | Type::UInt16 | Type::UInt32 => num_str,
@@ -28,7 +30,7 @@ fn render_literal(literal: &Literal, _ci: &ComponentInterface) -> String {
             Type::Float32 | Type::Float64 => num_str,
             _ => panic!("Unexpected literal: {num_str} for type: {type_:?}"),
         }
-    }
+    };
 
     match literal {
         Literal::Boolean(v) => format!("{v}"),
@@ -57,6 +59,9 @@ fn render_literal(literal: &Literal, _ci: &ComponentInterface) -> String {
 
 macro_rules! impl_code_type_for_primitive {
     ($T:ty, $canonical_name:literal, $class_name:literal) => {
+        impl_code_type_for_primitive!($T, $canonical_name, $class_name, Int64Type::Bigint);
+    };
+    ($T:ty, $canonical_name:literal, $class_name:literal, $int64:expr) => {
         paste! {
             #[derive(Debug)]
             pub struct $T;
@@ -71,7 +76,7 @@ macro_rules! impl_code_type_for_primitive {
                 }
 
                 fn literal(&self, literal: &Literal, ci: &ComponentInterface) -> String {
-                    render_literal(&literal, ci)
+                    render_literal(&literal, $int64, ci)
                 }
             }
         }
@@ -89,5 +94,17 @@ impl_code_type_for_primitive!(UInt8CodeType, "UInt8", "/*u8*/number");
 impl_code_type_for_primitive!(UInt16CodeType, "UInt16", "/*u16*/number");
 impl_code_type_for_primitive!(UInt32CodeType, "UInt32", "/*u32*/number");
 impl_code_type_for_primitive!(UInt64CodeType, "UInt64", "/*u64*/bigint");
+impl_code_type_for_primitive!(
+    Int64AsNumberCodeType,
+    "Int64AsNumber",
+    "/*i64*/number",
+    Int64Type::Number
+);
+impl_code_type_for_primitive!(
+    UInt64AsNumberCodeType,
+    "UInt64AsNumber",
+    "/*u64*/number",
+    Int64Type::Number
+);
 impl_code_type_for_primitive!(Float32CodeType, "Float32", "/*f32*/number");
 impl_code_type_for_primitive!(Float64CodeType, "Float64", "/*f64*/number");
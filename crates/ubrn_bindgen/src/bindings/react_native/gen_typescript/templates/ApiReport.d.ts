@@ -0,0 +1,226 @@
+{%- import "macros.ts" as ts -%}
+// This file was autogenerated by some hot garbage in the `uniffi-bindgen-react-native` crate.
+// Trust me, you don't want to mess with it!
+//
+// API report for the `{{ ci.namespace() }}` module, generated alongside `{{ module.ts_filename() }}`.
+// It contains only declarations, sorted by name, so that changes to the public API
+// show up clearly in code review. It is not imported by the generated bindings.
+{%- for (file, things) in self.imports() %}
+import { {{ things|join(", ") }} } from "{{ file }}";
+{%- endfor %}
+
+{%- macro arg_list(func) %}
+    {%- for arg in func.arguments() -%}
+        {{ arg.name()|var_name }}
+        {%- if arg.default_value().is_some() %}?{% endif %}: {{ arg|type_name(types) -}}
+        {%- if !loop.last %}, {% endif -%}
+    {%- endfor %}
+    {%- if func.is_async() %}
+    {%-   if !func.arguments().is_empty() %}, {% endif -%}
+    asyncOpts_?: { signal: AbortSignal }
+    {%- endif %}
+{%- endmacro %}
+
+{%- macro return_type(callable) %}
+    {%- if callable.is_async() %}Promise<{% endif %}
+    {%- match callable.return_type() %}
+    {%-  when Some with (return_type) %}{{ return_type|type_name(types) }}
+    {%-  when None %}void
+    {%- endmatch %}
+    {%- if callable.is_async() %}>{% endif %}
+{%- endmacro %}
+
+{%- macro throws(callable) %}
+    {%- match callable.throws_type() %}
+    {%-  when Some with (e) %} /*throws {{ e|type_name(types) }}*/
+    {%-  when None %}
+    {%- endmatch %}
+{%- endmacro %}
+
+{%- macro fields(item, indent, pad) %}
+    {%- for field in item.fields() %}
+    {%-   call ts::docstring(field, indent) %}
+{{ pad }}{{ field.name()|var_name }}
+    {%- if field.default_value().is_some() %}?{% endif %}: {{ field|type_name(types) }};
+    {%- endfor %}
+{%- endmacro %}
+
+{%- macro variant_classes(e, superclass, is_error) %}
+export declare namespace {{ e|decl_type_name(types) }} {
+    {%- for variant in e.variants() %}
+    {%- call ts::docstring(variant, 4) %}
+    class {{ variant.name()|class_name(ci) }} extends {{ superclass }} {
+        readonly tag: "{{ variant.name() }}";
+        {%- if is_error && e.is_flat() %}
+        constructor(message: string);
+        {%- else if variant.has_nameless_fields() %}
+        readonly inner: Readonly<[
+        {%- for field in variant.fields() %}
+        {{- field|type_name(types) }}
+        {%-   if !loop.last %}, {% endif %}
+        {%- endfor -%}
+        ]>;
+        {%- else if variant.has_fields() %}
+        readonly inner: Readonly<{
+        {%- call fields(variant, 12, "            ") %}
+        }>;
+        {%- endif %}
+    }
+    {%- endfor %}
+}
+{%- endmacro %}
+
+{%- let functions = self.functions() %}
+{%- if !functions.is_empty() %}
+
+// Functions
+{%- for func in functions %}
+{%- let types = renderer.scope(func.name()) %}
+{% call ts::docstring(func, 0) %}
+export declare function {{ func.name()|fn_name }}({% call arg_list(func) %}): {% call return_type(func) %}{% call throws(func) %};
+{%- endfor %}
+{%- endif %}
+
+{%- let records = self.records() %}
+{%- if !records.is_empty() %}
+
+// Records
+{%- for rec in records %}
+{%- let types = renderer.scope(rec.name()) %}
+{% call ts::docstring(rec, 0) %}
+export declare type {{ rec|type_name(types) }} = {
+{%- call fields(rec, 4, "    ") %}
+};
+{%- endfor %}
+{%- endif %}
+
+{%- let enums = self.enums() %}
+{%- if !enums.is_empty() %}
+
+// Enums
+{%- for e in enums %}
+{%- let types = renderer.scope(e.name()) %}
+{% call ts::docstring(e, 0) %}
+{%- if e.is_flat() %}
+export declare enum {{ e|type_name(types) }} {
+    {%- for variant in e.variants() %}
+    {%- call ts::docstring(variant, 4) %}
+    {{ variant|variant_name }}
+    {%- match e.variant_discr_type() %}
+    {%- when Some with (_) %} = {{ e|variant_discr_literal(loop.index0, ci) }}
+    {%- else %}{% endmatch %},
+    {%- endfor %}
+}
+{%- else %}
+{%- call variant_classes(e, "UniffiEnum", false) %}
+export declare type {{ e|type_name(types) }} = {# space #}
+{%- for variant in e.variants() %}
+{{- e|decl_type_name(types) }}.{{ variant.name()|class_name(ci) }}
+{%- if !loop.last %} | {% endif %}
+{%- endfor %};
+{%- endif %}
+{%- endfor %}
+{%- endif %}
+
+{%- let errors = self.errors() %}
+{%- if !errors.is_empty() %}
+
+// Errors
+{%- for e in errors %}
+{%- let types = renderer.scope(e.name()) %}
+{% call ts::docstring(e, 0) %}
+{%- call variant_classes(e, "Error", true) %}
+export declare type {{ e|type_name(types) }} = {# space #}
+{%- for variant in e.variants() %}
+{{- e|decl_type_name(types) }}.{{ variant.name()|class_name(ci) }}
+{%- if !loop.last %} | {% endif %}
+{%- endfor %};
+{%- endfor %}
+{%- endif %}
+
+{%- let objects = self.objects() %}
+{%- if !objects.is_empty() %}
+
+// Objects
+{%- for obj in objects %}
+{%- let types = renderer.scope(obj.name()) %}
+{%- let protocol_name = obj|type_name(types) %}
+{%- let impl_class_name = obj|decl_type_name(types) %}
+{% call ts::docstring(obj, 0) %}
+export declare interface {{ protocol_name }} {
+    {%- for meth in self.sorted_methods(obj.methods()) %}
+    {%- let types = renderer.member_scope(obj.name(), meth.name()) %}
+    {%- call ts::docstring(meth, 4) %}
+    {{ meth.name()|fn_name }}({% call arg_list(meth) %}): {% call return_type(meth) %}{% call throws(meth) %};
+    {%- endfor %}
+}
+{% call ts::docstring(obj, 0) %}
+export declare class {{ impl_class_name }} implements {{ protocol_name }} {
+    {%- match obj.primary_constructor() %}
+    {%- when Some with (cons) %}
+    {%- let types = renderer.member_scope(obj.name(), cons.name()) %}
+    {%- call ts::docstring(cons, 4) %}
+    {%- if cons.is_async() %}
+    static new({% call arg_list(cons) %}): Promise<{{ impl_class_name }}>{% call throws(cons) %};
+    {%- else %}
+    constructor({% call arg_list(cons) %}){% call throws(cons) %};
+    {%- endif %}
+    {%- when None %}
+    private constructor();
+    {%- endmatch %}
+    {%- for cons in self.sorted_constructors(obj.alternate_constructors()) %}
+    {%- let types = renderer.member_scope(obj.name(), cons.name()) %}
+    {%- call ts::docstring(cons, 4) %}
+    static {{ cons.name()|fn_name }}({% call arg_list(cons) %}): {% if cons.is_async() %}Promise<{{ impl_class_name }}>{% else %}{{ impl_class_name }}{% endif %}{% call throws(cons) %};
+    {%- endfor %}
+    {%- for meth in self.sorted_methods(obj.methods()) %}
+    {%- let types = renderer.member_scope(obj.name(), meth.name()) %}
+    {%- call ts::docstring(meth, 4) %}
+    {{ meth.name()|fn_name }}({% call arg_list(meth) %}): {% call return_type(meth) %}{% call throws(meth) %};
+    {%- endfor %}
+    {%- if obj.has_uniffi_trait("Display") || obj.has_uniffi_trait("Debug") %}
+    toString(): string;
+    {%- endif %}
+    {%- if obj.has_uniffi_trait("Debug") %}
+    toDebugString(): string;
+    {%- endif %}
+    {%- if obj.has_uniffi_trait("Eq") %}
+    equals(other: {{ impl_class_name }}): boolean;
+    {%- endif %}
+    {%- if obj.has_uniffi_trait("Hash") %}
+    {%- let types = renderer.scope(obj.name()) %}
+    {%- let hash_type = Type::UInt64 %}
+    hashCode(): {{ hash_type|type_name(types) }};
+    {%- endif %}
+    uniffiDestroy(): void;
+    static instanceOf(obj: any): obj is {{ impl_class_name }};
+}
+{%- endfor %}
+{%- endif %}
+
+{%- let callbacks = self.callback_interfaces() %}
+{%- if !callbacks.is_empty() %}
+
+// Callback interfaces
+{%- for cbi in callbacks %}
+{%- let types = renderer.scope(cbi.name()) %}
+{% call ts::docstring(cbi, 0) %}
+export declare interface {{ cbi|type_name(types) }} {
+    {%- for meth in self.sorted_methods(cbi.methods()) %}
+    {%- let types = renderer.member_scope(cbi.name(), meth.name()) %}
+    {%- call ts::docstring(meth, 4) %}
+    {{ meth.name()|fn_name }}({% call arg_list(meth) %}): {% call return_type(meth) %}{% call throws(meth) %};
+    {%- endfor %}
+}
+{%- endfor %}
+{%- endif %}
+
+{%- let custom_types = self.custom_types() %}
+{%- if !custom_types.is_empty() %}
+
+// Custom types
+{%- for (name, concrete_type_name) in custom_types %}
+export declare type {{ name }} = {{ concrete_type_name }};
+{%- endfor %}
+{%- endif %}
+{# space #}
@@ -13,6 +13,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
     // ts automatically converts these into C callback functions.
     vtable: {
         {%- for (ffi_callback, meth) in vtable_methods %}
+        {%- let types = self.member_scope(name, meth.name()) %}
         {{ meth.name()|fn_name }}: (
             {%- for arg in ffi_callback.arguments() %}
             {{ arg.name()|var_name }}: {{ arg.type_().borrow()|ffi_type_name }}{% if !loop.last || ffi_callback.has_rust_call_status_arg() %},{% endif %}
@@ -31,7 +32,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
                 const jsCallback = {{ ffi_converter_name }}.lift(uniffiHandle);
                 return {% call ts::await(meth) %}jsCallback.{{ meth.name()|fn_name }}(
                     {%- for arg in meth.arguments() %}
-                    {{ arg|ffi_converter_name(self) }}.lift({{ arg.name()|var_name }}){% if !loop.last %}, {% endif %}
+                    {{ arg|ffi_converter_name(types) }}.lift({{ arg.name()|var_name }}){% if !loop.last %}, {% endif %}
                     {%- endfor %}
                     {%- if meth.is_async() -%}
                     {%-   if !meth.arguments().is_empty() %}, {% endif -%}
@@ -43,7 +44,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
 
             {% match meth.return_type() %}
             {%- when Some(t) %}
-            const uniffiWriteReturn = (obj: any) => { uniffiOutReturn.pointee = {{ t|ffi_converter_name(self) }}.lower(obj) };
+            const uniffiWriteReturn = (obj: any) => { uniffiOutReturn.pointee = {{ t|ffi_converter_name(types) }}.lower(obj) };
             {%- when None %}
             const uniffiWriteReturn = (obj: any) => {};
             {%- endmatch %}
@@ -63,7 +64,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
                 /*callStatus:*/ uniffiCallStatus,
                 /*makeCall:*/ uniffiMakeCall,
                 /*writeReturn:*/ uniffiWriteReturn,
-                /*isErrorType:*/ {{ error_type|decl_type_name(self) }}.instanceOf,
+                /*isErrorType:*/ {{ error_type|decl_type_name(types) }}.instanceOf,
                 /*lowerError:*/ {{ error_type|lower_error_fn(self) }},
                 /*lowerString:*/ FfiConverterString.lower
             )
@@ -76,7 +77,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
                     /* {{ meth.foreign_future_ffi_result_struct().name()|ffi_struct_name }} */{
                         {%- match meth.return_type() %}
                         {%- when Some(return_type) %}
-                        returnValue: {{ return_type|ffi_converter_name(self) }}.lower(returnValue),
+                        returnValue: {{ return_type|ffi_converter_name(types) }}.lower(returnValue),
                         {%- when None %}
                         {%- endmatch %}
                         callStatus: uniffiCreateCallStatus()
@@ -112,7 +113,7 @@ const {{ trait_impl }}: { vtable: {{ vtable|ffi_type_name }}; register: () => vo
                 /*makeCall:*/ uniffiMakeCall,
                 /*handleSuccess:*/ uniffiHandleSuccess,
                 /*handleError:*/ uniffiHandleError,
-                /*isErrorType:*/ {{ error_type|decl_type_name(self) }}.instanceOf,
+                /*isErrorType:*/ {{ error_type|decl_type_name(types) }}.instanceOf,
                 /*lowerError:*/ {{ error_type|lower_error_fn(self) }},
                 /*lowerString:*/ FfiConverterString.lower
             )
@@ -65,7 +65,7 @@ const {{ ffi_converter_name }} = (() => {
                     {%- if flat %}FfiConverterString.read(from)
                     {%- else %}
                     {%-   for field in variant.fields() %}
-                    {{      field|ffi_converter_name(self) }}.read(from)
+                    {{      field|ffi_converter_name(types) }}.read(from)
                     {%-     if !loop.last %}, {% endif %}
                     {%-   endfor %}
                     {%- endif %}
@@ -83,7 +83,7 @@ const {{ ffi_converter_name }} = (() => {
                 {%-   for variant in e.variants() %}
                 case {{ loop.index }}:
                 {%-     for field in variant.fields() %}
-                    {{ field|ffi_converter_name(self) }}.write(obj.{{ field.name()|var_name }} as {{ field|type_name(self) }}, into);
+                    {{ field|ffi_converter_name(types) }}.write(obj.{{ field.name()|var_name }} as {{ field|type_name(types) }}, into);
                 {%-     endfor -%}
                     break;
                 {%-   endfor %}
@@ -102,7 +102,7 @@ const {{ ffi_converter_name }} = (() => {
                 case {{ loop.index }}:
                     return (intConverter.allocationSize({{ loop.index }})
                 {%-     for field in variant.fields() %} + {# space #}
-                    {{ field|ffi_converter_name(self) }}.allocationSize(obj.{{ field.name()|var_name }} as {{ field|type_name(self) }})
+                    {{ field|ffi_converter_name(types) }}.allocationSize(obj.{{ field.name()|var_name }} as {{ field|type_name(types) }})
                 {%-     endfor -%}
                     );
                 {%-   endfor %}
@@ -1,5 +1,7 @@
 {{- self.import_infra("FfiConverterMap", "ffi-converters") }}
-{%- let key_ffi_converter = key_type|ffi_converter_name(self) %}
-{%- let value_ffi_converter = value_type|ffi_converter_name(self) %}
-// FfiConverter for {{ type_name }}
-const {{ ffi_converter_name }} = new FfiConverterMap({{ key_ffi_converter }}, {{ value_ffi_converter }});
+{%- for int64 in self.int64_types(type_) %}
+{%- let key_ffi_converter = key_type|ffi_converter_name_as(int64, self) %}
+{%- let value_ffi_converter = value_type|ffi_converter_name_as(int64, self) %}
+// FfiConverter for {{ type_|type_name_as(int64, self) }}
+const {{ type_|ffi_converter_name_as(int64, self) }} = new FfiConverterMap({{ key_ffi_converter }}, {{ value_ffi_converter }});
+{%- endfor %}
@@ -2,6 +2,7 @@
 {%- call ts::docstring_value(protocol_docstring, 0) %}
 export interface {{ protocol_name }} {
     {% for meth in methods.iter() -%}
+    {%- let types = self.member_scope(name, meth.name()) %}
     {%- call ts::docstring(meth, 4) %}
     {{ meth.name()|fn_name }}({% call ts::arg_list_protocol(meth) %}) {% call ts::throws(meth) -%}
     : {# space #}
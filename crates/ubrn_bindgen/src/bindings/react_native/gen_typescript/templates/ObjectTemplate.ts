@@ -9,8 +9,8 @@
 {{- self.import_infra("uniffiTypeNameSymbol", "symbols") -}}
 
 {%- let obj = ci|get_object_definition(name) %}
-{%- let protocol_name = obj|type_name(self) %}
-{%- let impl_class_name = obj|decl_type_name(self) %}
+{%- let protocol_name = obj|type_name(types) %}
+{%- let impl_class_name = obj|decl_type_name(types) %}
 {%- let obj_factory = format!("uniffiType{}ObjectFactory", impl_class_name) %}
 {%- let methods = obj.methods() %}
 
@@ -34,6 +34,7 @@ export class {{ impl_class_name }} extends UniffiAbstractObject implements {{ pr
 
     {%- match obj.primary_constructor() %}
     {%- when Some with (cons) %}
+    {%- let types = self.member_scope(name, cons.name()) %}
     {%- if !cons.is_async() %}
     {%-   call ts::ctor_decl(obj_factory, cons, 4) %}
     {%- else %}
@@ -48,10 +49,12 @@ export class {{ impl_class_name }} extends UniffiAbstractObject implements {{ pr
     {%- endmatch %}
 
     {% for cons in obj.alternate_constructors() %}
+    {%- let types = self.member_scope(name, cons.name()) %}
     {%- call ts::method_decl("public static", obj_factory, cons, 4) %}
     {% endfor %}
 
     {% for meth in obj.methods() -%}
+    {%- let types = self.member_scope(name, meth.name()) %}
     {%- call ts::method_decl("public", obj_factory, meth, 4) %}
     {% endfor %}
 
@@ -1,4 +1,6 @@
 {{- self.import_infra("FfiConverterOptional", "ffi-converters") }}
-{%- let item_ffi_converter = inner_type|ffi_converter_name(self) %}
-// FfiConverter for {{ type_name }}
-const {{ ffi_converter_name }} = new FfiConverterOptional({{ item_ffi_converter }});
+{%- for int64 in self.int64_types(type_) %}
+{%- let item_ffi_converter = inner_type|ffi_converter_name_as(int64, self) %}
+// FfiConverter for {{ type_|type_name_as(int64, self) }}
+const {{ type_|ffi_converter_name_as(int64, self) }} = new FfiConverterOptional({{ item_ffi_converter }});
+{%- endfor %}
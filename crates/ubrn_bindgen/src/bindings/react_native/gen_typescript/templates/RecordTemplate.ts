@@ -2,11 +2,12 @@
 {{- self.import_infra("uniffiCreateRecord", "records") }}
 
 {%- let rec = ci|get_record_definition(name) %}
+{%- let json_fields = format!("uniffiType{decl_type_name}JsonFields") %}
 {%- call ts::docstring(rec, 0) %}
 export type {{ type_name }} = {
     {%- for field in rec.fields() %}
     {%- call ts::docstring(field, 4) %}
-    {{ field.name()|var_name }}: {{ field|type_name(self) }}
+    {{ field.name()|var_name }}: {{ field|type_name(types) }}
     {%- if !loop.last %},{% endif %}
     {%- endfor %}
 }
@@ -19,7 +20,7 @@ export const {{ decl_type_name }} = (() => {
         {%- for field in rec.fields() %}
         {%- match field.default_value() %}
         {%- when Some with(literal) %}
-        {{- field.name()|var_name }}: {{ literal|render_literal(field, ci) }}
+        {{- field.name()|var_name }}: {{ literal|render_literal(field, types) }}
         {%- if !loop.last %},{% endif %}
         {%- else %}
         {%- endmatch -%}
@@ -45,9 +46,56 @@ export const {{ decl_type_name }} = (() => {
          * Defaults specified in the {@link {{ ci.namespace() }}} crate.
          */
         defaults: () => Object.freeze(defaults()) as Partial<{{ type_name }}>,
+
+        /**
+         * Convert a {@link {{ type_name }}} into something `JSON.stringify` can write,
+         * keeping bigints, maps and enum variants, so that `fromJSON` can read it back.
+         */
+        toJSON: (value: {{ type_name }}): any => ({
+            {%- for field in rec.fields() %}
+            {%- let field_name = field.name()|var_name %}
+            {{ field_name }}: {{ json_fields }}.{{ field_name }}.toJSON(value.{{ field_name }}),
+            {%- endfor %}
+        }),
+
+        /**
+         * Create a frozen instance of {@link {{ type_name }}} from the output of `toJSON`,
+         * after it has been through `JSON.stringify` and `JSON.parse`.
+         */
+        fromJSON: (json: any): {{ type_name }} => Object.freeze({
+            {%- for field in rec.fields() %}
+            {%- let field_name = field.name()|var_name %}
+            {{ field_name }}: {{ json_fields }}.{{ field_name }}.fromJSON(json.{{ field_name }}),
+            {%- endfor %}
+        }),
+
+        /**
+         * Are the fields of the two {@link {{ type_name }}} equal, comparing records,
+         * enums, arrays and maps by their contents?
+         */
+        equals: (a: {{ type_name }}, b: {{ type_name }}): boolean => (
+            {%- for field in rec.fields() %}
+            {%- let field_name = field.name()|var_name %}
+            {{ json_fields }}.{{ field_name }}.equals(a.{{ field_name }}, b.{{ field_name }})
+            {%- if !loop.last %} &&{% endif %}
+            {%- endfor %}
+            {%- if !rec.has_fields() %}
+            true
+            {%- endif %}
+        ),
     });
 })();
 
+{%- if rec.has_fields() %}
+
+// The JSON converters for the fields of {{ type_name }}, used by its toJSON, fromJSON and equals.
+const {{ json_fields }} = {
+    {%- for field in rec.fields() %}
+    {{ field.name()|var_name }}: {{ field|json_converter(types) }},
+    {%- endfor %}
+};
+{%- endif %}
+
 const {{ ffi_converter_name }} = (() => {
     type TypeName = {{ type_name }};
     {{- self.import_infra("AbstractFfiConverterArrayBuffer", "ffi-converters") }}
@@ -55,20 +103,22 @@ const {{ ffi_converter_name }} = (() => {
         read(from: RustBuffer): TypeName {
             return {
             {%- for field in rec.fields() %}
-                {{ field.name()|arg_name }}: {{ field|ffi_converter_name(self) }}.read(from)
+                {{ field.name()|arg_name }}: {{ field|ffi_converter_name(types) }}.read(from)
                 {%- if !loop.last %}, {% endif %}
             {%- endfor %}
             };
         }
         write(value: TypeName, into: RustBuffer): void {
             {%- for field in rec.fields() %}
-            {{ field|ffi_converter_name(self) }}.write(value.{{ field.name()|var_name }}, into);
+            {%- let field_name = field.name()|var_name %}
+            {%- let field_value = format!("value.{}", field_name) %}
+            {{ field|ffi_converter_name(types) }}.write({{ field_value|check_integer(field, type_name, types) }}, into);
             {%- endfor %}
         }
         allocationSize(value: TypeName): number {
             {%- if rec.has_fields() %}
             return {% for field in rec.fields() -%}
-                {{ field|ffi_converter_name(self) }}.allocationSize(value.{{ field.name()|var_name }})
+                {{ field|ffi_converter_name(types) }}.allocationSize(value.{{ field.name()|var_name }})
             {%- if !loop.last %} + {% else %};{% endif %}
             {% endfor %}
             {%- else %}
@@ -1,4 +1,11 @@
+{%- for int64 in self.int64_types(type_) %}
+{%- let ffi_converter_name = type_|ffi_converter_name_as(int64, self) %}
+{%- if self.is_typed_array(type_, int64) %}
+{{- self.import_infra(ffi_converter_name, "ffi-converters") }}
+{%- else %}
 {{- self.import_infra("FfiConverterArray", "ffi-converters") }}
-{%- let item_ffi_converter = inner_type|ffi_converter_name(self) %}
-// FfiConverter for {{ type_name }}
+{%- let item_ffi_converter = inner_type|ffi_converter_name_as(int64, self) %}
+// FfiConverter for {{ type_|type_name_as(int64, self) }}
 const {{ ffi_converter_name }} = new FfiConverterArray({{ item_ffi_converter }});
+{%- endif %}
+{%- endfor %}
@@ -2,6 +2,8 @@
 
 // Enum: {{ type_name }}
 {%- let type_name__Tags = format!("{type_name}_Tags") %}
+{%- let variant_union = format!("{type_name}__Variant") %}
+{%- let json_fields = format!("uniffiType{decl_type_name}JsonFields") %}
 export enum {{ type_name__Tags }} {
     {%- for variant in e.variants() %}
     {{ variant|variant_name }} = "{{ variant.name() }}"
@@ -13,13 +15,13 @@ export enum {{ type_name__Tags }} {
 {%-   let fields = variant.fields() %}
 {%-   if !is_tuple %}{
 {%-     for field in fields %}
-{{-       field.name()|var_name }}: {{ field|type_name(self) }}
+{{-       field.name()|var_name }}: {{ field|type_name(types) }}
 {%-       if !loop.last %}; {% endif -%}
 {%-     endfor %}}
 {%-   else %}
 [
 {%-     for field in fields %}
-{{-       field|type_name(self) }}
+{{-       field|type_name(types) }}
 {%-       if !loop.last %}, {% endif -%}
 {%-     endfor %}
 ]
@@ -121,8 +123,108 @@ export const {{ decl_type_name }} = (() => {
         return obj[uniffiTypeNameSymbol] === "{{ type_name }}";
     }
 
+    type {{ variant_union }} =
+  {%- for variant in e.variants() %} {{ variant.name()|class_name(ci) }}_
+  {%-   if !loop.last %} |{% endif -%}
+  {%- endfor %};
+
+    /**
+     * Convert a {@link {{ type_name }}} into something `JSON.stringify` can write,
+     * keeping the tag of the variant, so that `fromJSON` can read it back.
+     */
+    function toJSON(value: {{ variant_union }}): any {
+        switch (value.tag) {
+        {%- for variant in e.variants() %}
+        {%-   let has_fields = !variant.fields().is_empty() %}
+        {%-   let is_tuple = variant.has_nameless_fields() %}
+            case {{ type_name__Tags }}.{{ variant|variant_name }}: {
+            {%- if has_fields %}
+                const inner = value.inner;
+                const fields = {{ json_fields }}.{{ variant.name()|class_name(ci) }};
+                return {
+                    tag: value.tag,
+                    inner: {% if is_tuple %}[{% else %}{ {% endif %}
+                {%-   for field in variant.fields() %}
+                {%-     if !is_tuple %}{{ field.name()|var_name }}: {% endif %}
+                {%- call ts::field_name("fields", field, loop.index0) %}.toJSON({% call ts::field_name("inner", field, loop.index0) %})
+                {%-     if !loop.last %}, {% endif %}
+                {%-   endfor %}{% if is_tuple %}]{% else %} }{% endif %},
+                };
+            {%- else %}
+                return { tag: value.tag };
+            {%- endif %}
+            }
+        {%- endfor %}
+            default:
+                throw new UniffiInternalError.UnexpectedEnumCase();
+        }
+    }
+
+    /**
+     * Create an instance of {@link {{ type_name }}} from the output of `toJSON`,
+     * after it has been through `JSON.stringify` and `JSON.parse`.
+     */
+    function fromJSON(json: any): {{ variant_union }} {
+        switch (json.tag) {
+        {%- for variant in e.variants() %}
+        {%-   let external_name = variant.name()|class_name(ci) %}
+        {%-   let has_fields = !variant.fields().is_empty() %}
+        {%-   let is_tuple = variant.has_nameless_fields() %}
+            case {{ type_name__Tags }}.{{ variant|variant_name }}: {
+            {%- if has_fields %}
+                const inner = json.inner;
+                const fields = {{ json_fields }}.{{ external_name }};
+                return new {{ external_name }}_({% if !is_tuple %}{ {% endif %}
+                {%-   for field in variant.fields() %}
+                {%-     if !is_tuple %}{{ field.name()|var_name }}: {% endif %}
+                {%- call ts::field_name("fields", field, loop.index0) %}.fromJSON({% call ts::field_name("inner", field, loop.index0) %})
+                {%-     if !loop.last %}, {% endif %}
+                {%-   endfor %}{% if !is_tuple %} }{% endif %});
+            {%- else %}
+                return new {{ external_name }}_();
+            {%- endif %}
+            }
+        {%- endfor %}
+            default:
+                throw new UniffiInternalError.UnexpectedEnumCase();
+        }
+    }
+
+    /**
+     * Are the two {@link {{ type_name }}} the same variant, with fields which are equal,
+     * comparing records, enums, arrays and maps by their contents?
+     */
+    function equals(a: {{ variant_union }}, b: {{ variant_union }}): boolean {
+        if (a.tag !== b.tag) {
+            return false;
+        }
+        switch (a.tag) {
+        {%- for variant in e.variants() %}
+        {%-   let external_name = variant.name()|class_name(ci) %}
+        {%-   if !variant.fields().is_empty() %}
+            case {{ type_name__Tags }}.{{ variant|variant_name }}: {
+                const inner = a.inner;
+                const other = (b as {{ external_name }}_).inner;
+                const fields = {{ json_fields }}.{{ external_name }};
+                return (
+                {%- for field in variant.fields() %}
+                    {% call ts::field_name("fields", field, loop.index0) %}.equals({% call ts::field_name("inner", field, loop.index0) %}, {% call ts::field_name("other", field, loop.index0) %})
+                    {%- if !loop.last %} &&{% endif %}
+                {%- endfor %}
+                );
+            }
+        {%-   endif %}
+        {%- endfor %}
+            default:
+                return true;
+        }
+    }
+
     return Object.freeze({
         instanceOf,
+        toJSON,
+        fromJSON,
+        equals,
   {%- for variant in e.variants() %}
   {%-   let external_name = variant.name()|class_name(ci) %}
   {%-   let variant_name = external_name|fmt("{}_") %}
@@ -133,6 +235,23 @@ export const {{ decl_type_name }} = (() => {
 
 })();
 
+{%- if e.iter_types().next().is_some() %}
+
+// The JSON converters for the fields of each variant of {{ type_name }}, used by its
+// toJSON, fromJSON and equals.
+const {{ json_fields }} = {
+  {%- for variant in e.variants() %}
+  {%-   if !variant.fields().is_empty() %}
+    {{ variant.name()|class_name(ci) }}: {% if variant.has_nameless_fields() %}[{% else %}{{ "{" }}{% endif %}
+    {%-   for field in variant.fields() %}
+        {% if !variant.has_nameless_fields() %}{{ field.name()|var_name }}: {% endif %}{{ field|json_converter(types) }},
+    {%-   endfor %}
+    {% if variant.has_nameless_fields() %}]{% else %}}{% endif %},
+  {%-   endif %}
+  {%- endfor %}
+};
+{%- endif %}
+
 {% call ts::docstring(e, 0) %}
 {% call ts::type_omit_instanceof(type_name, decl_type_name) %}
 
@@ -151,12 +270,12 @@ const {{ ffi_converter_name }} = (() => {
             {%-   if has_fields %}
             {%-     if !is_tuple %}{
             {%-     for field in variant.fields() %}
-            {{-       field.name()|var_name }}: {{ field|ffi_converter_name(self) }}.read(from)
+            {{-       field.name()|var_name }}: {{ field|ffi_converter_name(types) }}.read(from)
             {%-       if !loop.last -%}, {% endif %}
             {%-     endfor %} }
             {%-     else %}
             {%-       for field in variant.fields() %}
-            {{-         field|ffi_converter_name(self) }}.read(from)
+            {{-         field|ffi_converter_name(types) }}.read(from)
             {%-         if !loop.last -%}, {% endif %}
             {%-       endfor %}
             {%-     endif %}
@@ -175,7 +294,7 @@ const {{ ffi_converter_name }} = (() => {
                     {%- if has_fields %}
                     const inner = value.inner;
                     {%-   for field in variant.fields() %}
-                    {{ field|ffi_converter_name(self) }}.write({% call ts::field_name("inner", field, loop.index0) %}, into);
+                    {{ field|ffi_converter_name(types) }}.write({% call ts::field_name("inner", field, loop.index0) %}, into);
                     {%-   endfor %}
                     {%- endif %}
                     return;
@@ -196,7 +315,7 @@ const {{ ffi_converter_name }} = (() => {
                     const inner = value.inner;
                     let size = ordinalConverter.allocationSize({{ loop.index }});
                 {%-     for field in variant.fields() %}
-                    size += {{ field|ffi_converter_name(self) }}.allocationSize({% call ts::field_name("inner", field, loop.index0) %});
+                    size += {{ field|ffi_converter_name(types) }}.allocationSize({% call ts::field_name("inner", field, loop.index0) %});
                 {%-     endfor %}
                     return size;
                 {%-   else %}
@@ -1 +1,2 @@
+{%- let types = self.scope(func.name()) %}
 {%- call ts::top_func_decl("function", func, 0) %}
@@ -7,9 +7,10 @@
 {%- endfor %}
 
 {%- for type_ in ci.iter_sorted_types() %}
-{%- let type_name = type_|type_name(self) %}
-{%- let decl_type_name = type_|decl_type_name(self) %}
-{%- let ffi_converter_name = type_|ffi_converter_name(self) %}
+{%- let types = self.type_scope(type_) %}
+{%- let type_name = type_|type_name(types) %}
+{%- let decl_type_name = type_|decl_type_name(types) %}
+{%- let ffi_converter_name = type_|ffi_converter_name(types) %}
 {%- let contains_object_references = ci.item_contains_object_references(type_) %}
 
 {#
@@ -42,7 +43,10 @@
 {{- self.import_infra("FfiConverterInt32", "ffi-converters") }}
 
 {%- when Type::Int64 %}
-{{- self.import_infra("FfiConverterInt64", "ffi-converters") }}
+{%- for int64 in self.int64_types(type_) %}
+{%- let ffi_converter_name = type_|ffi_converter_name_as(int64, self) %}
+{{- self.import_infra(ffi_converter_name, "ffi-converters") }}
+{%- endfor %}
 
 {%- when Type::UInt8 %}
 {{- self.import_infra("FfiConverterUInt8", "ffi-converters") }}
@@ -54,7 +58,10 @@
 {{- self.import_infra("FfiConverterUInt32", "ffi-converters") }}
 
 {%- when Type::UInt64 %}
-{{- self.import_infra("FfiConverterUInt64", "ffi-converters") }}
+{%- for int64 in self.int64_types(type_) %}
+{%- let ffi_converter_name = type_|ffi_converter_name_as(int64, self) %}
+{{- self.import_infra(ffi_converter_name, "ffi-converters") }}
+{%- endfor %}
 
 {%- when Type::Float32 %}
 {{- self.import_infra("FfiConverterFloat32", "ffi-converters") }}
@@ -63,12 +70,12 @@
 {{- self.import_infra("FfiConverterFloat64", "ffi-converters") }}
 
 {%- when Type::Timestamp %}
-{{- self.import_infra("FfiConverterTimestamp", "ffi-converters") -}}
-{{- self.import_infra_type("UniffiTimestamp", "ffi-converters") -}}
+{{- self.import_infra(ffi_converter_name, "ffi-converters") -}}
+{{- self.import_infra_type(type_name, "ffi-converters") -}}
 
 {%- when Type::Duration %}
-{{- self.import_infra("FfiConverterDuration", "ffi-converters") -}}
-{{- self.import_infra_type("UniffiDuration", "ffi-converters") -}}
+{{- self.import_infra(ffi_converter_name, "ffi-converters") -}}
+{{- self.import_infra_type(type_name, "ffi-converters") -}}
 
 {%- when Type::CallbackInterface { name, module_path } %}
 {%- include "CallbackInterfaceTemplate.ts" %}
@@ -80,7 +80,7 @@
 
 {%- macro raw_return_type(callable) %}
     {%- match callable.return_type() %}
-    {%-  when Some with (return_type) %}{{ return_type|type_name(self) }}
+    {%-  when Some with (return_type) %}{{ return_type|type_name(types) }}
     {%-  when None %}void
     {%- endmatch %}
 {%- endmacro %}
@@ -112,7 +112,7 @@
 {%- else %}
 {%-     match callable.return_type() -%}
 {%-         when Some with (return_type) %}
-    return {{ return_type|ffi_converter_name(self) }}.lift({% call to_ffi_method_call(obj_factory, callable) %});
+    return {{ return_type|ffi_converter_name(types) }}.lift({% call to_ffi_method_call(obj_factory, callable) %});
 {%-         when None %}
 {%-             call to_ffi_method_call(obj_factory, callable) %};
 {%-     endmatch %}
@@ -129,7 +129,7 @@
                     {{ obj_factory }}.clonePointer(this){% if !callable.arguments().is_empty() %},{% endif %}
                     {% endif %}
                     {%- for arg in callable.arguments() -%}
-                    {{ arg|ffi_converter_name(self) }}.lower({{ arg.name()|var_name }}){% if !loop.last %},{% endif %}
+                    {{ arg|ffi_converter_name(types) }}.lower({% call arg_checked(callable, arg) %}){% if !loop.last %},{% endif %}
                     {%- endfor %}
                 );
             },
@@ -139,7 +139,7 @@
             /*freeFunc:*/ {% call native_method_handle_free(callable.ffi_rust_future_free(ci)) %},
             {%- match callable.return_type() %}
             {%- when Some(return_type) %}
-            /*liftFunc:*/ {{ return_type|lift_fn(self) }},
+            /*liftFunc:*/ {{ return_type|lift_fn(types) }},
             {%- when None %}
             /*liftFunc:*/ (_v) => {},
             {%- endmatch %}
@@ -155,10 +155,19 @@
 
 {%- macro arg_list_lowered(func) %}
     {%- for arg in func.arguments() %}
-        {{ arg|ffi_converter_name(self) }}.lower({{ arg.name()|var_name }}),
+        {{ arg|ffi_converter_name(types) }}.lower({% call arg_checked(func, arg) %}),
     {%- endfor %}
 {%- endmacro -%}
 
+{#-
+// The argument, checked to fit into its Rust type if it is an integer, and if `checkArguments`
+// is on.
+-#}
+{%- macro arg_checked(func, arg) %}
+{%- let label = types.callable_label(func.name()) %}
+{{- arg.name()|var_name|check_integer(arg, label, types) }}
+{%- endmacro -%}
+
 {#-
 // Arglist as used in ts declarations of methods, functions and constructors.
 // Note the var_name and type_name filters.
@@ -166,9 +175,9 @@
 
 {% macro arg_list_decl(func) %}
     {%- for arg in func.arguments() -%}
-        {{ arg.name()|var_name }}: {{ arg|type_name(self) -}}
+        {{ arg.name()|var_name }}: {{ arg|type_name(types) -}}
         {%- match arg.default_value() %}
-        {%- when Some with(literal) %} = {{ literal|render_literal(arg, ci) }}
+        {%- when Some with(literal) %} = {{ literal|render_literal(arg, types) }}
         {%- else %}
         {%- endmatch %}
         {%- if !loop.last %}, {% endif -%}
@@ -189,11 +198,11 @@
     {%-   call docstring(field, 8) %}
     {%-   if has_nameless_fields -%}
     v{{ loop.index0 }}: {# space #}
-    {{-     field|type_name(self) }}
+    {{-     field|type_name(types) }}
     {%-   else %}
-    {{-     field.name()|var_name }}: {{ field|type_name(self) -}}
+    {{-     field.name()|var_name }}: {{ field|type_name(types) -}}
     {%-     match field.default_value() %}
-    {%-       when Some with(literal) %} = {{ literal|render_literal(field, ci) }}
+    {%-       when Some with(literal) %} = {{ literal|render_literal(field, types) }}
     {%-       else %}
     {%-     endmatch -%}
     {%-   endif %}
@@ -228,7 +237,7 @@
 #}
 {% macro arg_list_protocol(func) %}
     {%- for arg in func.arguments() -%}
-        {{ arg.name()|var_name }}: {{ arg|type_name(self) -}}
+        {{ arg.name()|var_name }}: {{ arg|type_name(types) -}}
         {%- if !loop.last %}, {% endif -%}
     {%- endfor %}
     {%- if func.is_async() %}
@@ -267,7 +276,7 @@
 
 {%- macro type_omit_instanceof(type_name, decl_type_name) %}
 export type {{ type_name }} = InstanceType<
-    typeof {{ decl_type_name }}[keyof Omit<typeof {{ decl_type_name }}, 'instanceOf'>]
+    typeof {{ decl_type_name }}[keyof Omit<typeof {{ decl_type_name }}, 'instanceOf' | 'toJSON' | 'fromJSON' | 'equals'>]
 >;
 {%- endmacro %}
 
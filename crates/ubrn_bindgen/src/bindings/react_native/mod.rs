@@ -7,14 +7,14 @@ mod gen_cpp;
 mod gen_typescript;
 mod uniffi_toml;
 
-use std::{collections::HashMap, fs};
+use std::{cell::RefCell, collections::HashMap};
 
 use anyhow::Result;
-use camino::Utf8Path;
 use extend::ext;
 use heck::{ToLowerCamelCase, ToSnakeCase};
+use log::warn;
 use topological_sort::TopologicalSort;
-use ubrn_common::{fmt, run_cmd_quietly};
+use ubrn_common::{absolute, fmt, GeneratedFiles};
 use uniffi_bindgen::{
     interface::{
         FfiArgument, FfiCallbackFunction, FfiDefinition, FfiField, FfiFunction, FfiStruct, FfiType,
@@ -32,17 +32,17 @@ use crate::bindings::metadata::ModuleMetadata;
 
 pub(crate) struct ReactNativeBindingGenerator {
     output: OutputArgs,
+    files: RefCell<GeneratedFiles>,
 }
 
 impl ReactNativeBindingGenerator {
     pub(crate) fn new(output: OutputArgs) -> Self {
-        Self { output }
+        let files = RefCell::new(GeneratedFiles::new(output.check));
+        Self { output, files }
     }
 
-    pub(crate) fn format_code(&self) -> Result<()> {
-        format_ts(&self.output.ts_dir.canonicalize_utf8()?)?;
-        format_cpp(&self.output.cpp_dir.canonicalize_utf8()?)?;
-        Ok(())
+    pub(crate) fn into_files(self) -> GeneratedFiles {
+        self.files.into_inner()
     }
 }
 
@@ -70,55 +70,67 @@ impl BindingGenerator for ReactNativeBindingGenerator {
         settings: &GenerationSettings,
         components: &[Component<Self::Config>],
     ) -> Result<()> {
+        let ts_dir = absolute(&self.output.ts_dir)?;
+        let cpp_dir = absolute(&self.output.cpp_dir)?;
+        // In a dry run, the output directories may not exist yet, so the formatters can't
+        // be run in them. Every file would be created anyway.
+        let format_ts =
+            settings.try_format_code && ts_dir.exists() && fmt::prettier(&ts_dir, false)?.is_some();
+        let format_cpp = settings.try_format_code
+            && cpp_dir.exists()
+            && fmt::clang_format(&cpp_dir, false)?.is_some();
+        if settings.try_format_code && !format_ts {
+            warn!("No prettier found. Install with `yarn add --dev prettier`");
+        }
+        if settings.try_format_code && !format_cpp {
+            warn!("Skipping formatting C++. Is clang-format installed?");
+        }
+
         let mut type_map = TypeMap::default();
         for component in components {
             type_map.insert_ci(&component.ci);
         }
+        let mut ts_files = Vec::new();
+        let mut cpp_files = Vec::new();
         for component in components {
             let ci = &component.ci;
             let module: ModuleMetadata = component.into();
+            if self.output.is_generated(&module) {
+                continue;
+            }
             let config = &component.config;
-            let TsBindings { codegen, frontend } =
-                gen_typescript::generate_bindings(ci, &config.typescript, &module, &type_map)?;
+            let TsBindings {
+                codegen,
+                frontend,
+                api,
+            } = gen_typescript::generate_bindings(ci, &config.typescript, &module, &type_map)?;
 
-            let out_dir = &self.output.ts_dir.canonicalize_utf8()?;
-            let codegen_path = out_dir.join(module.ts_ffi_filename());
-            let frontend_path = out_dir.join(module.ts_filename());
-            fs::write(codegen_path, codegen)?;
-            fs::write(frontend_path, frontend)?;
+            ts_files.push((ts_dir.join(module.ts_ffi_filename()), codegen));
+            ts_files.push((ts_dir.join(module.ts_filename()), frontend));
+            ts_files.push((ts_dir.join(module.ts_api_filename()), api));
 
-            let out_dir = &self.output.cpp_dir.canonicalize_utf8()?;
             let CppBindings { hpp, cpp } = gen_cpp::generate_bindings(ci, &config.cpp, &module)?;
-            let cpp_path = out_dir.join(module.cpp_filename());
-            let hpp_path = out_dir.join(module.hpp_filename());
-            fs::write(cpp_path, cpp)?;
-            fs::write(hpp_path, hpp)?;
+            cpp_files.push((cpp_dir.join(module.cpp_filename()), cpp));
+            cpp_files.push((cpp_dir.join(module.hpp_filename()), hpp));
+        }
+
+        // Format every file with one run of each formatter, rather than one per file.
+        if format_ts {
+            fmt::format_files(&ts_dir, &mut ts_files, |dir| fmt::prettier(dir, false))?;
         }
-        if settings.try_format_code {
-            self.format_code()?;
+        if format_cpp {
+            fmt::format_files(&cpp_dir, &mut cpp_files, |dir| {
+                fmt::clang_format(dir, false)
+            })?;
+        }
+        let mut files = self.files.borrow_mut();
+        for (path, contents) in ts_files.iter().chain(&cpp_files) {
+            files.write(path, contents)?;
         }
         Ok(())
     }
 }
 
-fn format_ts(out_dir: &Utf8Path) -> Result<()> {
-    if let Some(mut prettier) = fmt::prettier(out_dir, false)? {
-        run_cmd_quietly(&mut prettier)?
-    } else {
-        eprintln!("No prettier found. Install with `yarn add --dev prettier`");
-    }
-    Ok(())
-}
-
-fn format_cpp(out_dir: &Utf8Path) -> Result<()> {
-    if let Some(mut clang_format) = fmt::clang_format(out_dir, false)? {
-        run_cmd_quietly(&mut clang_format)?
-    } else {
-        eprintln!("Skipping formatting C++. Is clang-format installed?");
-    }
-    Ok(())
-}
-
 #[ext]
 impl ComponentInterface {
     fn ffi_function_string_to_arraybuffer(&self) -> FfiFunction {
@@ -322,8 +334,8 @@ impl ComponentInterface {
         }
 
         if !graph.is_empty() {
-            eprintln!(
-                "WARN: Cyclic dependency for typescript types: {:?}",
+            warn!(
+                "Cyclic dependency for typescript types: {:?}",
                 types.values()
             );
             // We only warn if we have a cyclic dependency because by this stage,
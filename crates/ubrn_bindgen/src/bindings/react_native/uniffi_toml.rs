@@ -26,6 +26,20 @@ pub(crate) struct TsConfig {
     pub(crate) console_import: Option<String>,
     #[serde(default)]
     pub(crate) custom_types: HashMap<String, CustomTypeConfig>,
+    #[serde(default)]
+    pub(crate) int64: Int64Type,
+    #[serde(default)]
+    pub(crate) int64_overrides: HashMap<String, Int64Type>,
+    #[serde(default)]
+    pub(crate) check_arguments: Option<bool>,
+    #[serde(default)]
+    pub(crate) typed_arrays: bool,
+    #[serde(default)]
+    pub(crate) timestamp: TimestampType,
+    #[serde(default)]
+    pub(crate) timestamp_precision: TimestampPrecision,
+    #[serde(default)]
+    pub(crate) duration: DurationType,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -46,6 +60,52 @@ impl LogLevel {
     }
 }
 
+/// How `i64` and `u64` are represented in Typescript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Int64Type {
+    #[default]
+    Bigint,
+    /// A `number`, checked to be a safe integer when crossing the FFI.
+    Number,
+}
+
+/// How `SystemTime` is represented in Typescript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TimestampType {
+    #[default]
+    Date,
+    /// A `number` of milliseconds since the epoch.
+    Millis,
+    /// A `{ seconds, nanos }` object, with nothing lost.
+    Object,
+}
+
+/// What to do when a timestamp has more precision than a `Date` can hold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TimestampPrecision {
+    /// Drop anything less than a millisecond, like `new Date(ms)` does.
+    #[default]
+    Truncate,
+    /// Round to the nearest millisecond.
+    Round,
+    /// Throw, rather than lose anything.
+    Error,
+}
+
+/// How `Duration` is represented in Typescript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum DurationType {
+    /// A `number` of milliseconds.
+    #[default]
+    Millis,
+    /// A `{ seconds, nanos }` object, with nothing lost.
+    Object,
+}
+
 impl TsConfig {
     pub(crate) fn is_verbose(&self) -> bool {
         self.log_level.is_verbose()
@@ -53,6 +113,27 @@ impl TsConfig {
     pub(crate) fn is_debug(&self) -> bool {
         self.log_level.is_debug()
     }
+
+    /// Should integers passed to Rust be checked to be in range for their Rust type?
+    ///
+    /// Unless `checkArguments` is set, this is only done when debugging.
+    pub(crate) fn checks_arguments(&self) -> bool {
+        self.check_arguments.unwrap_or_else(|| self.is_debug())
+    }
+
+    /// The representation of 64-bit integers at the given path, e.g. `["MyRecord", "my_field"]`
+    /// or `["MyObject", "my_method", "my_arg"]`.
+    ///
+    /// The most specific entry in `int64Overrides` wins, so `MyObject.my_method` applies to
+    /// all of the method's arguments and its return value, unless one of the arguments has
+    /// its own entry.
+    pub(crate) fn int64_type(&self, path: &[&str]) -> Int64Type {
+        (1..=path.len())
+            .rev()
+            .find_map(|n| self.int64_overrides.get(&path[..n].join(".")))
+            .copied()
+            .unwrap_or(self.int64)
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -70,3 +151,41 @@ pub(crate) struct CustomTypeConfig {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CppConfig {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int64_overrides() {
+        let config: TsConfig = toml::from_str(
+            r#"
+            int64 = "number"
+
+            [int64Overrides]
+            MyObject = "bigint"
+            "MyObject.my_method.my_arg" = "number"
+            "MyRecord.my_field" = "bigint"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.int64_type(&[]), Int64Type::Number);
+        assert_eq!(config.int64_type(&["my_function"]), Int64Type::Number);
+        assert_eq!(
+            config.int64_type(&["MyObject", "my_method"]),
+            Int64Type::Bigint
+        );
+        assert_eq!(
+            config.int64_type(&["MyObject", "my_method", "my_arg"]),
+            Int64Type::Number
+        );
+        assert_eq!(
+            config.int64_type(&["MyRecord", "my_field"]),
+            Int64Type::Bigint
+        );
+        assert_eq!(
+            config.int64_type(&["MyRecord", "my_other_field"]),
+            Int64Type::Number
+        );
+    }
+}
@@ -5,5 +5,6 @@
  */
 mod bindings;
 
+pub use bindings::api_diff::DiffApiArgs;
 pub use bindings::metadata::ModuleMetadata;
 pub use bindings::{BindingsArgs, OutputArgs, SourceArgs};
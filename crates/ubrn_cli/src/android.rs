@@ -4,18 +4,26 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 use serde::Deserialize;
-use std::{collections::HashMap, fmt::Display, fs, process::Command, str::FromStr};
+use std::{
+    collections::HashMap, env::consts::EXE_SUFFIX, fmt::Display, process::Command, str::FromStr,
+};
 
 use clap::Args;
 
-use anyhow::{Error, Result};
+use anyhow::{Context, Error, Result};
 use camino::{Utf8Path, Utf8PathBuf};
-use ubrn_common::{mk_dir, rm_dir, run_cmd, CrateMetadata};
+use log::{info, warn};
+use ubrn_common::{
+    cp_file, dry_run, emit, file_paths, mk_dir, rm_dir, run_cmd, timed, CrateMetadata, LibraryType,
+    Message,
+};
 
 use crate::{
-    building::{CommonBuildArgs, ExtraArgs},
-    config::ProjectConfig,
+    building::{CargoSettings, CommonBuildArgs, ExtraArgs},
+    config::{ConfigArgs, ProjectConfig},
+    fingerprint::BuildCache,
     rust::CrateConfig,
+    symbols::{SymbolsConfig, SymbolsManifest},
     workspace,
 };
 
@@ -39,6 +47,15 @@ pub(crate) struct AndroidConfig {
 
     #[serde(default = "AndroidConfig::default_package_name")]
     pub(crate) package_name: String,
+
+    #[serde(default = "AndroidConfig::default_library_type")]
+    pub(crate) library_type: LibraryType,
+
+    #[serde(default)]
+    pub(crate) symbols: Option<SymbolsConfig>,
+
+    #[serde(flatten)]
+    pub(crate) cargo: CargoSettings,
 }
 
 impl Default for AndroidConfig {
@@ -52,6 +69,10 @@ impl AndroidConfig {
         workspace::package_json().android_package_name()
     }
 
+    fn default_library_type() -> LibraryType {
+        LibraryType::Static
+    }
+
     fn default_cargo_extras() -> ExtraArgs {
         let args: &[&str] = &[];
         args.into()
@@ -100,6 +121,19 @@ impl AndroidConfig {
         self.directory(project_root).join(self.java_src())
     }
 
+    /// The filename of the Rust library, as it is copied into the `jniLibs` directory.
+    pub(crate) fn library_file(&self, rust_crate: &CrateMetadata) -> String {
+        rust_crate
+            .with_library_type(self.library_type)
+            .library_file(Some("android"))
+    }
+
+    /// The directory, relative to the Android directory, where the headers are put for
+    /// the prefab package of an AAR.
+    pub(crate) fn prefab_headers(&self) -> &'static str {
+        "build/generated/ubrn/prefab/include"
+    }
+
     pub(crate) fn codegen_package_dir(&self, project_root: &Utf8Path) -> Utf8PathBuf {
         self.src_main_java_dir(project_root)
             .join(self.package_name.replace('.', "/"))
@@ -108,9 +142,8 @@ impl AndroidConfig {
 
 #[derive(Args, Debug)]
 pub(crate) struct AndroidArgs {
-    /// The configuration file for this build
-    #[clap(long)]
-    config: Utf8PathBuf,
+    #[clap(flatten)]
+    config: ConfigArgs,
 
     /// Comma separated list of targets, that override the values in the
     /// `config.yaml` file.
@@ -135,83 +168,183 @@ pub(crate) struct AndroidArgs {
     /// Suppress the copying of the Rust library into the JNI library directories.
     #[clap(long = "no-jniLibs")]
     no_jni_libs: bool,
+
+    /// Package the Rust library, the turbo-module and the generated bindings as an AAR.
+    ///
+    /// This runs Gradle to compile the C++ and Java, and to add the headers of the generated
+    /// bindings as a prefab package. It implies `--and-generate`.
+    #[clap(long, conflicts_with = "no_jni_libs")]
+    pub(crate) aar: bool,
 }
 
 impl AndroidArgs {
-    pub(crate) fn build(&self) -> Result<Vec<Utf8PathBuf>> {
+    /// Build each of the crates for each of the targets, returning the libraries for
+    /// each crate.
+    pub(crate) fn build(&self) -> Result<Vec<Vec<Utf8PathBuf>>> {
         let config: ProjectConfig = self.project_config()?;
 
-        let crate_ = &config.crate_;
+        let android = &config.android;
+        let metadata = config.crates_metadata()?;
+        let mut built = Vec::new();
+        for (crate_, metadata) in config.crates.iter().zip(metadata) {
+            let metadata = metadata.with_library_type(android.library_type);
+            let (target_files, should_strip) = self.build_crate(&config, crate_, &metadata)?;
+            built.push((metadata, target_files, should_strip));
+        }
+
+        // Only replace the libraries in jniLibs once every crate has built.
+        if !self.no_jni_libs {
+            let jni_libs = android.jni_libs(config.project_root());
+            rm_dir(&jni_libs)?;
+            for (metadata, target_files, should_strip) in &built {
+                let copied = self.copy_into_jni_libs(metadata, &jni_libs, target_files)?;
+                if *should_strip {
+                    strip(copied.values())?;
+                }
+            }
+        }
+
+        Ok(built
+            .into_iter()
+            .map(|(_, target_files, _)| target_files.into_values().collect())
+            .collect())
+    }
 
+    /// Build the crate for each of the targets, returning the libraries, and whether their
+    /// copies should be stripped.
+    fn build_crate(
+        &self,
+        config: &ProjectConfig,
+        crate_: &CrateConfig,
+        metadata: &CrateMetadata,
+    ) -> Result<(HashMap<Target, Utf8PathBuf>, bool)> {
         let android = &config.android;
         let target_list = if !self.targets.is_empty() {
             &self.targets
         } else {
             &android.targets
         };
+        let cargo = crate_.cargo.merge(&android.cargo);
+        let cache = self
+            .common_args
+            .build_cache(metadata, &format!("{:?}", find_ndk()))?;
         let target_files = if self.common_args.no_cargo {
-            let files = self.find_existing(&crate_.metadata()?, target_list);
+            let files = self.find_existing(metadata, &cargo, target_list);
             if !files.is_empty() {
                 files
             } else {
-                self.cargo_build_all(
-                    crate_,
-                    target_list,
-                    &android.cargo_extras,
-                    android.api_level,
-                )?
+                self.cargo_build_all(crate_, metadata, &cargo, target_list, android, &cache)?
             }
         } else {
-            self.cargo_build_all(
-                crate_,
-                target_list,
-                &android.cargo_extras,
-                android.api_level,
-            )?
+            self.cargo_build_all(crate_, metadata, &cargo, target_list, android, &cache)?
         };
 
-        if !self.no_jni_libs {
-            let project_root = config.project_root();
-            self.copy_into_jni_libs(
-                &crate_.metadata()?,
-                &android.jni_libs(project_root),
-                &target_files,
-            )?;
+        let project_root = config.project_root();
+        let symbols = self.symbols_config(android, &cargo);
+        if let Some(symbols) = symbols {
+            let mut dir = symbols.directory(project_root, "android");
+            if config.has_many_crates() {
+                dir = dir.join(metadata.library_name());
+            }
+            let profile = self.common_args.cargo_profile(&cargo);
+            self.keep_symbols(metadata, &dir, symbols.split, &profile, &target_files)?;
+        }
+
+        cache.save()?;
+        Ok((target_files, symbols.is_some()))
+    }
+
+    /// The symbols configuration, if debug symbols should be kept for this build.
+    ///
+    /// Only release builds of shared libraries are stripped. Static libraries are linked
+    /// into the C++ library, so it is that library's symbols which are needed.
+    fn symbols_config<'a>(
+        &self,
+        android: &'a AndroidConfig,
+        cargo: &CargoSettings,
+    ) -> Option<&'a SymbolsConfig> {
+        let symbols = android.symbols.as_ref()?;
+        if !self.common_args.is_release(cargo) {
+            return None;
         }
+        if !android.library_type.is_shared() {
+            warn!("Not keeping symbols: the Rust library is static, so its symbols are in the C++ library");
+            return None;
+        }
+        Some(symbols)
+    }
 
-        Ok(target_files.into_values().collect())
+    /// Copy the unstripped libraries, or just their debug info, into the symbols directory,
+    /// and list them in a `symbols.json` manifest.
+    fn keep_symbols(
+        &self,
+        metadata: &CrateMetadata,
+        dir: &Utf8Path,
+        split: bool,
+        profile: &str,
+        target_files: &HashMap<Target, Utf8PathBuf>,
+    ) -> Result<()> {
+        info!("-- Keeping debug symbols in {dir}");
+        rm_dir(dir)?;
+        let mut manifest = SymbolsManifest::new(&metadata.library_file(Some("android")), profile);
+        for (target, library) in target_files {
+            let dst_dir = dir.join(target.to_string());
+            mk_dir(&dst_dir)?;
+            let dst = dst_dir.join(metadata.library_file(Some(target.triple())));
+            let dst = if split {
+                let dst = Utf8PathBuf::from(format!("{dst}.debug"));
+                let mut cmd = Command::new(find_ndk_tool("llvm-objcopy")?);
+                cmd.arg("--only-keep-debug").arg(library).arg(&dst);
+                run_cmd(&mut cmd)?;
+                dst
+            } else {
+                cp_file(library, &dst)?;
+                emit(Message::Copied {
+                    from: library.clone(),
+                    to: dst.clone(),
+                });
+                dst
+            };
+            manifest.add(target, dir, &dst, library)?;
+        }
+        manifest.write(dir)
     }
 
     fn cargo_build_all(
         &self,
         crate_: &CrateConfig,
+        metadata: &CrateMetadata,
+        cargo: &CargoSettings,
         targets: &[Target],
-        cargo_extras: &ExtraArgs,
-        api_level: usize,
+        android: &AndroidConfig,
+        cache: &BuildCache,
     ) -> Result<HashMap<Target, Utf8PathBuf>> {
         let manifest_path = crate_.manifest_path()?;
         let rust_dir = crate_.crate_dir()?;
-        let metadata = crate_.metadata()?;
-        let mut target_files = HashMap::new();
-        let profile = self.common_args.profile();
-        for target in targets {
-            let target =
-                self.cargo_build(target, &manifest_path, cargo_extras, api_level, &rust_dir)?;
-            let library = metadata.library_path(Some(target.triple()), profile);
-            metadata.library_path_exists(&library)?;
-            target_files.insert(target, library);
-        }
-        Ok(target_files)
+        let profile = self.common_args.cargo_profile(cargo);
+        self.common_args
+            .build_targets(metadata.library_name(), targets, |target| {
+                let metadata = self.common_args.target_metadata(metadata, target.triple());
+                let library = metadata.library_path(Some(target.triple()), &profile);
+                let mut cmd = self.cargo_cmd(target, &manifest_path, android, cargo);
+                cmd.current_dir(&rust_dir);
+                if cache.is_fresh_cmd(&target.to_string(), &cmd, &library) {
+                    info!("{target}: up to date");
+                    return Ok((library, true));
+                }
+                self.common_args.run_cargo(&mut cmd, &metadata, target)?;
+                metadata.library_path_exists(&library)?;
+                Ok((library, false))
+            })
     }
 
-    fn cargo_build(
+    fn cargo_cmd(
         &self,
         target: &Target,
         manifest_path: &Utf8PathBuf,
-        cargo_extras: &ExtraArgs,
-        api_level: usize,
-        rust_dir: &Utf8PathBuf,
-    ) -> Result<Target> {
+        android: &AndroidConfig,
+        cargo: &CargoSettings,
+    ) -> Command {
         let mut cmd = Command::new("cargo");
         cmd.arg("ndk")
             .arg("--manifest-path")
@@ -219,34 +352,30 @@ impl AndroidArgs {
             .arg("--target")
             .arg(target.to_string())
             .arg("--platform")
-            .arg(format!("{}", api_level));
-        if !self.common_args.release {
+            .arg(format!("{}", android.api_level));
+        if !self.common_args.is_release(cargo) || self.symbols_config(android, cargo).is_some() {
             cmd.arg("--no-strip");
         }
         cmd.arg("--").arg("build");
-        if self.common_args.release {
-            cmd.arg("--release");
-        }
-        cmd.args(cargo_extras.clone());
-        run_cmd(cmd.current_dir(rust_dir))?;
-        Ok(target.clone())
+        cargo.apply(&mut cmd, &self.common_args.cargo_profile(cargo));
+        cmd.args(android.cargo_extras.clone());
+        cmd
     }
 
     fn find_existing(
         &self,
         metadata: &CrateMetadata,
+        cargo: &CargoSettings,
         targets: &[Target],
     ) -> HashMap<Target, Utf8PathBuf> {
-        let profile = self.common_args.profile();
+        let profile = self.common_args.cargo_profile(cargo);
         targets
             .iter()
             .filter_map(|target| {
-                let library = metadata.library_path(Some(target.triple()), profile);
-                if library.exists() {
-                    Some((target.clone(), library))
-                } else {
-                    None
-                }
+                let library =
+                    self.common_args
+                        .find_existing(metadata, target, target.triple(), &profile)?;
+                Some((target.clone(), library))
             })
             .collect()
     }
@@ -256,28 +385,203 @@ impl AndroidArgs {
         metadata: &CrateMetadata,
         jni_libs: &Utf8Path,
         target_files: &HashMap<Target, Utf8PathBuf>,
-    ) -> Result<()> {
-        println!("-- Copying into jniLibs directory");
-        println!("rm -Rf {jni_libs}");
-        rm_dir(jni_libs)?;
+    ) -> Result<HashMap<Target, Utf8PathBuf>> {
+        info!("-- Copying into jniLibs directory");
+        let mut copied = HashMap::new();
         for (target, library) in target_files {
             let dst_dir = jni_libs.join(target.to_string());
             mk_dir(&dst_dir)?;
 
             let dst_lib = dst_dir.join(metadata.library_file(Some(target.triple())));
-            println!("cp {library} {dst_lib}");
-            fs::copy(library, &dst_lib)?;
+            cp_file(library, &dst_lib)?;
+            emit(Message::Copied {
+                from: library.clone(),
+                to: dst_lib.clone(),
+            });
+            copied.insert(target.clone(), dst_lib);
+        }
+        Ok(copied)
+    }
+
+    /// Run Gradle to make the AAR, once the libraries have been built and the turbo-module
+    /// generated.
+    pub(crate) fn package_aar(&self) -> Result<()> {
+        let config = self.project_config()?;
+        let root = config.project_root();
+        let android = &config.android;
+        let android_dir = android.directory(root);
+        self.stage_prefab_headers(&config, &android_dir.join(android.prefab_headers()))?;
+
+        let cargo = config.crates[0].cargo.merge(&android.cargo);
+        let variant = if self.common_args.is_release(&cargo) {
+            "release"
+        } else {
+            "debug"
+        };
+        let targets = if !self.targets.is_empty() {
+            &self.targets
+        } else {
+            &android.targets
+        };
+        let abis = targets
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let task = format!("assemble{}", variant[..1].to_uppercase() + &variant[1..]);
+        let mut cmd = gradle_cmd(root, &android_dir, config.raw_name(), &task)?;
+        cmd.arg("-PubrnAar=true").arg(format!("-PubrnAbis={abis}"));
+        timed("gradle", || run_cmd(&mut cmd))?;
+
+        let outputs = android_dir.join("build/outputs/aar");
+        let aar = file_paths(&format!("{outputs}/*-{variant}.aar"))?
+            .into_iter()
+            .find_map(|p| Utf8PathBuf::from_path_buf(p.into()).ok());
+        match aar {
+            Some(aar) => {
+                info!("Built {aar}");
+                emit(Message::Aar { path: aar });
+            }
+            None if dry_run() => (),
+            None => anyhow::bail!("Gradle ran, but there is no {variant} AAR in {outputs}"),
+        }
+        Ok(())
+    }
+
+    /// Copy the headers of the turbo-module and of the generated bindings into one
+    /// directory, for Gradle to put into the prefab package.
+    fn stage_prefab_headers(&self, config: &ProjectConfig, dir: &Utf8Path) -> Result<()> {
+        let root = config.project_root();
+        rm_dir(dir)?;
+        mk_dir(dir)?;
+        for src in [config.tm.cpp_path(root), config.bindings.cpp_path(root)] {
+            for ext in ["h", "hpp"] {
+                for file in file_paths(&format!("{src}/*.{ext}"))? {
+                    let file = Utf8PathBuf::try_from(std::path::PathBuf::from(file))?;
+                    let name = file.file_name().expect("A header is a file");
+                    cp_file(&file, &dir.join(name))?;
+                }
+            }
         }
         Ok(())
     }
 
     pub(crate) fn project_config(&self) -> Result<ProjectConfig> {
-        self.config.clone().try_into()
+        self.config.load()
+    }
+
+    pub(crate) fn config(&self) -> &ConfigArgs {
+        &self.config
+    }
+}
+
+/// Find the Android NDK, in the same places that `cargo ndk` looks.
+///
+/// This is the first of `ANDROID_NDK_HOME`, `ANDROID_NDK_ROOT` or `ANDROID_NDK`, or else
+/// the newest NDK installed in the `ndk` directory of the Android SDK.
+pub(crate) fn find_ndk() -> Option<Utf8PathBuf> {
+    let from_env = ["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT", "ANDROID_NDK"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .map(Utf8PathBuf::from)
+        .find(|p| p.is_dir());
+    if from_env.is_some() {
+        return from_env;
+    }
+
+    ["ANDROID_HOME", "ANDROID_SDK_ROOT"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .map(Utf8PathBuf::from)
+        .find_map(|sdk| {
+            let mut versions = sdk
+                .join("ndk")
+                .read_dir_utf8()
+                .ok()?
+                .filter_map(Result::ok)
+                .map(|e| e.into_path())
+                .filter(|p| p.join("source.properties").exists())
+                .collect::<Vec<_>>();
+            versions.sort_by_key(|p| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .split('.')
+                    .map(|n| n.parse::<u32>().unwrap_or_default())
+                    .collect::<Vec<_>>()
+            });
+            versions
+                .pop()
+                .or_else(|| Some(sdk.join("ndk-bundle")).filter(|p| p.is_dir()))
+        })
+}
+
+/// A Gradle command to run the task for the library's Android project.
+///
+/// If there is an example app, as made by `create-react-native-library`, then its Gradle
+/// wrapper is used, because the app's build knows where to find React Native. Otherwise,
+/// the library's own wrapper, or else `gradle`, is run in the Android directory.
+fn gradle_cmd(
+    project_root: &Utf8Path,
+    android_dir: &Utf8Path,
+    package_name: &str,
+    task: &str,
+) -> Result<Command> {
+    let gradle = find_gradle(project_root, android_dir)?;
+    let mut cmd = Command::new(&gradle);
+    let example_dir = example_android_dir(project_root);
+    if gradle.starts_with(&example_dir) {
+        // React Native's autolinking names the library's project after its npm package.
+        let project = package_name.replace('@', "").replace('/', "_");
+        cmd.arg(format!(":{project}:{task}"))
+            .current_dir(example_dir);
+    } else {
+        cmd.arg(task).current_dir(android_dir);
     }
+    Ok(cmd)
+}
 
-    pub(crate) fn config(&self) -> Utf8PathBuf {
-        self.config.clone()
+/// Find the Gradle wrapper of the example app or of the library, or else `gradle` itself.
+pub(crate) fn find_gradle(project_root: &Utf8Path, android_dir: &Utf8Path) -> Result<Utf8PathBuf> {
+    let example_dir = example_android_dir(project_root);
+    if let Some(gradlew) = [&example_dir, android_dir]
+        .into_iter()
+        .map(|dir| dir.join("gradlew"))
+        .find(|gradlew| gradlew.exists())
+    {
+        return Ok(gradlew);
     }
+    which::which("gradle")
+        .ok()
+        .and_then(|p| Utf8PathBuf::from_path_buf(p).ok())
+        .with_context(|| {
+            format!("Cannot find Gradle: there is no gradlew in {example_dir} or {android_dir}, and no gradle on the PATH")
+        })
+}
+
+fn example_android_dir(project_root: &Utf8Path) -> Utf8PathBuf {
+    project_root.join("example").join("android")
+}
+
+/// Strip the libraries in place, with the `llvm-strip` from the NDK.
+fn strip<'a>(libraries: impl Iterator<Item = &'a Utf8PathBuf>) -> Result<()> {
+    let llvm_strip = find_ndk_tool("llvm-strip")?;
+    for library in libraries {
+        let mut cmd = Command::new(&llvm_strip);
+        cmd.arg("--strip-unneeded").arg(library);
+        run_cmd(&mut cmd)?;
+    }
+    Ok(())
+}
+
+/// Find one of the LLVM tools which comes with the NDK, e.g. `llvm-strip`.
+pub(crate) fn find_ndk_tool(name: &str) -> Result<Utf8PathBuf> {
+    let ndk = find_ndk().context("Cannot find the Android NDK. Set ANDROID_NDK_HOME")?;
+    let pattern = format!("{ndk}/toolchains/llvm/prebuilt/*/bin/{name}{EXE_SUFFIX}");
+    file_paths(&pattern)?
+        .into_iter()
+        .find_map(|p| Utf8PathBuf::from_path_buf(p.into()).ok())
+        .with_context(|| format!("Cannot find {name} in the Android NDK at {ndk}"))
 }
 
 #[derive(Debug, Deserialize, Default, Clone, Hash, PartialEq, Eq)]
@@ -4,18 +4,65 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
 use anyhow::{anyhow, Result};
 use camino::Utf8PathBuf;
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+use log::{info, warn};
 use serde::Deserialize;
-use ubrn_common::CrateMetadata;
+use ubrn_common::{emit, run_cmd, run_cmd_with_prefix, timed, CrateMetadata, Message, Watcher};
 
-use crate::{android::AndroidArgs, generate::GenerateAllArgs, ios::IOsArgs};
+use crate::{
+    android::AndroidArgs,
+    codegen,
+    config::{ConfigArgs, ProjectConfig},
+    fingerprint::BuildCache,
+    generate::GenerateAllArgs,
+    ios::IOsArgs,
+};
 
 #[derive(Args, Debug)]
 pub(crate) struct BuildArgs {
     #[clap(subcommand)]
     cmd: BuildCmd,
+
+    /// Watch the Rust crate and the uniffi.toml file, and rebuild and regenerate
+    /// the bindings whenever they change.
+    ///
+    /// This implies `--and-generate`.
+    #[clap(long, global = true)]
+    watch: bool,
+
+    /// How to report what was built.
+    ///
+    /// With `json`, a JSON object is written to stdout for each target built, library
+    /// copied and file generated, one per line, followed by a summary of the whole build.
+    /// Everything else is written to stderr.
+    #[clap(long, global = true, value_enum, default_value = "human")]
+    message_format: MessageFormat,
+
+    /// Print the commands which would be run, and the files and directories which would be
+    /// written or removed, without doing any of it.
+    #[clap(long, global = true, conflicts_with = "watch")]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum MessageFormat {
+    Human,
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
@@ -28,39 +75,110 @@ pub(crate) enum BuildCmd {
 
 impl BuildArgs {
     pub(crate) fn build(&self) -> Result<()> {
-        let lib_file = self.cmd.build()?;
-        if self.cmd.and_generate() {
-            self.generate(lib_file)?;
+        ubrn_common::set_json_messages(self.message_format == MessageFormat::Json);
+        ubrn_common::set_dry_run(self.dry_run);
+        if self.watch {
+            return self.watch();
         }
+        self.build_once(self.cmd.and_generate())
+    }
 
-        Ok(())
+    fn build_once(&self, and_generate: bool) -> Result<()> {
+        let start = Instant::now();
+        let result = self.cmd.build().and_then(|lib_files| {
+            if !and_generate {
+                Ok(())
+            } else if let Some(missing) = lib_files.iter().find(|f| self.dry_run && !f.exists()) {
+                info!("Would generate the bindings, once {missing} is built");
+                Ok(())
+            } else {
+                self.generate(lib_files)?;
+                self.cmd.package()
+            }
+        });
+        let error = result.as_ref().err().map(|e| format!("{e:#}"));
+        ubrn_common::emit_summary(start.elapsed(), error);
+        result
+    }
+
+    fn watch(&self) -> Result<()> {
+        let config = self.cmd.project_config()?;
+        let root = config.project_root();
+
+        let mut paths = self.cmd.config().files()?;
+        for crate_ in &config.crates {
+            paths.push(crate_.crate_dir()?);
+        }
+        paths.extend(config.bindings.uniffi_toml_path(root));
+
+        // The build and generate steps write into these directories, so watching
+        // them would cause a rebuild every time, if they are inside the crate.
+        let mut watcher = Watcher::new(paths);
+        for metadata in config.crates_metadata()? {
+            watcher = watcher
+                .ignore(metadata.target_dir())
+                .ignore(&config.ios_framework_path(root, &metadata));
+        }
+        let symbols = [
+            ("android", &config.android.symbols),
+            ("ios", &config.ios.symbols),
+        ];
+        for (platform, symbols) in symbols {
+            if let Some(symbols) = symbols {
+                watcher = watcher.ignore(&symbols.directory(root, platform));
+            }
+        }
+        watcher = watcher
+            .ignore(&config.android.directory(root))
+            .ignore(&config.ios.directory(root))
+            .ignore(&config.bindings.ts_path(root))
+            .ignore(&config.bindings.cpp_path(root));
+        // The turbo-module's directories default to the project's `src` and `cpp`, which
+        // may also be the crate's, so only the generated files themselves are ignored.
+        for file in codegen::file_paths(config) {
+            watcher = watcher.ignore(&file);
+        }
+        watcher.run(|| self.build_once(true))
     }
 
-    fn generate(&self, lib_file: Utf8PathBuf) -> Result<()> {
-        GenerateAllArgs::new(lib_file, self.cmd.config()).run()
+    fn generate(&self, lib_files: Vec<Utf8PathBuf>) -> Result<()> {
+        GenerateAllArgs::new(lib_files, self.cmd.config().clone()).run()
     }
 }
 
 impl BuildCmd {
-    pub(crate) fn build(&self) -> Result<Utf8PathBuf> {
-        let files = match self {
+    /// Build all the crates, returning one library for each crate, to generate the bindings from.
+    pub(crate) fn build(&self) -> Result<Vec<Utf8PathBuf>> {
+        let crates = match self {
             Self::Android(a) => a.build()?,
             Self::Ios(a) => a.build()?,
         };
 
-        files
-            .first()
-            .cloned()
-            .ok_or_else(|| anyhow!("No targets were specified"))
+        crates
+            .into_iter()
+            .map(|files| {
+                files
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No targets were specified"))
+            })
+            .collect()
     }
 
-    fn config(&self) -> Utf8PathBuf {
+    fn config(&self) -> &ConfigArgs {
         match self {
             Self::Android(a) => a.config(),
             Self::Ios(a) => a.config(),
         }
     }
 
+    fn project_config(&self) -> Result<ProjectConfig> {
+        match self {
+            Self::Android(a) => a.project_config(),
+            Self::Ios(a) => a.project_config(),
+        }
+    }
+
     fn common_args(&self) -> &CommonBuildArgs {
         match self {
             Self::Android(a) => &a.common_args,
@@ -69,7 +187,18 @@ impl BuildCmd {
     }
 
     pub(crate) fn and_generate(&self) -> bool {
-        self.common_args().and_generate
+        match self {
+            Self::Android(a) if a.aar => true,
+            _ => self.common_args().and_generate,
+        }
+    }
+
+    /// Package what was built and generated, e.g. as an AAR.
+    fn package(&self) -> Result<()> {
+        match self {
+            Self::Android(a) if a.aar => a.package_aar(),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -90,11 +219,271 @@ pub(crate) struct CommonBuildArgs {
     /// Optionally generate the bindings and turbo-module code for the crate
     #[clap(long = "and-generate", short = 'g')]
     pub(crate) and_generate: bool,
+
+    /// The number of targets to build at the same time.
+    ///
+    /// When more than one, each target is built into its own cargo target
+    /// directory, so the builds don't wait on each other's locks.
+    #[clap(long, short, alias = "parallel-targets", default_value = "1")]
+    pub(crate) jobs: usize,
+
+    /// Build with the given cargo profile, e.g. `release-small`.
+    ///
+    /// This overrides `--release` and any `profile` in the config file.
+    #[clap(long)]
+    pub(crate) profile: Option<String>,
+
+    /// Run cargo for every target, even those which are up to date.
+    ///
+    /// Without this, cargo, lipo and xcodebuild are skipped for the targets whose sources,
+    /// `Cargo.lock`, build settings and tool versions haven't changed since their last
+    /// successful build.
+    #[clap(long)]
+    pub(crate) force: bool,
 }
 
 impl CommonBuildArgs {
-    pub(crate) fn profile<'a>(&self) -> &'a str {
-        CrateMetadata::profile(self.release)
+    /// The cargo profile to build with.
+    ///
+    /// A `--profile` on the command line wins; otherwise a release build uses the `profile`
+    /// from the config file, or `release` if there isn't one. Any other build uses `dev`,
+    /// with a warning if the config file has a `profile`, as it is not used.
+    pub(crate) fn cargo_profile(&self, cargo: &CargoSettings) -> String {
+        if let Some(profile) = &self.profile {
+            return profile.clone();
+        }
+        if self.release {
+            return cargo.profile.as_deref().unwrap_or("release").to_string();
+        }
+        if let Some(profile) = &cargo.profile {
+            static WARNED: AtomicBool = AtomicBool::new(false);
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                warn!("The `{profile}` profile in the config file is only used with --release; building with `dev`");
+            }
+        }
+        "dev".to_string()
+    }
+
+    /// Is this an optimized build, i.e. one which should be stripped of its debug symbols?
+    ///
+    /// This goes by the name of the profile, as cargo doesn't say what a custom profile
+    /// `inherits`: only `dev`, and `test` which inherits from it, are debug builds. Any other
+    /// profile, even a custom one which inherits from `dev`, is treated as a release build.
+    pub(crate) fn is_release(&self, cargo: &CargoSettings) -> bool {
+        !matches!(self.cargo_profile(cargo).as_str(), "dev" | "debug" | "test")
+    }
+
+    /// The cache of fingerprints for the crate's builds.
+    pub(crate) fn build_cache(&self, metadata: &CrateMetadata, extra: &str) -> Result<BuildCache> {
+        BuildCache::new(metadata, extra, self.force)
+    }
+
+    pub(crate) fn is_parallel(&self) -> bool {
+        self.jobs > 1
+    }
+
+    /// The metadata for the cargo target directory used to build the given target.
+    ///
+    /// Parallel builds each get their own target directory, in
+    /// `target/parallel/<triple>`.
+    pub(crate) fn target_metadata(&self, metadata: &CrateMetadata, triple: &str) -> CrateMetadata {
+        if self.is_parallel() {
+            parallel_metadata(metadata, triple)
+        } else {
+            metadata.clone()
+        }
+    }
+
+    /// Run a cargo command for the given target.
+    ///
+    /// If this is a parallel build, then cargo is pointed at the target's own
+    /// target directory, and the output is prefixed with the target name.
+    pub(crate) fn run_cargo(
+        &self,
+        cmd: &mut Command,
+        metadata: &CrateMetadata,
+        target: impl Display,
+    ) -> Result<()> {
+        timed(&format!("cargo ({target})"), || {
+            if self.is_parallel() {
+                cmd.env("CARGO_TARGET_DIR", metadata.target_dir());
+                run_cmd_with_prefix(cmd, &target.to_string())
+            } else {
+                run_cmd(cmd)
+            }
+        })
+    }
+
+    /// Find the library for the given target, built by an earlier build in the same mode.
+    ///
+    /// Only the target directory this build would use is looked in: a library left in
+    /// the other one, by an earlier sequential or parallel build, may be out of date.
+    pub(crate) fn find_existing(
+        &self,
+        metadata: &CrateMetadata,
+        target: impl Display,
+        triple: &str,
+        profile: &str,
+    ) -> Option<Utf8PathBuf> {
+        let library = self
+            .target_metadata(metadata, triple)
+            .library_path(Some(triple), profile);
+        if !library.exists() {
+            return None;
+        }
+        emit(Message::TargetFinished {
+            library_name: metadata.library_name().to_string(),
+            target: target.to_string(),
+            path: Some(library.clone()),
+            duration: 0.0,
+            fresh: true,
+            error: None,
+        });
+        Some(library)
+    }
+
+    /// Build each of the targets, up to `--jobs` at a time.
+    ///
+    /// The build returns the library, and whether it was already up to date.
+    ///
+    /// Unlike a sequential loop, this does not stop at the first failure: every target is
+    /// attempted, and the failures are reported together.
+    pub(crate) fn build_targets<T, F>(
+        &self,
+        library_name: &str,
+        targets: &[T],
+        build: F,
+    ) -> Result<HashMap<T, Utf8PathBuf>>
+    where
+        T: Clone + Display + Eq + Hash + Send + Sync,
+        F: Fn(&T) -> Result<(Utf8PathBuf, bool)> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::new());
+        let workers = self.jobs.clamp(1, targets.len().max(1));
+        thread::scope(|s| {
+            for _ in 0..workers {
+                s.spawn(|| {
+                    while let Some(target) = targets.get(next.fetch_add(1, Ordering::SeqCst)) {
+                        emit(Message::TargetStarted {
+                            library_name: library_name.to_string(),
+                            target: target.to_string(),
+                        });
+                        let start = Instant::now();
+                        let result = build(target);
+                        emit(Message::TargetFinished {
+                            library_name: library_name.to_string(),
+                            target: target.to_string(),
+                            path: result.as_ref().ok().map(|(library, _)| library.clone()),
+                            duration: start.elapsed().as_secs_f64(),
+                            fresh: matches!(result, Ok((_, true))),
+                            error: result.as_ref().err().map(|e| format!("{e:#}")),
+                        });
+                        results
+                            .lock()
+                            .expect("No other builds have panicked")
+                            .push((target.clone(), result));
+                    }
+                });
+            }
+        });
+
+        let mut target_files = HashMap::new();
+        let mut failures = Vec::new();
+        for (target, result) in results.into_inner().expect("No builds have panicked") {
+            match result {
+                Ok((library, _)) => {
+                    target_files.insert(target, library);
+                }
+                Err(e) => failures.push(format!("  {target}: {e:#}")),
+            }
+        }
+        if !failures.is_empty() {
+            anyhow::bail!(
+                "{} of {} targets failed to build:\n{}",
+                failures.len(),
+                targets.len(),
+                failures.join("\n")
+            );
+        }
+        Ok(target_files)
+    }
+}
+
+fn parallel_metadata(metadata: &CrateMetadata, triple: &str) -> CrateMetadata {
+    let target_dir = metadata.target_dir().join("parallel").join(triple);
+    metadata.with_target_dir(target_dir)
+}
+
+/// The cargo settings which can be given in the `rust` section of the config file, and
+/// overridden for each platform in the `android` and `ios` sections.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CargoSettings {
+    #[serde(default)]
+    pub(crate) features: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub(crate) no_default_features: Option<bool>,
+
+    #[serde(default)]
+    pub(crate) profile: Option<String>,
+
+    #[serde(default)]
+    pub(crate) rustflags: Option<ExtraArgs>,
+
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+}
+
+impl CargoSettings {
+    /// These settings, with any given in `overrides` taking precedence.
+    ///
+    /// The environment variables are merged, rather than replaced.
+    pub(crate) fn merge(&self, overrides: &Self) -> Self {
+        let mut env = self.env.clone();
+        env.extend(overrides.env.clone());
+        Self {
+            features: overrides.features.clone().or_else(|| self.features.clone()),
+            no_default_features: overrides.no_default_features.or(self.no_default_features),
+            profile: overrides.profile.clone().or_else(|| self.profile.clone()),
+            rustflags: overrides
+                .rustflags
+                .clone()
+                .or_else(|| self.rustflags.clone()),
+            env,
+        }
+    }
+
+    /// Add the arguments and environment for these settings to a `cargo build` command.
+    pub(crate) fn apply(&self, cmd: &mut Command, profile: &str) {
+        match profile {
+            "dev" | "debug" => (),
+            "release" => {
+                cmd.arg("--release");
+            }
+            profile => {
+                cmd.arg("--profile").arg(profile);
+            }
+        }
+        if let Some(features) = &self.features {
+            if !features.is_empty() {
+                cmd.arg("--features").arg(features.join(","));
+            }
+        }
+        if self.no_default_features == Some(true) {
+            cmd.arg("--no-default-features");
+        }
+        if let Some(rustflags) = &self.rustflags {
+            let mut flags = std::env::var("RUSTFLAGS")
+                .ok()
+                .into_iter()
+                .filter(|f| !f.is_empty())
+                .collect::<Vec<_>>();
+            flags.extend(rustflags.clone());
+            cmd.env("RUSTFLAGS", flags.join(" "));
+        }
+        cmd.envs(&self.env);
     }
 }
 
@@ -133,3 +522,111 @@ impl From<&[&str]> for ExtraArgs {
         ExtraArgs::AsList(vec)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_args(release: bool, profile: Option<&str>) -> CommonBuildArgs {
+        CommonBuildArgs {
+            release,
+            no_cargo: false,
+            and_generate: false,
+            jobs: 1,
+            profile: profile.map(str::to_owned),
+            force: false,
+        }
+    }
+
+    fn cargo_settings(profile: Option<&str>) -> CargoSettings {
+        CargoSettings {
+            profile: profile.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cargo_profile() {
+        let no_config = cargo_settings(None);
+        let config = cargo_settings(Some("release-small"));
+
+        assert_eq!(build_args(false, None).cargo_profile(&no_config), "dev");
+        assert_eq!(build_args(true, None).cargo_profile(&no_config), "release");
+
+        // The config's profile is only for release builds.
+        assert_eq!(build_args(false, None).cargo_profile(&config), "dev");
+        assert_eq!(
+            build_args(true, None).cargo_profile(&config),
+            "release-small"
+        );
+
+        // --profile wins over both --release and the config.
+        for release in [false, true] {
+            let args = build_args(release, Some("bench"));
+            assert_eq!(args.cargo_profile(&no_config), "bench");
+            assert_eq!(args.cargo_profile(&config), "bench");
+        }
+    }
+
+    #[test]
+    fn test_is_release() {
+        let config = cargo_settings(Some("release-small"));
+        assert!(!build_args(false, None).is_release(&config));
+        assert!(build_args(true, None).is_release(&config));
+        assert!(!build_args(true, Some("dev")).is_release(&config));
+        assert!(build_args(false, Some("bench")).is_release(&config));
+    }
+
+    #[test]
+    fn test_build_targets() -> Result<()> {
+        let targets = ["a", "b", "c", "d", "e"];
+        for jobs in [1, 3, 10] {
+            let args = CommonBuildArgs {
+                jobs,
+                ..build_args(false, None)
+            };
+            let built = Mutex::new(Vec::new());
+            let files = args.build_targets("lib", &targets, |target| {
+                built.lock().unwrap().push(*target);
+                Ok((Utf8PathBuf::from(format!("{target}/lib.so")), false))
+            })?;
+
+            // Each target is built once, whatever the number of jobs.
+            let mut built = built.into_inner().unwrap();
+            built.sort();
+            assert_eq!(built, targets);
+            assert_eq!(files.len(), targets.len());
+            assert_eq!(files["c"], "c/lib.so");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_targets_failures() {
+        let targets = ["a", "b", "c", "d"];
+        let args = CommonBuildArgs {
+            jobs: 2,
+            ..build_args(false, None)
+        };
+        let built = AtomicUsize::new(0);
+        let error = args
+            .build_targets("lib", &targets, |target| {
+                built.fetch_add(1, Ordering::SeqCst);
+                match *target {
+                    "b" | "d" => anyhow::bail!("no linker"),
+                    _ => Ok((Utf8PathBuf::from(*target), false)),
+                }
+            })
+            .unwrap_err()
+            .to_string();
+
+        // Every target is attempted, and all of the failures are reported.
+        assert_eq!(built.into_inner(), targets.len());
+        assert!(
+            error.starts_with("2 of 4 targets failed to build:"),
+            "{error}"
+        );
+        assert!(error.contains("  b: no linker"), "{error}");
+        assert!(error.contains("  d: no linker"), "{error}");
+    }
+}
@@ -5,39 +5,115 @@
  */
 use crate::{
     building::BuildArgs,
+    doctor::DoctorArgs,
     generate::GenerateArgs,
+    init::InitArgs,
+    pack::{FetchArgs, PackArgs},
     repo::{CheckoutArgs, GitRepoArgs},
     workspace, AsConfig,
 };
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use camino::Utf8PathBuf;
+use clap::{ArgAction, Parser, Subcommand};
+use log::LevelFilter;
+use ubrn_bindgen::DiffApiArgs;
 
 #[derive(Parser)]
 pub(crate) struct CliArgs {
     #[command(subcommand)]
     pub(crate) cmd: CliCmd,
+
+    /// Print more: `-v` adds timestamps and how long each phase took,
+    /// `-vv` adds everything else
+    #[clap(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only print warnings and errors
+    ///
+    /// The output of commands is only shown if they fail.
+    #[clap(short, long, conflicts_with = "verbose", global = true)]
+    quiet: bool,
+
+    /// Write everything to this file, with timestamps, including the full output of every
+    /// command run, whatever the verbosity
+    #[clap(long, global = true)]
+    log_file: Option<Utf8PathBuf>,
+}
+
+impl CliArgs {
+    pub(crate) fn init_logging(&self) -> Result<()> {
+        let level = match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::Warn,
+            (_, 0) => LevelFilter::Info,
+            (_, 1) => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        };
+        ubrn_common::init_logging(level, self.log_file.as_deref())
+    }
 }
 
 #[derive(Debug, Subcommand)]
 pub(crate) enum CliCmd {
+    /// Create the configuration files for a new React Native library project
+    Init(InitArgs),
     /// Checkout a given Github repo into `rust_modules`
+    ///
+    /// The commit is recorded in `ubrn.lock`, and is used by later checkouts.
     Checkout(CheckoutArgs),
+    /// Update the checkout in `rust_modules` to the latest commit of its branch
+    /// or tag, and record it in `ubrn.lock`
+    Update(CheckoutArgs),
     /// Build (and optionally generate code) for Android or iOS
     Build(BuildArgs),
     /// Generate bindings or the turbo-module glue code from the Rust.
     ///
     /// These steps are already performed when building with `--and-generate`.
     Generate(GenerateArgs),
+    /// Check that the tools needed to build for Android and iOS are installed,
+    /// and that the configuration file can be read.
+    ///
+    /// Exits with a non-zero status if any problems are found.
+    Doctor(DoctorArgs),
+    /// Compare the API of two builds of a crate, and recommend a semver bump.
+    ///
+    /// Each change is classified as breaking, additive or internal for
+    /// Javascript callers of the generated bindings.
+    DiffApi(DiffApiArgs),
+    /// Bundle the built Android libraries and iOS xcframeworks into tarballs, with a
+    /// manifest of their hashes, for publishing.
+    Pack(PackArgs),
+    /// Download the tarballs made by `pack`, check their hashes, and unpack them to
+    /// where `build` would put the libraries.
+    ///
+    /// This is meant to be run when the npm package is installed, falling back to
+    /// building from source if it fails.
+    Fetch(FetchArgs),
 }
 
 impl CliCmd {
     pub(crate) fn run(&self) -> Result<()> {
         match self {
+            Self::Init(i) => i.run(),
             Self::Checkout(c) => {
-                AsConfig::<GitRepoArgs>::as_config(c)?.checkout(&workspace::project_root()?)
+                let project_root = workspace::project_root()?;
+                for repo in AsConfig::<Vec<GitRepoArgs>>::as_config(c)? {
+                    repo.checkout(&project_root)?;
+                }
+                Ok(())
+            }
+            Self::Update(c) => {
+                let project_root = workspace::project_root()?;
+                for repo in AsConfig::<Vec<GitRepoArgs>>::as_config(c)? {
+                    repo.update(&project_root)?;
+                }
+                Ok(())
             }
             Self::Build(b) => b.build(),
             Self::Generate(g) => g.run(),
+            Self::Doctor(d) => d.run(),
+            Self::DiffApi(d) => d.run(),
+            Self::Pack(p) => p.run(),
+            Self::Fetch(f) => f.run(),
         }
     }
 }
@@ -10,15 +10,19 @@ use clap::Args;
 use std::{collections::BTreeMap, rc::Rc};
 
 use ubrn_bindgen::ModuleMetadata;
-use ubrn_common::{mk_dir, CrateMetadata};
+use ubrn_common::{timed, CrateMetadata, GeneratedFiles};
 
-use crate::config::ProjectConfig;
+use crate::config::{ConfigArgs, ProjectConfig};
 
 #[derive(Args, Debug)]
 pub(crate) struct TurboModuleArgs {
-    /// The configuration file for this build
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    /// Do not write any files, but exit with an error if any generated file
+    /// is out of date.
     #[clap(long)]
-    config: Utf8PathBuf,
+    check: bool,
 
     /// The namespaces that are generated by `generate bindings`.
     namespaces: Vec<String>,
@@ -26,15 +30,16 @@ pub(crate) struct TurboModuleArgs {
 
 impl TurboModuleArgs {
     pub(crate) fn run(&self) -> Result<()> {
-        let project = ProjectConfig::try_from(self.config.clone())?;
+        let project = self.config.load()?;
         let modules = self
             .namespaces
             .iter()
             .map(|s| ModuleMetadata::new(s))
             .collect();
-        let rust_crate = project.crate_.metadata()?;
-        render_files(project, rust_crate, modules)?;
-        Ok(())
+        let rust_crates = project.crates_metadata()?;
+        let mut files = GeneratedFiles::new(self.check);
+        render_files(project, rust_crates, modules, &mut files)?;
+        files.finish()
     }
 }
 
@@ -54,21 +59,21 @@ pub(crate) trait RenderedFile: DynTemplate {
 
 pub(crate) struct TemplateConfig {
     pub(crate) project: ProjectConfig,
-    pub(crate) rust_crate: CrateMetadata,
+    pub(crate) rust_crates: Vec<CrateMetadata>,
     pub(crate) modules: Vec<ModuleMetadata>,
 }
 
 impl TemplateConfig {
     pub(crate) fn new(
         project: ProjectConfig,
-        rust_crate: CrateMetadata,
+        rust_crates: Vec<CrateMetadata>,
         modules: Vec<ModuleMetadata>,
     ) -> Self {
         let mut modules = modules;
         modules.sort_by_key(|m| m.ts());
         Self {
             project,
-            rust_crate,
+            rust_crates,
             modules,
         }
     }
@@ -76,14 +81,15 @@ impl TemplateConfig {
 
 pub(crate) fn render_files(
     project: ProjectConfig,
-    rust_crate: CrateMetadata,
+    rust_crates: Vec<CrateMetadata>,
     modules: Vec<ModuleMetadata>,
+    generated: &mut GeneratedFiles,
 ) -> Result<()> {
-    let config = Rc::new(TemplateConfig::new(project, rust_crate, modules));
+    let config = Rc::new(TemplateConfig::new(project, rust_crates, modules));
     let files = files::get_files(config.clone());
 
     let project_root = config.project.project_root();
-    let map = render_templates(project_root, files)?;
+    let map = timed("codegen", || render_templates(project_root, files))?;
     let exclude_files = config.project.exclude_files();
     for (path, contents) in map {
         // We don't want to write files that the config file has excluded.
@@ -92,16 +98,25 @@ pub(crate) fn render_files(
         let rel = pathdiff::diff_utf8_paths(&path, project_root)
             .expect("path should be relative to root");
         if exclude_files.is_match(&rel) {
+            generated.exclude(&path);
             continue;
         }
-        let parent = path.parent().expect("Parent for path");
-        mk_dir(parent)?;
-        std::fs::write(path, contents)?;
+        generated.write(&path, &contents)?;
     }
 
     Ok(())
 }
 
+/// The paths of the turbo-module files which `render_files` writes.
+pub(crate) fn file_paths(project: ProjectConfig) -> Vec<Utf8PathBuf> {
+    let config = Rc::new(TemplateConfig::new(project, vec![], vec![]));
+    let project_root = config.project.project_root();
+    files::get_files(config.clone())
+        .iter()
+        .map(|f| f.path(project_root))
+        .collect()
+}
+
 fn render_templates(
     project_root: &Utf8Path,
     files: Vec<Rc<dyn RenderedFile>>,
@@ -319,6 +334,9 @@ mod tests {
                 cargo_extras: ExtraArgs::default(),
                 api_level: 21,
                 package_name: "com.tester".to_string(),
+                library_type: Default::default(),
+                symbols: None,
+                cargo: Default::default(),
             };
             let ios = IOsConfig {
                 directory: "ios".to_string(),
@@ -326,6 +344,9 @@ mod tests {
                 xcodebuild_extras: ExtraArgs::default(),
                 targets: Default::default(),
                 cargo_extras: ExtraArgs::default(),
+                library_type: Default::default(),
+                symbols: None,
+                cargo: Default::default(),
             };
             let bindings = BindingsConfig {
                 cpp: "cpp/bindings".to_string(),
@@ -343,7 +364,7 @@ mod tests {
             Self {
                 name: name.to_string(),
                 repository,
-                crate_,
+                crates: vec![crate_],
                 android,
                 ios,
                 bindings,
@@ -364,11 +385,8 @@ mod tests {
         );
 
         let project_config = config::ProjectConfig::empty(name, crate_config);
-        let modules = modules
-            .into_iter()
-            .map(|s| ModuleMetadata::new(s))
-            .collect();
-        let template = TemplateConfig::new(project_config, crate_metadata, modules);
+        let modules = modules.iter().map(|s| ModuleMetadata::new(s)).collect();
+        let template = TemplateConfig::new(project_config, vec![crate_metadata], modules);
         Ok(Rc::new(template))
     }
 
@@ -25,6 +25,11 @@ public class {{ module_class_name }} extends {{ self.config.project.codegen_file
   }
 
   static {
+    {%- if android.library_type.is_shared() %}
+    {%- for rust_crate in self.config.rust_crates %}
+    System.loadLibrary("{{ rust_crate.library_name() }}");
+    {%- endfor %}
+    {%- endif %}
     System.loadLibrary("{{ self.config.project.cpp_filename() }}");
   }
 
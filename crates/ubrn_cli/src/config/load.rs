@@ -0,0 +1,245 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use serde_yaml::Value;
+
+use super::{
+    validate::{validate_value, Source},
+    ProjectConfig,
+};
+
+/// The names of the configuration file, in order of preference, if there is more than one
+/// in the same directory.
+const CONFIG_FILES: &[&str] = &["ubrn.config.yaml", "ubrn.config.yml", "ubrn.config.json"];
+
+/// The key in `package.json` which the configuration can be kept under, instead of in a file
+/// of its own.
+const PACKAGE_JSON_KEY: &str = "ubrn";
+
+#[derive(Args, Clone, Debug)]
+pub(crate) struct ConfigArgs {
+    /// The configuration file for this project.
+    ///
+    /// If not given, then the nearest `ubrn.config.yaml`, `ubrn.config.yml` or
+    /// `ubrn.config.json` is used, or else the `ubrn` key of `package.json`.
+    #[clap(long)]
+    config: Option<Utf8PathBuf>,
+
+    /// Merge another configuration file over the first one, e.g. `--env ci` merges
+    /// `ubrn.config.ci.yaml` over `ubrn.config.yaml`.
+    ///
+    /// Maps are merged key by key; lists and other values are replaced.
+    #[clap(long)]
+    env: Option<String>,
+}
+
+/// A file which the configuration is read from.
+struct ConfigFile {
+    path: Utf8PathBuf,
+    /// The key which the configuration is under, if this is `package.json`.
+    key: Option<&'static str>,
+}
+
+impl ConfigArgs {
+    /// The files which the configuration is read from, with the base file first.
+    pub(crate) fn files(&self) -> Result<Vec<Utf8PathBuf>> {
+        let base = self.base()?;
+        let overlay = self.overlay(&base)?;
+        Ok([Some(base.path), overlay].into_iter().flatten().collect())
+    }
+
+    /// Find, merge, validate and deserialize the configuration.
+    pub(crate) fn load(&self) -> Result<ProjectConfig> {
+        let base = self.base()?;
+        let base_text = read(&base.path)?;
+        let mut value = parse(&base.path, &base_text)?;
+        if let Some(key) = base.key {
+            value = value
+                .get(key)
+                .cloned()
+                .with_context(|| format!("There is no `{key}` key in {}", base.path))?;
+        }
+        let mut description = base.path.to_string();
+        let mut sources = vec![Source {
+            name: file_name(&base.path),
+            text: &base_text,
+            key: base.key,
+        }];
+
+        let overlay = self.overlay(&base)?;
+        let overlay_text = overlay.as_deref().map(read).transpose()?;
+        if let (Some(path), Some(text)) = (&overlay, &overlay_text) {
+            merge(&mut value, parse(path, text)?);
+            description = format!("{description} with {path}");
+            sources.push(Source {
+                name: file_name(path),
+                text,
+                key: None,
+            });
+        }
+
+        validate_value(&description, &value, &sources)?;
+        serde_yaml::from_value(value)
+            .with_context(|| format!("Failed to read the configuration in {description}"))
+    }
+
+    fn base(&self) -> Result<ConfigFile> {
+        if let Some(path) = &self.config {
+            let key = (path.file_name() == Some("package.json")).then_some(PACKAGE_JSON_KEY);
+            return Ok(ConfigFile {
+                path: path.clone(),
+                key,
+            });
+        }
+
+        let pwd = ubrn_common::pwd()?;
+        let mut found = Vec::new();
+        for name in CONFIG_FILES {
+            found.extend(ubrn_common::resolve(&pwd, name)?);
+        }
+        // The nearest file wins; in the same directory, the first of `CONFIG_FILES` does.
+        let nearest = found
+            .into_iter()
+            .rev()
+            .max_by_key(|p| p.components().count());
+        if let Some(path) = nearest {
+            return Ok(ConfigFile { path, key: None });
+        }
+
+        if let Some(path) = ubrn_common::resolve(&pwd, "package.json")? {
+            let package_json = parse(&path, &read(&path)?)?;
+            if package_json.get(PACKAGE_JSON_KEY).is_some() {
+                return Ok(ConfigFile {
+                    path,
+                    key: Some(PACKAGE_JSON_KEY),
+                });
+            }
+        }
+
+        anyhow::bail!(
+            "Cannot find a configuration file: expected one of {}, or a `{PACKAGE_JSON_KEY}` key in package.json. Use `--config` to give one",
+            CONFIG_FILES.join(", ")
+        )
+    }
+
+    /// The file for the `--env`, next to the base file.
+    ///
+    /// If the configuration is in `package.json`, then this is one of the `CONFIG_FILES`
+    /// with the env name added, e.g. `ubrn.config.ci.yaml`.
+    fn overlay(&self, base: &ConfigFile) -> Result<Option<Utf8PathBuf>> {
+        let Some(env) = &self.env else {
+            return Ok(None);
+        };
+        let dir = base.path.parent().unwrap_or(Utf8Path::new("."));
+        let candidates = if base.key.is_some() {
+            CONFIG_FILES
+                .iter()
+                .map(|f| dir.join(with_env(Utf8Path::new(f), env)))
+                .collect::<Vec<_>>()
+        } else {
+            vec![dir.join(with_env(&base.path, env))]
+        };
+        match candidates.iter().find(|p| p.exists()) {
+            Some(path) => Ok(Some(path.clone())),
+            None => anyhow::bail!(
+                "Cannot find the configuration for `--env {env}`: expected {}",
+                candidates[0]
+            ),
+        }
+    }
+}
+
+/// The name of the file, with the env name before the extension.
+fn with_env(file: &Utf8Path, env: &str) -> String {
+    match (file.file_stem(), file.extension()) {
+        (Some(stem), Some(ext)) => format!("{stem}.{env}.{ext}"),
+        _ => format!("{}.{env}", file_name(file)),
+    }
+}
+
+fn file_name(file: &Utf8Path) -> &str {
+    file.file_name().unwrap_or(file.as_str())
+}
+
+fn read(file: &Utf8Path) -> Result<String> {
+    std::fs::read_to_string(file).with_context(|| format!("Failed to read from {file:?}"))
+}
+
+fn parse(file: &Utf8Path, text: &str) -> Result<Value> {
+    // JSON is a subset of YAML, so this works for both file formats.
+    serde_yaml::from_str(text)
+        .with_context(|| format!("Failed to read {file:?} as valid YAML or JSON"))
+}
+
+/// Merge the overlay into the base: maps are merged key by key, and anything else is replaced.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (k, v) in overlay {
+                match base.get_mut(&k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut base: Value = serde_yaml::from_str(
+            r#"
+rust:
+  directory: ./rust
+android:
+  targets: [arm64-v8a, x86_64]
+  cargoExtras: [--locked]
+"#,
+        )
+        .unwrap();
+        let overlay: Value = serde_yaml::from_str(
+            r#"
+android:
+  targets: [x86_64]
+ios:
+  targets: [aarch64-apple-ios-sim]
+"#,
+        )
+        .unwrap();
+        merge(&mut base, overlay);
+        let expected: Value = serde_yaml::from_str(
+            r#"
+rust:
+  directory: ./rust
+android:
+  targets: [x86_64]
+  cargoExtras: [--locked]
+ios:
+  targets: [aarch64-apple-ios-sim]
+"#,
+        )
+        .unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_with_env() {
+        assert_eq!(
+            with_env(Utf8Path::new("dir/ubrn.config.yaml"), "ci"),
+            "ubrn.config.ci.yaml"
+        );
+        assert_eq!(with_env(Utf8Path::new("config"), "ci"), "config.ci");
+    }
+}
@@ -3,13 +3,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
+mod load;
 mod npm;
+mod validate;
 
+use std::collections::HashSet;
+
+use anyhow::Result;
 use camino::{Utf8Path, Utf8PathBuf};
 use globset::GlobSet;
+pub(crate) use load::ConfigArgs;
 pub(crate) use npm::PackageJson;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use ubrn_common::CrateMetadata;
 
 use crate::{android::AndroidConfig, ios::IOsConfig, rust::CrateConfig, workspace};
 
@@ -22,8 +29,9 @@ pub(crate) struct ProjectConfig {
     #[serde(default = "ProjectConfig::default_repository")]
     pub(crate) repository: String,
 
-    #[serde(rename = "rust", alias = "crate")]
-    pub(crate) crate_: CrateConfig,
+    /// The crates to build, from the `rust` section: either one crate, or a list of them.
+    #[serde(rename = "rust", alias = "crate", deserialize_with = "one_or_more")]
+    pub(crate) crates: Vec<CrateConfig>,
 
     #[serde(default)]
     pub(crate) android: AndroidConfig,
@@ -55,11 +63,11 @@ impl ProjectConfig {
     }
 }
 
-fn trim_react_native(name: &str) -> String {
+pub(crate) fn trim_react_native(name: &str) -> String {
     name.trim_matches('-').trim_matches('_').to_string()
 }
 
-fn trim_react_native_2(name: &str) -> String {
+pub(crate) fn trim_react_native_2(name: &str) -> String {
     name.strip_prefix("RN")
         .unwrap_or(name)
         .replace("ReactNative", "")
@@ -71,7 +79,60 @@ fn trim_react_native_2(name: &str) -> String {
 
 impl ProjectConfig {
     pub(crate) fn project_root(&self) -> &Utf8Path {
-        &self.crate_.project_root
+        &self.crates[0].project_root
+    }
+
+    /// The metadata for each of the crates, in the order they are in the config file.
+    ///
+    /// The libraries of all the crates end up side by side in `jniLibs` and in the
+    /// app, so no two crates can have the same library name.
+    pub(crate) fn crates_metadata(&self) -> Result<Vec<CrateMetadata>> {
+        let mut names = HashSet::new();
+        let mut metadata = Vec::new();
+        for crate_ in &self.crates {
+            let m = crate_.metadata()?;
+            if !names.insert(m.library_name().to_string()) {
+                anyhow::bail!(
+                    "More than one crate has the library name `{}`",
+                    m.library_name()
+                );
+            }
+            metadata.push(m);
+        }
+        Ok(metadata)
+    }
+
+    pub(crate) fn has_many_crates(&self) -> bool {
+        self.crates.len() > 1
+    }
+
+    /// The xcframework which the given crate is built into.
+    pub(crate) fn ios_framework_path(
+        &self,
+        project_root: &Utf8Path,
+        metadata: &CrateMetadata,
+    ) -> Utf8PathBuf {
+        let library_name = self.has_many_crates().then(|| metadata.library_name());
+        self.ios.framework_path(project_root, library_name)
+    }
+}
+
+fn one_or_more<'de, D>(deserializer: D) -> Result<Vec<CrateConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMore {
+        One(Box<CrateConfig>),
+        More(Vec<CrateConfig>),
+    }
+    match OneOrMore::deserialize(deserializer)? {
+        OneOrMore::One(c) => Ok(vec![*c]),
+        OneOrMore::More(v) if v.is_empty() => Err(serde::de::Error::custom(
+            "the `rust` section needs at least one crate",
+        )),
+        OneOrMore::More(v) => Ok(v),
     }
 }
 
@@ -14,6 +14,7 @@ use super::{trim_react_native, trim_react_native_2};
 #[allow(dead_code)]
 pub(crate) struct PackageJson {
     name: String,
+    version: Option<String>,
     repository: PackageJsonRepo,
     react_native: Option<String>,
     main: Option<String>,
@@ -25,6 +26,10 @@ impl PackageJson {
         self.name.clone()
     }
 
+    pub(crate) fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
     pub(crate) fn name(&self) -> String {
         trim_react_native(&self.name)
     }
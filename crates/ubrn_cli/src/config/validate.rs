@@ -0,0 +1,1006 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+    str::FromStr,
+};
+
+use anyhow::Result;
+use serde_yaml::{Mapping, Value};
+
+use crate::{android, ios, repo::GitRepoArgs};
+
+/// The lowest API level supported by the NDKs that `cargo ndk` can use.
+const MIN_API_LEVEL: u64 = 21;
+
+// The keys of each section of the config. These must match the fields of the structs they
+// are deserialized into: the tests check that each of them is read.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "name",
+    "repository",
+    "rust",
+    "crate",
+    "android",
+    "ios",
+    "bindings",
+    "turboModule",
+    "noOverwrite",
+];
+const RUST_KEYS: &[&str] = &[
+    "manifestPath",
+    "directory",
+    "src",
+    "rust",
+    "repo",
+    "branch",
+    "tag",
+    "rev",
+    "features",
+    "noDefaultFeatures",
+    "profile",
+    "rustflags",
+    "env",
+];
+const ANDROID_KEYS: &[&str] = &[
+    "directory",
+    "jniLibs",
+    "targets",
+    "cargoExtras",
+    "apiLevel",
+    "platform",
+    "packageName",
+    "libraryType",
+    "symbols",
+    "features",
+    "noDefaultFeatures",
+    "profile",
+    "rustflags",
+    "env",
+];
+const IOS_KEYS: &[&str] = &[
+    "directory",
+    "frameworkName",
+    "xcodebuildExtras",
+    "targets",
+    "cargoExtras",
+    "libraryType",
+    "symbols",
+    "features",
+    "noDefaultFeatures",
+    "profile",
+    "rustflags",
+    "env",
+];
+const SYMBOLS_KEYS: &[&str] = &["directory", "split"];
+const BINDINGS_KEYS: &[&str] = &["cpp", "ts", "uniffiToml"];
+const TURBO_MODULE_KEYS: &[&str] = &["cpp", "ts", "specName", "spec", "name"];
+
+/// The text of a file which the configuration was read from, to find the line numbers of errors.
+pub(crate) struct Source<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) text: &'a str,
+    /// The key which the configuration is under, e.g. `ubrn` in `package.json`.
+    pub(crate) key: Option<&'a str>,
+}
+
+/// Check the configuration before it is deserialized into a `ProjectConfig`.
+///
+/// Serde stops at the first error, and cannot tell the difference between an optional
+/// key which is missing and one that is misspelled. This collects every problem it can find,
+/// with the path and line number of each.
+///
+/// The configuration may have been merged from more than one source: the line numbers
+/// are looked for in the last source first.
+pub(crate) fn validate_value(description: &str, value: &Value, sources: &[Source]) -> Result<()> {
+    let errors = validate_sources(value, sources);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!("Invalid configuration in {description}:");
+    for error in errors {
+        message.push_str(&format!("\n  - {error}"));
+    }
+    anyhow::bail!(message)
+}
+
+fn validate_sources(value: &Value, sources: &[Source]) -> Vec<ConfigError> {
+    let mut validator = Validator {
+        sources,
+        errors: Default::default(),
+    };
+    validator.validate(value);
+    validator.errors
+}
+
+#[derive(Debug)]
+pub(crate) struct ConfigError {
+    path: String,
+    location: Option<String>,
+    message: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} ({location}): {}", self.path, self.message),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+struct Validator<'a> {
+    sources: &'a [Source<'a>],
+    errors: Vec<ConfigError>,
+}
+
+impl Validator<'_> {
+    fn validate(&mut self, value: &Value) {
+        let Some(root) = self.mapping(&[], value) else {
+            return;
+        };
+        self.keys(&[], root, TOP_LEVEL_KEYS);
+
+        let rust = root.get("rust").or_else(|| root.get("crate"));
+        match rust.map(|r| (r, r.as_sequence())) {
+            Some((_, Some(crates))) if crates.is_empty() => {
+                self.error(&[key("rust")], "needs at least one crate")
+            }
+            Some((_, Some(crates))) => {
+                for (i, c) in crates.iter().enumerate() {
+                    self.rust(&[key("rust"), Segment::Index(i)], c);
+                }
+                self.repo_directories(crates);
+            }
+            Some((rust, None)) => self.rust(&[key("rust")], rust),
+            None => self.error(&[], "missing the `rust` section"),
+        }
+        if let Some(android) = root.get("android") {
+            self.android(android);
+        }
+        if let Some(ios) = root.get("ios") {
+            self.ios(ios);
+        }
+        if let Some(bindings) = root.get("bindings") {
+            let path = [key("bindings")];
+            if let Some(map) = self.mapping(&path, bindings) {
+                self.keys(&path, map, BINDINGS_KEYS);
+            }
+        }
+        if let Some(tm) = root.get("turboModule") {
+            let path = [key("turboModule")];
+            if let Some(map) = self.mapping(&path, tm) {
+                self.keys(&path, map, TURBO_MODULE_KEYS);
+            }
+        }
+    }
+
+    fn rust(&mut self, path: &[Segment], value: &Value) {
+        let Some(map) = self.mapping(path, value) else {
+            return;
+        };
+        self.keys(path, map, RUST_KEYS);
+
+        let on_disk = ["directory", "src", "rust"]
+            .iter()
+            .filter(|k| map.contains_key(**k))
+            .collect::<Vec<_>>();
+        let has_repo = map.contains_key("repo");
+        match (on_disk.as_slice(), has_repo) {
+            ([], false) => self.error(
+                path,
+                "needs either a `directory` containing the crate, or a git `repo`",
+            ),
+            ([_, _, ..], _) => {
+                self.error(path, "only one of `directory`, `src` or `rust` can be used")
+            }
+            ([k], true) => self.error(path, format!("cannot have both a `{k}` and a `repo`")),
+            _ => (),
+        }
+        let refs = ["branch", "tag", "rev"]
+            .iter()
+            .filter(|k| map.contains_key(**k))
+            .collect::<Vec<_>>();
+        for k in &refs {
+            if !has_repo {
+                self.error(
+                    &[path, &[key(k)]].concat(),
+                    format!("`{k}` can only be used with a `repo`"),
+                );
+            }
+        }
+        if refs.len() > 1 {
+            self.error(path, "only one of `branch`, `tag` or `rev` can be used");
+        }
+        self.cargo_settings(path, map);
+    }
+
+    /// Check that no two git repos would be checked out into the same directory.
+    ///
+    /// Crates from the same repo share its checkout, so they must also ask for the same
+    /// branch, tag or rev.
+    fn repo_directories(&mut self, crates: &[Value]) {
+        let mut directories: HashMap<&str, &str> = HashMap::new();
+        let mut references: HashMap<&str, (usize, String)> = HashMap::new();
+        for (i, c) in crates.iter().enumerate() {
+            let Some(repo) = c.get("repo").and_then(Value::as_str) else {
+                continue;
+            };
+            let directory = GitRepoArgs::directory_name(repo);
+            match directories.get(directory) {
+                Some(other) if *other != repo => self.error(
+                    &[key("rust"), Segment::Index(i), key("repo")],
+                    format!(
+                        "would be checked out into `rust_modules/{directory}`, like `{other}`: \
+                        repos with the same name cannot be used together"
+                    ),
+                ),
+                Some(_) => (),
+                None => {
+                    directories.insert(directory, repo);
+                }
+            }
+
+            let reference = repo_reference(c);
+            match references.get(repo) {
+                Some((other, other_reference)) if *other_reference != reference => self.error(
+                    &[key("rust"), Segment::Index(i), key("repo")],
+                    format!(
+                        "uses the {reference}, but `rust[{other}]` uses the {other_reference} \
+                        of the same repo: crates from one repo must use the same branch, tag or rev"
+                    ),
+                ),
+                Some(_) => (),
+                None => {
+                    references.insert(repo, (i, reference));
+                }
+            }
+        }
+    }
+
+    fn android(&mut self, value: &Value) {
+        let path = [key("android")];
+        let Some(map) = self.mapping(&path, value) else {
+            return;
+        };
+        self.keys(&path, map, ANDROID_KEYS);
+
+        if let Some(targets) = map.get("targets") {
+            self.targets::<android::Target>(&[key("android"), key("targets")], targets);
+        }
+
+        let api_level = map.get("apiLevel").map(|v| ("apiLevel", v));
+        let api_level = api_level.or_else(|| map.get("platform").map(|v| ("platform", v)));
+        if let Some((k, v)) = api_level {
+            let path = [key("android"), key(k)];
+            match v.as_u64() {
+                Some(n) if n >= MIN_API_LEVEL => (),
+                Some(n) => self.error(
+                    &path,
+                    format!("API level {n} must be at least {MIN_API_LEVEL}"),
+                ),
+                None => self.error(&path, "must be a number"),
+            }
+        }
+
+        if let Some(v) = map.get("libraryType") {
+            self.library_type(&[key("android"), key("libraryType")], v);
+        }
+
+        if let Some(v) = map.get("symbols") {
+            self.symbols(&[key("android"), key("symbols")], v);
+        }
+        self.cargo_settings(&[key("android")], map);
+
+        if let Some(v) = map.get("packageName") {
+            let path = [key("android"), key("packageName")];
+            match v.as_str() {
+                Some(name) => {
+                    if let Err(e) = check_java_package(name) {
+                        self.error(&path, e);
+                    }
+                }
+                None => self.error(&path, "must be a string"),
+            }
+        }
+    }
+
+    fn ios(&mut self, value: &Value) {
+        let path = [key("ios")];
+        let Some(map) = self.mapping(&path, value) else {
+            return;
+        };
+        self.keys(&path, map, IOS_KEYS);
+
+        if let Some(targets) = map.get("targets") {
+            self.targets::<ios::Target>(&[key("ios"), key("targets")], targets);
+        }
+
+        if let Some(v) = map.get("libraryType") {
+            let path = [key("ios"), key("libraryType")];
+            self.library_type(&path, v);
+            // A bare `.dylib` in the xcframework is neither given an `@rpath` install name
+            // nor embedded in the app, so the app could not load it.
+            if v.as_str() == Some("shared") {
+                self.error(&path, "only `static` libraries can be used on iOS");
+            }
+        }
+
+        if let Some(v) = map.get("symbols") {
+            self.symbols(&[key("ios"), key("symbols")], v);
+        }
+        self.cargo_settings(&[key("ios")], map);
+    }
+
+    /// Check the cargo settings which can be in the `rust` section, or overridden
+    /// in the `android` and `ios` sections.
+    fn cargo_settings(&mut self, path: &[Segment], map: &Mapping) {
+        if let Some(v) = map.get("features") {
+            let path = [path, &[key("features")]].concat();
+            let all_strings = v
+                .as_sequence()
+                .is_some_and(|list| list.iter().all(Value::is_string));
+            if !all_strings {
+                self.error(&path, "must be a list of feature names");
+            }
+        }
+        if let Some(v) = map.get("noDefaultFeatures") {
+            if !v.is_bool() {
+                self.error(
+                    &[path, &[key("noDefaultFeatures")]].concat(),
+                    "must be true or false",
+                );
+            }
+        }
+        if let Some(v) = map.get("profile") {
+            let path = [path, &[key("profile")]].concat();
+            match v.as_str() {
+                Some("debug") => {
+                    self.error(&path, "`debug` is reserved by cargo: use `dev` instead")
+                }
+                Some(_) => (),
+                None => self.error(&path, "must be a string"),
+            }
+        }
+        if let Some(v) = map.get("env") {
+            let path = [path, &[key("env")]].concat();
+            if let Some(env) = self.mapping(&path, v) {
+                for (k, v) in env {
+                    let is_scalar = v.is_string() || v.is_number() || v.is_bool();
+                    if !is_scalar {
+                        let name = k.as_str().unwrap_or_default();
+                        self.error(&[&path[..], &[key(name)]].concat(), "must be a string");
+                    }
+                }
+            }
+        }
+    }
+
+    fn symbols(&mut self, path: &[Segment], value: &Value) {
+        if let Some(map) = self.mapping(path, value) {
+            self.keys(path, map, SYMBOLS_KEYS);
+        }
+    }
+
+    fn library_type(&mut self, path: &[Segment], value: &Value) {
+        match value.as_str() {
+            Some("static" | "shared") => (),
+            Some(s) => self.error(
+                path,
+                format!("unknown library type `{s}`: must be `static` or `shared`"),
+            ),
+            None => self.error(path, "must be a string"),
+        }
+    }
+
+    fn targets<T>(&mut self, path: &[Segment], value: &Value)
+    where
+        T: FromStr + Hash + Eq,
+        T::Err: Display,
+    {
+        let Some(list) = value.as_sequence() else {
+            self.error(path, "must be a list of targets");
+            return;
+        };
+        let mut seen = HashSet::new();
+        for (i, v) in list.iter().enumerate() {
+            let item_path = [path, &[Segment::Index(i)]].concat();
+            let Some(s) = v.as_str() else {
+                self.error(&item_path, "must be a string");
+                continue;
+            };
+            match T::from_str(s) {
+                Ok(target) => {
+                    if !seen.insert(target) {
+                        self.error(&item_path, format!("duplicate target `{s}`"));
+                    }
+                }
+                Err(e) => self.error(&item_path, e.to_string()),
+            }
+        }
+        if list.is_empty() {
+            self.error(path, "must have at least one target");
+        }
+    }
+
+    fn mapping<'v>(&mut self, path: &[Segment], value: &'v Value) -> Option<&'v Mapping> {
+        let map = value.as_mapping();
+        if map.is_none() {
+            let what = if path.is_empty() {
+                "the configuration file"
+            } else {
+                "this section"
+            };
+            self.error(path, format!("{what} must be a map of keys to values"));
+        }
+        map
+    }
+
+    fn keys(&mut self, path: &[Segment], map: &Mapping, known: &[&str]) {
+        for k in map.keys() {
+            let Some(k) = k.as_str() else {
+                self.error(path, format!("keys must be strings, not {k:?}"));
+                continue;
+            };
+            if known.contains(&k) {
+                continue;
+            }
+            let key_path = [path, &[key(k)]].concat();
+            let message = match did_you_mean(k, known) {
+                Some(suggestion) => {
+                    format!("unknown key `{k}`, did you mean `{suggestion}`?")
+                }
+                None => format!(
+                    "unknown key `{k}`, expected one of {}",
+                    known
+                        .iter()
+                        .map(|k| format!("`{k}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            self.error(&key_path, message);
+        }
+    }
+
+    fn error(&mut self, path: &[Segment], message: impl Display) {
+        self.errors.push(ConfigError {
+            path: path_to_string(path),
+            location: self.location(path),
+            message: message.to_string(),
+        })
+    }
+
+    fn location(&self, path: &[Segment]) -> Option<String> {
+        self.sources.iter().rev().find_map(|source| {
+            let find = |path: &[Segment]| match source.key {
+                Some(k) => find_line(source.text, &[&[key(k)], path].concat()),
+                None => find_line(source.text, path),
+            };
+            let line = find(path).or_else(|| {
+                // Lists aren't merged, so if this source has the list, the error is in it,
+                // even if the item in the list can't be found.
+                let i = path.iter().position(|s| matches!(s, Segment::Index(_)))?;
+                find(&path[..i])
+            })?;
+            Some(if self.sources.len() > 1 {
+                format!("line {line} of {}", source.name)
+            } else {
+                format!("line {line}")
+            })
+        })
+    }
+}
+
+fn key(k: &str) -> Segment {
+    Segment::Key(k.to_string())
+}
+
+fn path_to_string(path: &[Segment]) -> String {
+    if path.is_empty() {
+        return "<root>".to_string();
+    }
+    let mut s = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(k) => {
+                if !s.is_empty() {
+                    s.push('.');
+                }
+                s.push_str(k);
+            }
+            Segment::Index(i) => s.push_str(&format!("[{i}]")),
+        }
+    }
+    s
+}
+
+/// Find the line number (1-based) of the YAML (or JSON) node at the given path.
+///
+/// This is not a YAML parser: it follows indentation of block style YAML and pretty printed JSON,
+/// which is what people write by hand. If the node can't be found, then `None` is returned.
+fn find_line(source: &str, path: &[Segment]) -> Option<usize> {
+    if path.is_empty() {
+        return None;
+    }
+    let mut depth = 0;
+    let mut parent_indent: Option<usize> = None;
+    let mut index = 0;
+    for (n, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if matches!(parent_indent, Some(p) if indent <= p) {
+            // We've left the parent block without finding what we were looking for.
+            return None;
+        }
+        let found = match &path[depth] {
+            Segment::Key(k) => is_key(trimmed, k),
+            Segment::Index(i) => {
+                if trimmed.starts_with('-') {
+                    index += 1;
+                    index - 1 == *i
+                } else {
+                    false
+                }
+            }
+        };
+        if found {
+            depth += 1;
+            // The first key of an item in a list is on the same line as its `-`.
+            if let (Segment::Index(_), Some(Segment::Key(k))) = (&path[depth - 1], path.get(depth))
+            {
+                if is_key(trimmed, k) {
+                    depth += 1;
+                }
+            }
+            if depth == path.len() {
+                return Some(n + 1);
+            }
+            parent_indent = Some(indent);
+            index = 0;
+        }
+    }
+    None
+}
+
+fn is_key(trimmed: &str, k: &str) -> bool {
+    let trimmed = trimmed.trim_start_matches("- ").trim_start_matches('"');
+    trimmed
+        .strip_prefix(k)
+        .map(|rest| rest.trim_start_matches('"').trim_start().starts_with(':'))
+        .unwrap_or(false)
+}
+
+/// Suggest the closest known key, if it is close enough to have been a typo.
+fn did_you_mean<'a>(unknown: &str, known: &[&'a str]) -> Option<&'a str> {
+    let normalize = |s: &str| s.replace(['-', '_'], "").to_lowercase();
+    let unknown = normalize(unknown);
+    known
+        .iter()
+        .map(|k| (*k, levenshtein(&unknown, &normalize(k))))
+        .filter(|(k, d)| *d <= 2.max(k.len() / 3))
+        .min_by_key(|(_, d)| *d)
+        .map(|(k, _)| k)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+fn check_java_package(name: &str) -> Result<(), String> {
+    const KEYWORDS: &[&str] = &[
+        "abstract",
+        "assert",
+        "boolean",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "final",
+        "finally",
+        "float",
+        "for",
+        "goto",
+        "if",
+        "implements",
+        "import",
+        "instanceof",
+        "int",
+        "interface",
+        "long",
+        "native",
+        "new",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "static",
+        "strictfp",
+        "super",
+        "switch",
+        "synchronized",
+        "this",
+        "throw",
+        "throws",
+        "transient",
+        "try",
+        "void",
+        "volatile",
+        "while",
+        "true",
+        "false",
+        "null",
+    ];
+    for part in name.split('.') {
+        let mut chars = part.chars();
+        let valid = match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(format!(
+                "`{name}` is not a valid Java package name: `{part}` is not a valid identifier"
+            ));
+        }
+        if KEYWORDS.contains(&part) {
+            return Err(format!(
+                "`{name}` is not a valid Java package name: `{part}` is a Java keyword"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The branch, tag or rev of a git crate, in the same order of precedence as its checkout.
+fn repo_reference(crate_: &Value) -> String {
+    ["rev", "tag", "branch"]
+        .into_iter()
+        .find_map(|k| Some(format!("{k} `{}`", crate_.get(k)?.as_str()?)))
+        .unwrap_or_else(|| format!("branch `{}`", GitRepoArgs::default_branch()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<String> {
+        let value = serde_yaml::from_str(source).unwrap();
+        let sources = [Source {
+            name: "ubrn.config.yaml",
+            text: source,
+            key: None,
+        }];
+        validate_sources(&value, &sources)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let source = r#"
+rust:
+  directory: ./rust
+  manifestPath: Cargo.toml
+android:
+  targets:
+    - arm64-v8a
+    - x86_64
+  apiLevel: 24
+  packageName: com.example.mylib
+ios:
+  targets: [aarch64-apple-ios, aarch64-apple-ios-sim]
+"#;
+        assert!(errors(source).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_keys_have_suggestions_and_lines() {
+        let source = r#"
+rust:
+  directory: ./rust
+  cargo-extras: --verbose
+andriod:
+  targets: [x86]
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "andriod (line 5): unknown key `andriod`, did you mean `android`?",
+                "rust.cargo-extras (line 4): unknown key `cargo-extras`, expected one of `manifestPath`, `directory`, `src`, `rust`, `repo`, `branch`, `tag`, `rev`, `features`, `noDefaultFeatures`, `profile`, `rustflags`, `env`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semantic_checks() {
+        let source = r#"
+rust:
+  repo: https://github.com/example/example.git
+  directory: ./rust
+android:
+  apiLevel: 12
+  packageName: com.example.my-lib
+  targets:
+    - arm64-v8a
+    - aarch64-linux-android
+    - mips
+  libraryType: dynamic
+ios:
+  libraryType: shared
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "rust (line 2): cannot have both a `directory` and a `repo`",
+                "android.targets[1] (line 10): duplicate target `aarch64-linux-android`",
+                "android.targets[2] (line 11): Unsupported target: 'mips'",
+                "android.apiLevel (line 6): API level 12 must be at least 21",
+                "android.libraryType (line 12): unknown library type `dynamic`: must be `static` or `shared`",
+                "android.packageName (line 7): `com.example.my-lib` is not a valid Java package name: `my-lib` is not a valid identifier",
+                "ios.libraryType (line 14): only `static` libraries can be used on iOS",
+            ]
+        );
+    }
+
+    /// Every key which the validator knows about, with the crate on disk.
+    ///
+    /// When a key is added to the config, it should be added here too.
+    const EVERY_KEY: &str = r#"
+name: my-lib
+repository: https://github.com/example/my-lib
+rust:
+  directory: ./rust
+  manifestPath: crates/api/Cargo.toml
+  features: [websockets]
+  noDefaultFeatures: true
+  profile: release-small
+  rustflags: -C debuginfo=1
+  env: { RUST_LOG: debug }
+android:
+  directory: ./android
+  jniLibs: src/main/jniLibs
+  targets: [arm64-v8a]
+  cargoExtras: [--locked]
+  apiLevel: 24
+  packageName: com.example.mylib
+  libraryType: shared
+  symbols: { directory: build/symbols, split: true }
+  features: [android]
+  noDefaultFeatures: true
+  profile: release
+  rustflags: [-C, lto]
+  env: { ANDROID: "1" }
+ios:
+  directory: ./ios
+  frameworkName: MyFramework
+  xcodebuildExtras: [-quiet]
+  targets: [aarch64-apple-ios]
+  cargoExtras: --locked
+  libraryType: static
+  symbols: { directory: build/symbols, split: false }
+  features: [ios]
+  noDefaultFeatures: false
+  profile: release
+  rustflags: -C lto
+  env: { IOS: "1" }
+bindings:
+  cpp: cpp/bindings
+  ts: src/bindings
+  uniffiToml: uniffi.toml
+turboModule:
+  cpp: cpp
+  ts: src
+  specName: MyLib
+  name: MyLibSpec
+noOverwrite: ["*.podspec"]
+"#;
+
+    /// The same, but with the crate in a git repo.
+    const EVERY_REPO_KEY: &str = r#"
+name: my-lib
+repository: https://github.com/example/my-lib
+rust:
+  repo: https://github.com/example/my-rust-lib
+  branch: develop
+  tag: v1.0
+  rev: abc123
+android:
+  packageName: com.example.mylib
+ios:
+  frameworkName: MyFramework
+turboModule:
+  ts: src
+  specName: MyLib
+  name: MyLibSpec
+"#;
+
+    #[test]
+    fn test_every_key_is_known() {
+        assert_eq!(errors(EVERY_KEY), Vec::<String>::new());
+        assert_eq!(
+            errors(EVERY_REPO_KEY),
+            vec!["rust (line 4): only one of `branch`, `tag` or `rev` can be used"]
+        );
+    }
+
+    /// The keys the validator knows about are kept by hand, so check each of them is
+    /// read into the config: a value which can't be deserialized as anything must make
+    /// the config fail to load, rather than being ignored.
+    #[test]
+    fn test_every_known_key_is_deserialized() {
+        use crate::config::ProjectConfig;
+
+        let sections: &[(&[&str], &[&str])] = &[
+            (&[], TOP_LEVEL_KEYS),
+            (&["rust"], RUST_KEYS),
+            (&["android"], ANDROID_KEYS),
+            (&["ios"], IOS_KEYS),
+            (&["android", "symbols"], SYMBOLS_KEYS),
+            (&["bindings"], BINDINGS_KEYS),
+            (&["turboModule"], TURBO_MODULE_KEYS),
+        ];
+        let configs: Vec<Value> = [EVERY_KEY, EVERY_REPO_KEY]
+            .iter()
+            .map(|s| serde_yaml::from_str(s).unwrap())
+            .collect();
+        for config in &configs {
+            serde_yaml::from_value::<ProjectConfig>(config.clone()).unwrap();
+        }
+        let unreadable: Value = serde_yaml::from_str("[{ unreadable: [] }]").unwrap();
+
+        for (path, keys) in sections {
+            for k in *keys {
+                let is_read = configs.iter().any(|config| {
+                    let mut config = config.clone();
+                    let section = path
+                        .iter()
+                        .try_fold(&mut config, |v, p| v.get_mut(*p))
+                        .and_then(Value::as_mapping_mut);
+                    let Some(section) = section else {
+                        return false;
+                    };
+                    section.insert(Value::from(*k), unreadable.clone());
+                    serde_yaml::from_value::<ProjectConfig>(config).is_err()
+                });
+                assert!(is_read, "`{k}` in `{}` is not read", path.join("."));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cargo_settings() {
+        let source = r#"
+rust:
+  directory: ./rust
+  features: [a, b]
+  profile: release-small
+  env:
+    MY_FLAG: 1
+ios:
+  features: a
+  noDefaultFeatures: yes
+  profile: debug
+  env:
+    NESTED: [1, 2]
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "ios.features (line 9): must be a list of feature names",
+                "ios.noDefaultFeatures (line 10): must be true or false",
+                "ios.profile (line 11): `debug` is reserved by cargo: use `dev` instead",
+                "ios.env.NESTED (line 13): must be a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_of_crates() {
+        let source = r#"
+rust:
+  - directory: ./crypto
+  - repo: https://github.com/example/sync.git
+    tag: v1.0
+  - manifestPath: storage/Cargo.toml
+    branch: main
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "rust[2] (line 6): needs either a `directory` containing the crate, or a git `repo`",
+                "rust[2].branch (line 7): `branch` can only be used with a `repo`",
+            ]
+        );
+
+        let source = r#"
+rust:
+  - repo: https://github.com/example/sync.git
+    manifestPath: crates/a/Cargo.toml
+  - repo: https://github.com/example/sync.git
+    manifestPath: crates/b/Cargo.toml
+  - repo: https://github.com/fork/sync
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "rust[2].repo (line 7): would be checked out into `rust_modules/sync`, like \
+                `https://github.com/example/sync.git`: repos with the same name cannot be used together",
+            ]
+        );
+
+        let source = r#"
+rust:
+  - repo: https://github.com/example/sync.git
+    manifestPath: crates/a/Cargo.toml
+  - repo: https://github.com/example/sync.git
+    manifestPath: crates/b/Cargo.toml
+    branch: main
+  - repo: https://github.com/example/sync.git
+    manifestPath: crates/c/Cargo.toml
+    tag: v1.0
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "rust[2].repo (line 8): uses the tag `v1.0`, but `rust[0]` uses the branch `main` \
+                of the same repo: crates from one repo must use the same branch, tag or rev",
+            ]
+        );
+        assert_eq!(
+            errors("rust: []"),
+            vec!["rust (line 1): needs at least one crate"]
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(
+            did_you_mean("cargoExtra", ANDROID_KEYS),
+            Some("cargoExtras")
+        );
+        assert_eq!(did_you_mean("jni_libs", ANDROID_KEYS), Some("jniLibs"));
+        assert_eq!(
+            did_you_mean("turbo-module", TOP_LEVEL_KEYS),
+            Some("turboModule")
+        );
+        assert_eq!(did_you_mean("xyzzy", TOP_LEVEL_KEYS), None);
+    }
+}
@@ -0,0 +1,405 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{collections::BTreeSet, fmt::Display, process::Command};
+
+use anyhow::Result;
+use clap::Args;
+use log::{error, info, warn};
+use which::which;
+
+use crate::{
+    android,
+    config::{ConfigArgs, ProjectConfig},
+    rust::RustSource,
+};
+
+#[derive(Args, Debug)]
+pub(crate) struct DoctorArgs {
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    /// Only check what is needed to build for Android
+    #[clap(long, conflicts_with_all = ["ios"])]
+    android: bool,
+
+    /// Only check what is needed to build for iOS
+    #[clap(long)]
+    ios: bool,
+}
+
+impl DoctorArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let mut report = Report::default();
+
+        report.section("Project");
+        let config: Option<ProjectConfig> = match self.config.load() {
+            Ok(config) => {
+                let files = self.config.files()?;
+                let files = files.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+                report.pass("Configuration can be read", Some(files.join(", ")));
+                Some(config)
+            }
+            Err(e) => {
+                report.fail(
+                    "Configuration can be read",
+                    format!("{e:#}"),
+                    "Fix the configuration file, or create one with `ubrn init`",
+                );
+                None
+            }
+        };
+        for crate_ in config.iter().flat_map(|c| &c.crates) {
+            match crate_.manifest_path() {
+                Ok(manifest) if manifest.exists() => {
+                    report.pass("Rust crate can be found", Some(manifest.to_string()))
+                }
+                Ok(manifest) => report.fail(
+                    "Rust crate can be found",
+                    format!("{manifest} does not exist"),
+                    "Run `ubrn checkout`, or fix the `rust` section of the configuration file",
+                ),
+                Err(e) => report.fail(
+                    "Rust crate can be found",
+                    format!("{e:#}"),
+                    "Fix the `rust` section of the configuration file",
+                ),
+            }
+        }
+        let has_repo = config
+            .iter()
+            .flat_map(|c| &c.crates)
+            .any(|c| matches!(c.src, RustSource::GitRepo(_)));
+        if has_repo {
+            report.tool(
+                "git",
+                &["--version"],
+                "Install git from https://git-scm.com",
+            );
+        }
+
+        report.section("Rust");
+        report.tool(
+            "cargo",
+            &["--version"],
+            "Install Rust from https://rustup.rs",
+        );
+        let installed = installed_rust_targets();
+        if installed.is_none() {
+            report.fail(
+                "rustup",
+                "rustup is not installed",
+                "Install Rust from https://rustup.rs",
+            );
+        }
+
+        if self.check_android() {
+            report.section("Android");
+            report.tool(
+                "cargo ndk",
+                &["ndk", "--version"],
+                "cargo install cargo-ndk",
+            );
+            match android::find_ndk() {
+                Some(ndk) => report.pass("Android NDK", Some(ndk.to_string())),
+                None => report.fail(
+                    "Android NDK",
+                    "cannot find the Android NDK",
+                    "Install the NDK with Android Studio's SDK Manager, and set ANDROID_NDK_HOME",
+                ),
+            }
+            if let (Some(config), Some(installed)) = (&config, &installed) {
+                let triples = config
+                    .android
+                    .targets
+                    .iter()
+                    .map(|t| t.triple().to_string());
+                report.rust_targets(triples, installed);
+            }
+            if let Some(config) = &config {
+                let android = &config.android;
+                if let Some(symbols) = &android.symbols {
+                    report.ndk_tool("llvm-strip");
+                    if symbols.split {
+                        report.ndk_tool("llvm-objcopy");
+                    }
+                }
+                let root = config.project_root();
+                let gradle = android::find_gradle(root, &android.directory(root));
+                match gradle {
+                    Ok(gradle) => report.pass("gradle", Some(gradle.to_string())),
+                    Err(_) => report.warn(
+                        "gradle",
+                        "not found, but it is only needed for `build android --aar`",
+                        "Install Gradle, or add a Gradle wrapper to the Android project",
+                    ),
+                }
+            }
+        }
+
+        if self.check_ios() {
+            report.section("iOS");
+            report.tool(
+                "xcodebuild",
+                &["-version"],
+                "Install Xcode from the App Store, then run `xcode-select --install`",
+            );
+            report.which("lipo", "Run `xcode-select --install`");
+            if let (Some(config), Some(installed)) = (&config, &installed) {
+                let triples = config.ios.targets.iter().map(|t| t.to_string());
+                report.rust_targets(triples, installed);
+            }
+            if config.as_ref().is_some_and(|c| c.ios.symbols.is_some()) {
+                report.which("dsymutil", "Run `xcode-select --install`");
+                report.which("strip", "Run `xcode-select --install`");
+            }
+        }
+
+        report.section("Formatting");
+        if which("clang-format").is_ok() {
+            report.pass("clang-format", None);
+        } else {
+            report.warn(
+                "clang-format",
+                "generated C++ will not be formatted",
+                "Install clang-format, e.g. `brew install clang-format`",
+            );
+        }
+        let prettier = config
+            .as_ref()
+            .map(|c| c.project_root().to_owned())
+            .map_or_else(ubrn_common::pwd, Ok)
+            .and_then(|root| ubrn_common::resolve(root, "node_modules/.bin/prettier"));
+        match prettier {
+            Ok(Some(prettier)) => report.pass("prettier", Some(prettier.to_string())),
+            _ => report.warn(
+                "prettier",
+                "generated Typescript will not be formatted",
+                "yarn add --dev prettier",
+            ),
+        }
+
+        report.section("Packaging");
+        for (program, command) in [
+            ("tar", "`pack` and `fetch`"),
+            ("curl", "`fetch --from <url>`"),
+        ] {
+            if let Ok(path) = which(program) {
+                report.pass(program, Some(path.display().to_string()));
+            } else {
+                report.warn(
+                    program,
+                    format!("not installed, but it is only needed for {command}"),
+                    format!("Install {program}"),
+                );
+            }
+        }
+
+        report.finish()
+    }
+
+    fn check_android(&self) -> bool {
+        self.android || !self.ios
+    }
+
+    fn check_ios(&self) -> bool {
+        self.ios || (!self.android && cfg!(target_os = "macos"))
+    }
+}
+
+fn installed_rust_targets() -> Option<BTreeSet<String>> {
+    let output = Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.trim().to_string())
+            .collect(),
+    )
+}
+
+enum Status {
+    Pass(Option<String>),
+    Warn(String, String),
+    Fail(String, String),
+}
+
+#[derive(Default)]
+struct Report {
+    warnings: usize,
+    failures: usize,
+}
+
+impl Report {
+    fn section(&self, name: &str) {
+        info!("{name}");
+    }
+
+    /// Log the result of the check: with `-q`, only the problems are shown.
+    fn print(&mut self, check: &str, status: Status) {
+        match status {
+            Status::Pass(detail) => match detail {
+                Some(detail) => info!("  [ok]   {check}: {detail}"),
+                None => info!("  [ok]   {check}"),
+            },
+            Status::Warn(problem, fix) => {
+                self.warnings += 1;
+                warn!("{check}: {problem}\n  fix: {fix}");
+            }
+            Status::Fail(problem, fix) => {
+                self.failures += 1;
+                error!("{check}: {problem}\n  fix: {fix}");
+            }
+        }
+    }
+
+    fn pass(&mut self, check: impl Display, detail: Option<String>) {
+        self.print(&check.to_string(), Status::Pass(detail))
+    }
+
+    fn warn(&mut self, check: impl Display, problem: impl Display, fix: impl Display) {
+        self.print(
+            &check.to_string(),
+            Status::Warn(problem.to_string(), fix.to_string()),
+        )
+    }
+
+    fn fail(&mut self, check: impl Display, problem: impl Display, fix: impl Display) {
+        self.print(
+            &check.to_string(),
+            Status::Fail(problem.to_string(), fix.to_string()),
+        )
+    }
+
+    fn which(&mut self, program: &str, fix: &str) {
+        match which(program) {
+            Ok(path) => self.pass(program, Some(path.display().to_string())),
+            Err(_) => self.fail(program, format!("{program} is not installed"), fix),
+        }
+    }
+
+    /// Check for one of the LLVM tools which comes with the Android NDK.
+    fn ndk_tool(&mut self, name: &str) {
+        match android::find_ndk_tool(name) {
+            Ok(path) => self.pass(name, Some(path.to_string())),
+            Err(e) => self.fail(
+                name,
+                format!("{e:#}"),
+                "Install the NDK with Android Studio's SDK Manager, and set ANDROID_NDK_HOME",
+            ),
+        }
+    }
+
+    /// Check that the tool is installed by asking it for its version.
+    fn tool(&mut self, name: &str, version_args: &[&str], fix: &str) {
+        let program = name.split_whitespace().next().unwrap_or(name);
+        let output = Command::new(program).args(version_args).output();
+        match output {
+            Ok(output) if output.status.success() => {
+                let version = String::from_utf8_lossy(&output.stdout);
+                let version = version.lines().next().unwrap_or_default().trim();
+                self.pass(name, Some(version.to_string()))
+            }
+            _ => self.fail(name, format!("{name} is not installed"), fix),
+        }
+    }
+
+    fn rust_targets(
+        &mut self,
+        triples: impl Iterator<Item = String>,
+        installed: &BTreeSet<String>,
+    ) {
+        for triple in triples {
+            let check = format!("rustup target {triple}");
+            if installed.contains(&triple) {
+                self.pass(check, None);
+            } else {
+                self.fail(
+                    check,
+                    "not installed",
+                    format!("rustup target add {triple}"),
+                );
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        info!("");
+        match (self.failures, self.warnings) {
+            (0, 0) => info!("No problems found"),
+            (0, w) => info!("{w} warning(s), but nothing that will stop a build"),
+            (f, w) => anyhow::bail!("{f} problem(s) and {w} warning(s) found"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status() {
+        let mut report = Report::default();
+        report.pass("cargo", None);
+        assert!(report.finish().is_ok());
+
+        // Warnings don't stop a build, so they don't fail.
+        let mut report = Report::default();
+        report.pass("cargo", None);
+        report.warn("prettier", "not installed", "yarn add --dev prettier");
+        assert!(report.finish().is_ok());
+
+        let mut report = Report::default();
+        report.warn("prettier", "not installed", "yarn add --dev prettier");
+        report.fail("cargo-ndk", "not installed", "cargo install cargo-ndk");
+        report.fail("xcodebuild", "not installed", "Install Xcode");
+        assert_eq!(
+            report.finish().unwrap_err().to_string(),
+            "2 problem(s) and 1 warning(s) found"
+        );
+    }
+
+    #[test]
+    fn test_rust_targets() {
+        let installed = BTreeSet::from(["aarch64-linux-android".to_string()]);
+        let mut report = Report::default();
+        report.rust_targets(
+            ["aarch64-linux-android", "x86_64-linux-android"]
+                .into_iter()
+                .map(str::to_owned),
+            &installed,
+        );
+        assert_eq!(report.failures, 1);
+        assert_eq!(report.warnings, 0);
+    }
+
+    #[test]
+    fn test_platforms() {
+        use crate::cli::{CliArgs, CliCmd};
+        use clap::Parser;
+
+        let args = |args: &[&str]| {
+            let cli = CliArgs::try_parse_from(
+                [&["ubrn", "doctor", "--config", "ubrn.config.yaml"], args].concat(),
+            )
+            .unwrap();
+            match cli.cmd {
+                CliCmd::Doctor(d) => (d.check_android(), d.check_ios()),
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(args(&["--android"]), (true, false));
+        assert_eq!(args(&["--ios"]), (false, true));
+        // With neither, iOS is only checked where it can be built.
+        assert_eq!(args(&[]), (true, cfg!(target_os = "macos")));
+        assert!(CliArgs::try_parse_from(["ubrn", "doctor", "--android", "--ios"]).is_err());
+    }
+}
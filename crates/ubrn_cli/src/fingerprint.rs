@@ -0,0 +1,251 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{fs, process::Command, sync::Mutex};
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use sha2::{Digest, Sha256};
+use ubrn_common::{dry_run, list_files, mk_dir, run_cmd_for_output, CrateMetadata};
+
+/// Remembers the inputs of the last successful build of each target, so that cargo is
+/// only run for the targets whose inputs have changed.
+///
+/// The inputs are the sources of the crate and of each of its local dependencies, the
+/// `Cargo.lock`, the versions of `rustc` and `cargo`, and the command line and environment
+/// for the build, which includes the features, profile, target, API level and extras.
+pub(crate) struct BuildCache {
+    dir: Utf8PathBuf,
+    library_name: String,
+    inputs: String,
+    force: bool,
+    /// The fingerprints of the outputs which were out of date, to be saved once the build
+    /// has succeeded.
+    pending: Mutex<Vec<(Utf8PathBuf, String)>>,
+}
+
+impl BuildCache {
+    /// The `extra` inputs are anything else which the platform's build depends on, e.g. the NDK.
+    pub(crate) fn new(metadata: &CrateMetadata, extra: &str, force: bool) -> Result<Self> {
+        let mut hasher = Sha256::new();
+        hash_sources(&mut hasher, metadata)?;
+        hasher.update(tool_versions()?);
+        hasher.update(extra);
+        Ok(Self {
+            dir: metadata.target_dir().join("ubrn").join("fingerprints"),
+            library_name: metadata.library_name().to_string(),
+            inputs: hex(hasher),
+            force,
+            pending: Default::default(),
+        })
+    }
+
+    /// Is the output of the given command up to date?
+    ///
+    /// If it is not, then the command's fingerprint is kept, to be saved once the build
+    /// has succeeded.
+    pub(crate) fn is_fresh_cmd(&self, key: &str, cmd: &Command, output: &Utf8Path) -> bool {
+        // The debug format of the command includes its arguments, its working directory,
+        // and the environment variables set for it.
+        let rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+        self.is_fresh(key, &format!("{cmd:?} RUSTFLAGS={rustflags}"), output)
+    }
+
+    /// Is the output up to date, i.e. does it exist, and have none of its inputs changed?
+    pub(crate) fn is_fresh(&self, key: &str, inputs: &str, output: &Utf8Path) -> bool {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.inputs);
+        hasher.update(inputs);
+        hasher.update(output.as_str());
+        let fingerprint = hex(hasher);
+
+        let file = self.dir.join(format!("{}-{key}", self.library_name));
+        let fresh = !self.force
+            && output.exists()
+            && fs::read_to_string(&file).ok().as_deref() == Some(fingerprint.as_str());
+        if !fresh {
+            self.pending
+                .lock()
+                .expect("Nothing panics while holding the lock")
+                .push((file, fingerprint));
+        }
+        fresh
+    }
+
+    /// Was every output checked so far up to date?
+    pub(crate) fn is_all_fresh(&self) -> bool {
+        self.pending
+            .lock()
+            .expect("Nothing panics while holding the lock")
+            .is_empty()
+    }
+
+    /// Save the fingerprints of everything which was built.
+    ///
+    /// This should only be called once the whole build has succeeded, so that a failed or
+    /// interrupted step is run again next time.
+    pub(crate) fn save(&self) -> Result<()> {
+        if dry_run() {
+            return Ok(());
+        }
+        let pending = std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .expect("Nothing panics while holding the lock"),
+        );
+        if pending.is_empty() {
+            return Ok(());
+        }
+        mk_dir(&self.dir)?;
+        for (file, fingerprint) in pending {
+            fs::write(&file, fingerprint).with_context(|| format!("Failed to write {file}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Hash every file in the source directories, except for those in the target directory,
+/// in `node_modules`, and in hidden directories like `.git`.
+fn hash_sources(hasher: &mut Sha256, metadata: &CrateMetadata) -> Result<()> {
+    let target_dir = metadata.target_dir();
+    let skip = |path: &Utf8Path| {
+        let name = path.file_name().unwrap_or_default();
+        name.starts_with('.') || name == "node_modules" || path == target_dir
+    };
+    let mut files = Vec::new();
+    for dir in metadata.source_dirs() {
+        files.extend(list_files(dir, &skip)?);
+    }
+    files.push(metadata.lock_file());
+    files.sort();
+    files.dedup();
+    for file in files {
+        // The lock file may not exist yet, before the first build.
+        let Ok(contents) = fs::read(&file) else {
+            continue;
+        };
+        hasher.update(file.as_str());
+        hasher.update(contents.len().to_le_bytes());
+        hasher.update(contents);
+    }
+    Ok(())
+}
+
+fn tool_versions() -> Result<String> {
+    let rustc = run_cmd_for_output(Command::new("rustc").arg("-vV"))?;
+    let cargo = run_cmd_for_output(Command::new("cargo").arg("-V"))?;
+    Ok(format!("{rustc}{cargo}"))
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestDir(Utf8PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("ubrn-{name}-{}", std::process::id()));
+            let dir = Utf8PathBuf::try_from(dir).unwrap();
+            if dir.exists() {
+                fs::remove_dir_all(&dir).unwrap();
+            }
+            mk_dir(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// A cache for a crate whose sources, tools and platform hash to `inputs`.
+        fn cache(&self, inputs: &str, force: bool) -> BuildCache {
+            BuildCache {
+                dir: self.0.join("fingerprints"),
+                library_name: "mylib".into(),
+                inputs: inputs.into(),
+                force,
+                pending: Default::default(),
+            }
+        }
+
+        fn output(&self) -> Utf8PathBuf {
+            let output = self.0.join("libmylib.a");
+            fs::write(&output, "library").unwrap();
+            output
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_is_fresh() -> Result<()> {
+        let dir = TestDir::new("build-cache");
+        let output = dir.output();
+
+        let cache = dir.cache("sources", false);
+        assert!(!cache.is_fresh("arm64", "cargo build", &output));
+        assert!(!cache.is_all_fresh());
+        cache.save()?;
+
+        let cache = dir.cache("sources", false);
+        assert!(cache.is_fresh("arm64", "cargo build", &output));
+        assert!(cache.is_all_fresh());
+
+        // Each target has its own fingerprint.
+        assert!(!cache.is_fresh("x86_64", "cargo build", &output));
+
+        // The command, the sources, or the output changing makes it stale.
+        assert!(!cache.is_fresh("arm64", "cargo build --release", &output));
+        let changed_sources = dir.cache("changed sources", false);
+        assert!(!changed_sources.is_fresh("arm64", "cargo build", &output));
+        let other_output = dir.0.join("libmylib.so");
+        fs::write(&other_output, "library")?;
+        assert!(!cache.is_fresh("arm64", "cargo build", &other_output));
+
+        // As does --force.
+        let forced = dir.cache("sources", true);
+        assert!(!forced.is_fresh("arm64", "cargo build", &output));
+
+        // As does the output going missing.
+        fs::remove_file(&output)?;
+        assert!(!cache.is_fresh("arm64", "cargo build", &output));
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_after_success() -> Result<()> {
+        let dir = TestDir::new("build-cache-save");
+        let output = dir.output();
+
+        // The build fails, so the cache isn't saved, and the next build runs again.
+        let cache = dir.cache("sources", false);
+        assert!(!cache.is_fresh("arm64", "cargo build", &output));
+        drop(cache);
+        let cache = dir.cache("sources", false);
+        assert!(!cache.is_fresh("arm64", "cargo build", &output));
+
+        // Once it succeeds, it is saved, and only the stale outputs are written.
+        cache.save()?;
+        let files: Vec<_> = fs::read_dir(dir.0.join("fingerprints"))?.collect();
+        assert_eq!(files.len(), 1);
+        assert!(dir
+            .cache("sources", false)
+            .is_fresh("arm64", "cargo build", &output));
+
+        // Nothing is left to be saved.
+        assert!(cache.is_all_fresh());
+        Ok(())
+    }
+}
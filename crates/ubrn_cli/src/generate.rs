@@ -6,18 +6,28 @@
 use anyhow::Result;
 use camino::Utf8PathBuf;
 use clap::{Args, Subcommand};
-use ubrn_bindgen::{BindingsArgs, OutputArgs, SourceArgs};
+use ubrn_bindgen::{BindingsArgs, ModuleMetadata, OutputArgs, SourceArgs};
+use ubrn_common::{CrateMetadata, GeneratedFiles, Watcher};
 
-use crate::{codegen::TurboModuleArgs, config::ProjectConfig};
+use crate::{
+    codegen::TurboModuleArgs,
+    config::{ConfigArgs, ProjectConfig},
+};
 
 #[derive(Args, Debug)]
 pub(crate) struct GenerateArgs {
     #[clap(subcommand)]
     cmd: GenerateCmd,
+
+    /// Print the files which would be generated, and whether they would be created,
+    /// updated, unchanged or excluded by `noOverwrite`, without writing any of them.
+    #[clap(long, global = true)]
+    dry_run: bool,
 }
 
 impl GenerateArgs {
     pub(crate) fn run(&self) -> Result<()> {
+        ubrn_common::set_dry_run(self.dry_run);
         self.cmd.run()
     }
 }
@@ -38,6 +48,9 @@ pub(crate) enum GenerateCmd {
 impl GenerateCmd {
     pub(crate) fn run(&self) -> Result<()> {
         match self {
+            Self::Bindings(b) if b.is_watching() => {
+                Watcher::new(b.watched_paths()).run(|| b.run().map(|_| ()))
+            }
             Self::Bindings(b) => {
                 b.run()?;
                 Ok(())
@@ -56,48 +69,101 @@ impl GenerateCmd {
 
 #[derive(Args, Debug)]
 pub(crate) struct GenerateAllArgs {
-    /// The configuration file for this project
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    /// Do not write any files, but exit with an error if any generated file
+    /// is out of date.
     #[clap(long)]
-    config: Utf8PathBuf,
+    check: bool,
 
-    /// A path to staticlib file.
-    lib_file: Utf8PathBuf,
+    /// The paths to the library files, one for each crate in the config file.
+    #[clap(required = true, num_args = 1..)]
+    lib_files: Vec<Utf8PathBuf>,
 }
 
 impl GenerateAllArgs {
-    pub(crate) fn new(lib_file: Utf8PathBuf, config: Utf8PathBuf) -> Self {
-        Self { lib_file, config }
+    pub(crate) fn new(lib_files: Vec<Utf8PathBuf>, config: ConfigArgs) -> Self {
+        Self {
+            lib_files,
+            config,
+            check: false,
+        }
     }
 
     pub(crate) fn run(&self) -> Result<()> {
         let project = self.project_config()?;
         let root = project.project_root();
         let pwd = ubrn_common::pwd()?;
-        let lib_file = pwd.join(&self.lib_file);
-        let modules = {
-            ubrn_common::cd(&project.crate_.crate_dir()?)?;
-            let ts_dir = project.bindings.ts_path(root);
-            let cpp_dir = project.bindings.cpp_path(root);
-            let config = project.bindings.uniffi_toml_path(root);
-            if let Some(ref file) = config {
-                if !file.exists() {
-                    anyhow::bail!("uniffi.toml file {:?} does not exist. Either delete the uniffiToml property or supply a file", file)
+        let ts_dir = project.bindings.ts_path(root);
+        let cpp_dir = project.bindings.cpp_path(root);
+        let config = project.bindings.uniffi_toml_path(root);
+        if let Some(ref file) = config {
+            if !file.exists() {
+                anyhow::bail!("uniffi.toml file {:?} does not exist. Either delete the uniffiToml property or supply a file", file)
+            }
+        }
+
+        let rust_crates = project.crates_metadata()?;
+        let lib_files = self.lib_files_for(&rust_crates)?;
+        let mut modules: Vec<ModuleMetadata> = Vec::new();
+        let mut files = GeneratedFiles::new(self.check);
+        for (crate_, lib_file) in project.crates.iter().zip(lib_files) {
+            let lib_file = pwd.join(lib_file);
+            let generated = {
+                ubrn_common::cd(&crate_.crate_dir()?)?;
+                let bindings = BindingsArgs::new(
+                    SourceArgs::library(&lib_file).with_config(config.clone()),
+                    OutputArgs::new(&ts_dir, &cpp_dir, false)
+                        .with_check(self.check)
+                        .with_generated_modules(&modules),
+                );
+
+                bindings.generate()
+            };
+            // Change back before checking for errors, so that a watcher can try again.
+            ubrn_common::cd(&pwd)?;
+            let (crate_modules, mut crate_files) = generated?;
+            // Crates which share a dependency each find its module, but only the
+            // first one generates it.
+            for m in crate_modules {
+                if !modules.iter().any(|n| n.ts() == m.ts()) {
+                    modules.push(m);
                 }
             }
-            let bindings = BindingsArgs::new(
-                SourceArgs::library(&lib_file).with_config(config),
-                OutputArgs::new(&ts_dir, &cpp_dir, false),
-            );
+            files.append(&mut crate_files);
+        }
+        crate::codegen::render_files(project, rust_crates, modules, &mut files)?;
+        files.finish()
+    }
 
-            bindings.run()?
-        };
-        ubrn_common::cd(&pwd)?;
-        let rust_crate = project.crate_.metadata()?;
-        crate::codegen::render_files(project, rust_crate, modules)?;
-        Ok(())
+    /// Match each crate to its library file, by the library name.
+    fn lib_files_for(&self, rust_crates: &[CrateMetadata]) -> Result<Vec<&Utf8PathBuf>> {
+        if let ([_], [lib_file]) = (rust_crates, self.lib_files.as_slice()) {
+            return Ok(vec![lib_file]);
+        }
+        if rust_crates.len() != self.lib_files.len() {
+            anyhow::bail!(
+                "Expected {} library files, one for each crate, but {} were given",
+                rust_crates.len(),
+                self.lib_files.len()
+            );
+        }
+        rust_crates
+            .iter()
+            .map(|c| {
+                let prefix = format!("lib{}.", c.library_name());
+                self.lib_files
+                    .iter()
+                    .find(|f| f.file_name().is_some_and(|n| n.starts_with(&prefix)))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No library file given for `{}`", c.library_name())
+                    })
+            })
+            .collect()
     }
 
     fn project_config(&self) -> Result<ProjectConfig> {
-        self.config.clone().try_into()
+        self.config.load()
     }
 }
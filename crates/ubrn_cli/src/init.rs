@@ -0,0 +1,422 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use askama::Template;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use heck::ToUpperCamelCase;
+use log::info;
+use serde_json::{json, Value};
+
+use crate::{
+    config::{trim_react_native, trim_react_native_2},
+    repo::GitRepoArgs,
+};
+
+#[derive(Args, Debug)]
+pub(crate) struct InitArgs {
+    /// The directory containing the Rust crate, relative to the project root.
+    #[clap(long, conflicts_with_all = ["repo", "branch"])]
+    crate_dir: Option<String>,
+
+    /// A git repository containing the Rust crate.
+    ///
+    /// This will be checked out into the `rust_modules` directory.
+    #[clap(long)]
+    repo: Option<String>,
+
+    /// The branch or tag of the git repository to checkout.
+    #[clap(long, requires = "repo")]
+    branch: Option<String>,
+
+    /// The path to the Cargo.toml of the crate, relative to the crate directory
+    /// or repository root.
+    #[clap(long)]
+    manifest_path: Option<String>,
+
+    /// The name of the npm package.
+    #[clap(long)]
+    name: Option<String>,
+
+    /// The Java package name of the Android module, e.g. `com.example.mylib`.
+    #[clap(long)]
+    android_package: Option<String>,
+
+    /// The configuration file to write.
+    #[clap(long, default_value = "ubrn.config.yaml")]
+    config: Utf8PathBuf,
+
+    /// Overwrite the configuration file if it already exists.
+    #[clap(long)]
+    force: bool,
+
+    /// Do not ask any questions: anything not given on the command line uses
+    /// its default value.
+    #[clap(long, short)]
+    yes: bool,
+
+    /// Do not checkout the git repository after writing the configuration.
+    #[clap(long)]
+    no_checkout: bool,
+}
+
+impl InitArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let project_root = ubrn_common::pwd()?;
+        let config_file = project_root.join(&self.config);
+        if config_file.exists() && !self.force {
+            anyhow::bail!("{config_file} already exists. Use --force to overwrite it");
+        }
+
+        let package_json_file = project_root.join("package.json");
+        let mut package_json = if package_json_file.exists() {
+            ubrn_common::read_from_file(&package_json_file)?
+        } else {
+            Value::Object(Default::default())
+        };
+
+        let answers = self.answers(&project_root, &package_json)?;
+
+        update_package_json(&mut package_json, &answers);
+        write_json(&package_json_file, &package_json)?;
+        info!("Wrote {package_json_file}");
+
+        let tsconfig = project_root.join("tsconfig.json");
+        if !tsconfig.exists() {
+            write_json(&tsconfig, &default_tsconfig())?;
+            info!("Wrote {tsconfig}");
+        }
+
+        let contents = answers.render()?;
+        std::fs::write(&config_file, contents)?;
+        info!("Wrote {config_file}");
+
+        if let Some(repo) = &answers.repo {
+            let repo = GitRepoArgs {
+                repo: repo.clone(),
+                branch: answers.branch.clone(),
+                tag: None,
+                rev: None,
+            };
+            let directory = repo.directory(&project_root)?;
+            if self.no_checkout || directory.exists() {
+                info!(
+                    "Run `ubrn checkout --config {}` to fetch the Rust crate",
+                    self.config
+                );
+            } else {
+                repo.checkout(&project_root)?;
+            }
+        } else if !project_root.join(&answers.directory).exists() {
+            info!(
+                "{} does not exist yet: create a crate with `cargo init --lib {}`",
+                answers.directory, answers.directory
+            );
+        }
+
+        info!("Build and generate the bindings with:");
+        info!(
+            "  ubrn build android --config {} --and-generate",
+            self.config
+        );
+        info!("  ubrn build ios --config {} --and-generate", self.config);
+        Ok(())
+    }
+
+    fn answers(&self, project_root: &Utf8Path, package_json: &Value) -> Result<InitAnswers> {
+        let prompter = Prompter::new(!self.yes);
+
+        let default_name = package_json["name"]
+            .as_str()
+            .map(ToString::to_string)
+            .or_else(|| project_root.file_name().map(ToString::to_string))
+            .unwrap_or_else(|| "my-rust-lib".to_string());
+        let name = prompter.ask_if_missing(&self.name, "npm package name", &default_name)?;
+
+        let (repo, directory) = match (&self.repo, &self.crate_dir) {
+            (Some(repo), _) => (Some(repo.clone()), String::new()),
+            (_, Some(dir)) => (None, dir.clone()),
+            (None, None) => {
+                let answer = prompter.ask("Rust crate directory or git repository URL", "rust")?;
+                if is_git_url(&answer) {
+                    (Some(answer), String::new())
+                } else {
+                    (None, answer)
+                }
+            }
+        };
+
+        let branch = if repo.is_some() {
+            prompter.ask_if_missing(&self.branch, "git branch or tag", "main")?
+        } else {
+            Default::default()
+        };
+
+        let manifest_path =
+            prompter.ask_if_missing(&self.manifest_path, "path to Cargo.toml", "Cargo.toml")?;
+
+        let default_package = package_json["codegenConfig"]["android"]["javaPackageName"]
+            .as_str()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| default_android_package(&name));
+        let android_package = prompter.ask_if_missing(
+            &self.android_package,
+            "Android package name",
+            &default_package,
+        )?;
+
+        let framework_name = format!(
+            "{}Framework",
+            trim_react_native(&name).to_upper_camel_case()
+        );
+
+        Ok(InitAnswers {
+            name,
+            repo,
+            branch,
+            directory,
+            manifest_path,
+            android_package,
+            framework_name,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ubrn.config.yaml", escape = "none")]
+struct InitAnswers {
+    name: String,
+    repo: Option<String>,
+    branch: String,
+    directory: String,
+    manifest_path: String,
+    android_package: String,
+    framework_name: String,
+}
+
+impl InitAnswers {
+    fn upper_camel(&self) -> String {
+        trim_react_native_2(&self.name).to_upper_camel_case()
+    }
+}
+
+/// Add the fields that `config::PackageJson` needs, leaving any existing values alone.
+fn update_package_json(package_json: &mut Value, answers: &InitAnswers) {
+    let upper_camel = answers.upper_camel();
+    let defaults = json!({
+        "name": answers.name,
+        "version": "0.1.0",
+        "main": "src/index.ts",
+        "react-native": "src/index.ts",
+        "repository": {
+            "url": default_repository_url(),
+        },
+        "codegenConfig": {
+            "name": format!("RN{upper_camel}Spec"),
+            "type": "modules",
+            "jsSrcsDir": "src",
+            "android": {
+                "javaPackageName": answers.android_package,
+            },
+        },
+    });
+    merge_missing(package_json, defaults);
+}
+
+fn merge_missing(target: &mut Value, defaults: Value) {
+    match (target, defaults) {
+        (Value::Object(target), Value::Object(defaults)) => {
+            for (k, v) in defaults {
+                match target.get_mut(&k) {
+                    Some(existing) => merge_missing(existing, v),
+                    None => {
+                        target.insert(k, v);
+                    }
+                }
+            }
+        }
+        (target @ Value::Null, defaults) => *target = defaults,
+        _ => (),
+    }
+}
+
+fn default_tsconfig() -> Value {
+    json!({
+        "compilerOptions": {
+            "target": "esnext",
+            "module": "esnext",
+            "moduleResolution": "node",
+            "strict": true,
+            "esModuleInterop": true,
+            "skipLibCheck": true,
+            "jsx": "react-jsx",
+            "lib": ["esnext"],
+            "noEmit": true,
+        },
+        "include": ["src"],
+    })
+}
+
+fn write_json(file: &Utf8Path, value: &Value) -> Result<()> {
+    let mut contents = serde_json::to_string_pretty(value)?;
+    contents.push('\n');
+    std::fs::write(file, contents).with_context(|| format!("Failed to write {file}"))
+}
+
+fn default_android_package(name: &str) -> String {
+    let name = name.rsplit('/').next().unwrap_or(name);
+    format!(
+        "com.{}",
+        trim_react_native_2(name)
+            .to_upper_camel_case()
+            .to_lowercase()
+    )
+}
+
+fn default_repository_url() -> String {
+    let output = Command::new("git")
+        .args(["config", "--get", "remote.origin.url"])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => Default::default(),
+    }
+}
+
+fn is_git_url(s: &str) -> bool {
+    s.contains("://") || s.starts_with("git@") || s.ends_with(".git")
+}
+
+struct Prompter {
+    interactive: bool,
+}
+
+impl Prompter {
+    fn new(interactive: bool) -> Self {
+        Self {
+            interactive: interactive && io::stdin().is_terminal(),
+        }
+    }
+
+    fn ask_if_missing(
+        &self,
+        value: &Option<String>,
+        question: &str,
+        default: &str,
+    ) -> Result<String> {
+        match value {
+            Some(v) => Ok(v.clone()),
+            None => self.ask(question, default),
+        }
+    }
+
+    fn ask(&self, question: &str, default: &str) -> Result<String> {
+        if !self.interactive {
+            return Ok(default.to_string());
+        }
+        print!("{question} [{default}]: ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        let line = line.trim();
+        Ok(if line.is_empty() {
+            default.to_string()
+        } else {
+            line.to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PackageJson;
+
+    fn answers() -> InitAnswers {
+        InitAnswers {
+            name: "react-native-my-lib".into(),
+            repo: None,
+            branch: "main".into(),
+            directory: "rust".into(),
+            manifest_path: "Cargo.toml".into(),
+            android_package: "com.mylib".into(),
+            framework_name: "MyLibFramework".into(),
+        }
+    }
+
+    #[test]
+    fn test_merge_missing() {
+        let mut target = json!({
+            "name": "existing",
+            "version": null,
+            "scripts": { "test": "jest" },
+            "files": ["lib"],
+        });
+        merge_missing(
+            &mut target,
+            json!({
+                "name": "default",
+                "version": "0.1.0",
+                "scripts": { "test": "default", "build": "tsc" },
+                "files": ["src"],
+                "main": "src/index.ts",
+            }),
+        );
+        assert_eq!(
+            target,
+            json!({
+                "name": "existing",
+                "version": "0.1.0",
+                "scripts": { "test": "jest", "build": "tsc" },
+                "files": ["lib"],
+                "main": "src/index.ts",
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_package_json() {
+        let mut package_json = json!({
+            "name": "@me/existing",
+            "repository": { "url": "https://github.com/me/existing" },
+            "codegenConfig": { "jsSrcsDir": "lib" },
+        });
+        update_package_json(&mut package_json, &answers());
+
+        assert_eq!(package_json["name"], "@me/existing");
+        assert_eq!(
+            package_json["repository"]["url"],
+            "https://github.com/me/existing"
+        );
+        assert_eq!(package_json["codegenConfig"]["jsSrcsDir"], "lib");
+        assert_eq!(package_json["codegenConfig"]["name"], "RNMyLibSpec");
+        assert_eq!(
+            package_json["codegenConfig"]["android"]["javaPackageName"],
+            "com.mylib"
+        );
+
+        // It has everything the config needs from the package.json.
+        let package_json: PackageJson = serde_json::from_value(package_json).unwrap();
+        assert_eq!(package_json.codegen().name, "RNMyLibSpec");
+        assert_eq!(package_json.android_package_name(), "com.mylib");
+    }
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(default_android_package("react-native-my-lib"), "com.mylib");
+        assert_eq!(default_android_package("@me/my-lib"), "com.mylib");
+        assert!(is_git_url("https://github.com/me/my-lib"));
+        assert!(is_git_url("git@github.com:me/my-lib.git"));
+        assert!(!is_git_url("./rust"));
+    }
+}
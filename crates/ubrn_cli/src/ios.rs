@@ -10,13 +10,16 @@ use anyhow::{Context, Error, Result};
 use camino::{Utf8Path, Utf8PathBuf};
 use clap::Args;
 use heck::ToUpperCamelCase;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
-use ubrn_common::{mk_dir, rm_dir, run_cmd, CrateMetadata};
+use ubrn_common::{emit, mk_dir, rm_dir, run_cmd, timed, CrateMetadata, LibraryType, Message};
 
 use crate::{
-    building::{CommonBuildArgs, ExtraArgs},
-    config::ProjectConfig,
+    building::{CargoSettings, CommonBuildArgs, ExtraArgs},
+    config::{ConfigArgs, ProjectConfig},
+    fingerprint::BuildCache,
     rust::CrateConfig,
+    symbols::{SymbolsConfig, SymbolsManifest},
     workspace,
 };
 
@@ -37,6 +40,15 @@ pub(crate) struct IOsConfig {
 
     #[serde(default = "IOsConfig::default_cargo_extras")]
     pub(crate) cargo_extras: ExtraArgs,
+
+    #[serde(default = "IOsConfig::default_library_type")]
+    pub(crate) library_type: LibraryType,
+
+    #[serde(default)]
+    pub(crate) symbols: Option<SymbolsConfig>,
+
+    #[serde(flatten)]
+    pub(crate) cargo: CargoSettings,
 }
 
 impl IOsConfig {
@@ -51,6 +63,10 @@ impl IOsConfig {
         )
     }
 
+    fn default_library_type() -> LibraryType {
+        LibraryType::Static
+    }
+
     fn default_cargo_extras() -> ExtraArgs {
         let args: &[&str] = &[];
         args.into()
@@ -78,17 +94,27 @@ impl IOsConfig {
         project_root.join(&self.directory)
     }
 
-    pub(crate) fn framework_path(&self, project_root: &Utf8Path) -> Utf8PathBuf {
-        let filename = format!("{}.xcframework", self.framework_name);
+    /// The path of the xcframework for one crate.
+    ///
+    /// When there is more than one crate, each gets its own xcframework, with the
+    /// library name of the crate added to the `frameworkName`.
+    pub(crate) fn framework_path(
+        &self,
+        project_root: &Utf8Path,
+        library_name: Option<&str>,
+    ) -> Utf8PathBuf {
+        let filename = match library_name {
+            Some(name) => format!("{}-{name}.xcframework", self.framework_name),
+            None => format!("{}.xcframework", self.framework_name),
+        };
         project_root.join(filename)
     }
 }
 
 #[derive(Args, Debug)]
 pub(crate) struct IOsArgs {
-    /// The configuration file for this build
-    #[clap(long)]
-    config: Utf8PathBuf,
+    #[clap(flatten)]
+    config: ConfigArgs,
 
     /// Only build for the simulator
     #[clap(long, default_value = "false")]
@@ -120,9 +146,10 @@ pub(crate) struct IOsArgs {
 }
 
 impl IOsArgs {
-    pub(crate) fn build(&self) -> Result<Vec<Utf8PathBuf>> {
+    /// Build each of the crates for each of the targets, returning the libraries for
+    /// each crate.
+    pub(crate) fn build(&self) -> Result<Vec<Vec<Utf8PathBuf>>> {
         let config = self.project_config()?;
-        let crate_ = &config.crate_;
         let ios = &config.ios;
 
         let target_list = if !self.targets.is_empty() {
@@ -146,96 +173,232 @@ impl IOsArgs {
             .cloned()
             .collect::<Vec<_>>();
 
+        let metadata = config.crates_metadata()?;
+        let mut crates = Vec::new();
+        for (crate_, metadata) in config.crates.iter().zip(metadata) {
+            let metadata = metadata.with_library_type(ios.library_type);
+            crates.push(self.build_crate(&config, crate_, &metadata, &targets)?);
+        }
+        Ok(crates)
+    }
+
+    fn build_crate(
+        &self,
+        config: &ProjectConfig,
+        crate_: &CrateConfig,
+        metadata: &CrateMetadata,
+        targets: &[Target],
+    ) -> Result<Vec<Utf8PathBuf>> {
+        let ios = &config.ios;
+        let cargo = crate_.cargo.merge(&ios.cargo);
+        let cache = self.common_args.build_cache(metadata, "")?;
         let target_files = if self.common_args.no_cargo {
-            let files = self.find_existing(&crate_.metadata()?, &targets);
+            let files = self.find_existing(metadata, &cargo, targets);
             if !files.is_empty() {
                 files
             } else {
-                self.cargo_build_all(crate_, &targets, &ios.cargo_extras)?
+                self.cargo_build_all(crate_, metadata, &cargo, targets, &ios.cargo_extras, &cache)?
             }
         } else {
-            self.cargo_build_all(crate_, &targets, &ios.cargo_extras)?
+            self.cargo_build_all(crate_, metadata, &cargo, targets, &ios.cargo_extras, &cache)?
         };
 
-        Ok(if !self.no_xcodebuild {
-            let target_files = self.lipo_when_necessary(crate_, target_files)?;
-            self.create_xcframework(&config, &target_files)?;
-            target_files
+        let libraries = if !self.no_xcodebuild {
+            let framework_path = config.ios_framework_path(config.project_root(), metadata);
+            let symbols = self.symbols_config(ios, &cargo);
+            let mut libraries = target_files.values().collect::<Vec<_>>();
+            libraries.sort();
+            let inputs = format!(
+                "{libraries:?} {} {:?}",
+                symbols.is_some(),
+                ios.xcodebuild_extras
+            );
+            // Only if every target is up to date can the xcframework be.
+            let targets_fresh = cache.is_all_fresh();
+            let framework_fresh = cache.is_fresh("xcframework", &inputs, &framework_path);
+            let fresh = targets_fresh && framework_fresh;
+            let libraries = self.lipo_when_necessary(metadata, target_files, fresh)?;
+            if fresh {
+                info!("{framework_path}: up to date");
+                libraries.into_values().collect()
+            } else {
+                self.make_xcframework(config, metadata, &cargo, &framework_path, libraries)?
+            }
         } else {
             target_files.into_values().collect()
-        })
+        };
+        cache.save()?;
+        Ok(libraries)
+    }
+
+    /// Make the xcframework from the libraries, with their dSYMs if the symbols are kept.
+    fn make_xcframework(
+        &self,
+        config: &ProjectConfig,
+        metadata: &CrateMetadata,
+        cargo: &CargoSettings,
+        framework_path: &Utf8Path,
+        libraries: HashMap<Platform, Utf8PathBuf>,
+    ) -> Result<Vec<Utf8PathBuf>> {
+        let ios = &config.ios;
+        let framework_libraries = match self.symbols_config(ios, cargo) {
+            Some(symbols) => {
+                let mut dir = symbols.directory(config.project_root(), "ios");
+                if config.has_many_crates() {
+                    dir = dir.join(metadata.library_name());
+                }
+                let profile = self.common_args.cargo_profile(cargo);
+                self.split_symbols(metadata, &dir, &profile, &libraries)?
+            }
+            None => libraries.values().map(|l| (l.clone(), None)).collect(),
+        };
+        self.create_xcframework(config, framework_path, &framework_libraries)?;
+        Ok(libraries.into_values().collect())
+    }
+
+    /// The symbols configuration, if dSYMs should be made for this build.
+    ///
+    /// Only release builds of shared libraries are stripped. Static libraries are linked
+    /// into the app, so Xcode makes the dSYM for them.
+    fn symbols_config<'a>(
+        &self,
+        ios: &'a IOsConfig,
+        cargo: &CargoSettings,
+    ) -> Option<&'a SymbolsConfig> {
+        let symbols = ios.symbols.as_ref()?;
+        if !self.common_args.is_release(cargo) {
+            return None;
+        }
+        if !ios.library_type.is_shared() {
+            warn!("Not making dSYMs: the Rust library is static, so its symbols are in the app's dSYM");
+            return None;
+        }
+        Some(symbols)
+    }
+
+    /// Make a dSYM for each library, and a stripped copy of the library to go into
+    /// the xcframework alongside it.
+    fn split_symbols(
+        &self,
+        metadata: &CrateMetadata,
+        dir: &Utf8Path,
+        profile: &str,
+        libraries: &HashMap<Platform, Utf8PathBuf>,
+    ) -> Result<Vec<(Utf8PathBuf, Option<Utf8PathBuf>)>> {
+        info!("-- Keeping debug symbols in {dir}");
+        rm_dir(dir)?;
+        let library_file = metadata.library_file(Some("ios"));
+        let mut manifest = SymbolsManifest::new(&library_file, profile);
+        let mut split = Vec::new();
+        for (platform, library) in libraries {
+            let folder = platform.lib_folder_name();
+            let dsym_dir = dir.join(folder);
+            mk_dir(&dsym_dir)?;
+            let dsym = dsym_dir.join(format!("{library_file}.dSYM"));
+            let mut cmd = Command::new("dsymutil");
+            cmd.arg(library).arg("-o").arg(&dsym);
+            run_cmd(&mut cmd)?;
+            manifest.add(folder, dir, &dsym, library)?;
+
+            let stripped_dir = metadata.target_dir().join("stripped").join(folder);
+            mk_dir(&stripped_dir)?;
+            let stripped = stripped_dir.join(&library_file);
+            let mut cmd = Command::new("strip");
+            cmd.arg("-x").arg(library).arg("-o").arg(&stripped);
+            run_cmd(&mut cmd)?;
+            split.push((stripped, Some(dsym)));
+        }
+        manifest.write(dir)?;
+        Ok(split)
     }
 
     fn cargo_build_all(
         &self,
         crate_: &CrateConfig,
+        metadata: &CrateMetadata,
+        cargo: &CargoSettings,
         targets: &[Target],
         cargo_extras: &ExtraArgs,
+        cache: &BuildCache,
     ) -> Result<HashMap<Target, Utf8PathBuf>> {
-        let mut target_files = HashMap::new();
-        let metadata = crate_.metadata()?;
         let rust_dir = crate_.directory()?;
         let manifest_path = crate_.manifest_path()?;
-        for target in targets {
-            self.cargo_build(&manifest_path, target, cargo_extras, &rust_dir)?;
-
-            // Now we need to get the path to the lib.a file, to feed to xcodebuild.
-            let library = metadata.library_path(Some(&target.triple), self.common_args.profile());
-            metadata.library_path_exists(&library)?;
-            target_files.insert(target.clone(), library);
-        }
-        Ok(target_files)
+        let profile = self.common_args.cargo_profile(cargo);
+        self.common_args
+            .build_targets(metadata.library_name(), targets, |target| {
+                let metadata = self.common_args.target_metadata(metadata, &target.triple);
+                // This is the path to the lib.a file, to feed to xcodebuild.
+                let library = metadata.library_path(Some(&target.triple), &profile);
+                let mut cmd = self.cargo_cmd(&manifest_path, target, cargo, cargo_extras);
+                cmd.current_dir(&rust_dir);
+                if cache.is_fresh_cmd(&target.triple, &cmd, &library) {
+                    info!("{}: up to date", target.triple);
+                    return Ok((library, true));
+                }
+                self.common_args
+                    .run_cargo(&mut cmd, &metadata, &target.triple)?;
+                metadata.library_path_exists(&library)?;
+                Ok((library, false))
+            })
     }
 
-    fn cargo_build(
+    fn cargo_cmd(
         &self,
         manifest_path: &Utf8PathBuf,
         target: &Target,
+        cargo: &CargoSettings,
         cargo_extras: &ExtraArgs,
-        rust_dir: &Utf8PathBuf,
-    ) -> Result<()> {
+    ) -> Command {
         let mut cmd = Command::new("cargo");
         cmd.arg("build")
             .arg("--manifest-path")
             .arg(manifest_path)
             .arg("--target")
             .arg(&target.triple);
-        if self.common_args.release {
-            cmd.arg("--release");
-        }
+        cargo.apply(&mut cmd, &self.common_args.cargo_profile(cargo));
         cmd.args(cargo_extras.clone());
-        run_cmd(cmd.current_dir(rust_dir))?;
-        Ok(())
+        cmd
     }
 
+    /// Combine the libraries for each platform into one, if there is more than one.
+    ///
+    /// If the libraries are up to date, then so is the output of any earlier `lipo`.
     fn lipo_when_necessary(
         &self,
-        crate_: &CrateConfig,
+        metadata: &CrateMetadata,
         target_files: HashMap<Target, Utf8PathBuf>,
-    ) -> Result<Vec<Utf8PathBuf>> {
+        fresh: bool,
+    ) -> Result<HashMap<Platform, Utf8PathBuf>> {
         let mut by_platform = HashMap::new();
         for (target, file) in target_files {
             let files = by_platform.entry(target.platform).or_insert(Vec::new());
             files.push(file);
         }
-        let metadata = crate_.metadata()?;
 
-        let mut sorted = Vec::new();
+        let mut sorted = HashMap::new();
         for (p, mut files) in by_platform {
             if files.len() == 1 {
-                sorted.append(&mut files);
+                sorted.insert(p, files.remove(0));
             } else {
                 let dir = metadata.target_dir().join("lipo").join(p.lib_folder_name());
                 mk_dir(&dir)?;
                 let output = dir.join(metadata.library_file(Some("ios")));
+                if fresh && output.exists() {
+                    sorted.insert(p, output);
+                    continue;
+                }
                 let mut cmd = Command::new("lipo");
                 cmd.arg("-create");
-                for f in files {
+                for f in &files {
                     cmd.arg(f);
                 }
                 cmd.arg("-output").arg(&output);
-                run_cmd(&mut cmd)?;
-                sorted.push(output);
+                timed("lipo", || run_cmd(&mut cmd))?;
+                emit(Message::Lipo {
+                    inputs: files,
+                    output: output.clone(),
+                });
+                sorted.insert(p, output);
             }
         }
 
@@ -245,57 +408,67 @@ impl IOsArgs {
     fn create_xcframework(
         &self,
         config: &ProjectConfig,
-        target_files: &[Utf8PathBuf],
+        framework_path: &Utf8Path,
+        target_files: &[(Utf8PathBuf, Option<Utf8PathBuf>)],
     ) -> Result<(), anyhow::Error> {
         let ios = &config.ios;
         let project_root = config.project_root();
         let ios_dir = ios.directory(project_root);
         ubrn_common::mk_dir(&ios_dir)?;
         let mut library_args = Vec::new();
-        for library in target_files {
+        for (library, dsym) in target_files {
             // :eyes: single dash arg.
             library_args.push("-library".to_string());
             library_args.push(library.to_string());
+            if let Some(dsym) = dsym {
+                library_args.push("-debug-symbols".to_string());
+                library_args.push(dsym.to_string());
+            }
         }
-        let framework_path = ios.framework_path(project_root);
         if framework_path.exists() {
-            rm_dir(&framework_path)?;
+            rm_dir(framework_path)?;
         }
         let mut cmd = Command::new("xcodebuild");
         cmd.arg("-create-xcframework")
             .args(library_args)
             .arg("-output")
-            .arg(&framework_path)
+            .arg(framework_path)
             .args(ios.xcodebuild_extras.clone());
-        run_cmd(cmd.current_dir(ios_dir))?;
+        timed("xcodebuild", || run_cmd(cmd.current_dir(ios_dir)))?;
+        emit(Message::Xcframework {
+            path: framework_path.to_owned(),
+            libraries: target_files.iter().map(|(l, _)| l.clone()).collect(),
+        });
         Ok(())
     }
 
     fn find_existing(
         &self,
         metadata: &CrateMetadata,
+        cargo: &CargoSettings,
         targets: &[Target],
     ) -> HashMap<Target, Utf8PathBuf> {
-        let profile = self.common_args.profile();
+        let profile = self.common_args.cargo_profile(cargo);
         targets
             .iter()
             .filter_map(|target| {
-                let library = metadata.library_path(Some(&target.triple), profile);
-                if library.exists() {
-                    Some((target.clone(), library))
-                } else {
-                    None
-                }
+                let library = self.common_args.find_existing(
+                    metadata,
+                    &target.triple,
+                    &target.triple,
+                    &profile,
+                )?;
+                Some((target.clone(), library))
             })
             .collect::<HashMap<_, _>>()
     }
 
     pub(crate) fn project_config(&self) -> Result<ProjectConfig> {
-        self.config.clone().try_into()
+        self.config.load()
     }
 
-    pub(crate) fn config(&self) -> Utf8PathBuf {
-        self.config.clone()
+    pub(crate) fn config(&self) -> &ConfigArgs {
+        &self.config
     }
 }
 
@@ -4,49 +4,48 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 use anyhow::{Error, Result};
-use camino::Utf8PathBuf;
 use clap::Parser;
-use config::ProjectConfig;
+use config::{ConfigArgs, ProjectConfig};
 
 mod android;
 mod building;
 mod cli;
 mod codegen;
 mod config;
+mod doctor;
+mod fingerprint;
 mod generate;
+mod init;
 mod ios;
+mod pack;
 mod repo;
 mod rust;
+mod symbols;
 mod workspace;
 
 fn main() -> Result<()> {
     let args = cli::CliArgs::parse();
-    args.cmd.run()
+    args.init_logging()?;
+    if let Err(e) = args.cmd.run() {
+        // Log the error, so that it goes to the log file too.
+        log::error!("{e:?}");
+        std::process::exit(1);
+    }
+    Ok(())
 }
 
 pub(crate) trait AsConfig<T>
 where
     T: TryFrom<ProjectConfig, Error = Error>,
 {
-    fn config_file(&self) -> Option<Utf8PathBuf>;
+    fn config(&self) -> &ConfigArgs;
     fn get(&self) -> Option<T>;
 
     fn as_config(&self) -> Result<T> {
         if let Some(t) = self.get() {
             Ok(t)
-        } else if let Some(f) = self.config_file() {
-            let config: ProjectConfig = f.try_into()?;
-            config.try_into()
         } else {
-            anyhow::bail!("Could not find a suitable value")
+            self.config().load()?.try_into()
         }
     }
 }
-
-impl TryFrom<Utf8PathBuf> for ProjectConfig {
-    type Error = Error;
-
-    fn try_from(value: Utf8PathBuf) -> Result<Self, Self::Error> {
-        ubrn_common::read_from_file(value)
-    }
-}
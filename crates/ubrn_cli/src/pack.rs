@@ -0,0 +1,493 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{collections::BTreeMap, fmt::Display, fs, process::Command};
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ubrn_common::{absolute, cp_file, list_files, mk_dir, rm_dir, run_cmd_quietly, write_file};
+
+use crate::{
+    config::{ConfigArgs, ProjectConfig},
+    workspace,
+};
+
+#[derive(Args, Debug)]
+pub(crate) struct PackArgs {
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    /// Only pack the Android libraries
+    #[clap(long, conflicts_with_all = ["ios"])]
+    android: bool,
+
+    /// Only pack the iOS xcframeworks
+    #[clap(long)]
+    ios: bool,
+
+    /// The directory to write the tarballs and the manifest to
+    #[clap(long, default_value = "dist")]
+    out_dir: Utf8PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct FetchArgs {
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    /// Only fetch the Android libraries
+    #[clap(long, conflicts_with_all = ["ios"])]
+    android: bool,
+
+    /// Only fetch the iOS xcframeworks
+    #[clap(long)]
+    ios: bool,
+
+    /// Where the output of `ubrn pack` was published: either a base URL, or a local directory
+    #[clap(long)]
+    from: String,
+}
+
+/// What `ubrn pack` produced for one version of the package.
+///
+/// This is written next to the tarballs, as `<package>-<version>.json`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    name: String,
+    version: String,
+    crates: Vec<CrateVersion>,
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CrateVersion {
+    library_name: String,
+    version: String,
+}
+
+/// One tarball, of the built libraries for one platform.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Artifact {
+    platform: Platform,
+    file: String,
+    sha256: String,
+    /// The Android ABIs, or the slices of the xcframeworks.
+    targets: Vec<String>,
+    /// The directories in the tarball, relative to the project root.
+    paths: Vec<Utf8PathBuf>,
+    /// The hash of each file in the tarball.
+    files: BTreeMap<Utf8PathBuf, String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Platform {
+    Android,
+    Ios,
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Android => "android",
+            Self::Ios => "ios",
+        })
+    }
+}
+
+impl PackArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let config = self.config.load()?;
+        let project_root = config.project_root();
+        let version = package_version()?;
+        let metadata = config.crates_metadata()?;
+        let crates = metadata
+            .iter()
+            .map(|m| CrateVersion {
+                library_name: m.library_name().to_string(),
+                version: m.version().to_string(),
+            })
+            .collect::<Vec<_>>();
+        let library_names = metadata.iter().map(|m| m.library_name());
+        let library_names = library_names.collect::<Vec<_>>();
+
+        let platforms = match (self.android, self.ios) {
+            (true, _) => vec![Platform::Android],
+            (_, true) => vec![Platform::Ios],
+            _ => [Platform::Android, Platform::Ios]
+                .into_iter()
+                .filter(|p| {
+                    platform_paths(&config, *p, &library_names)
+                        .iter()
+                        .all(|path| project_root.join(path).exists())
+                })
+                .collect(),
+        };
+        if platforms.is_empty() {
+            bail!("Nothing to pack: build for Android or iOS first, with `ubrn build`");
+        }
+
+        mk_dir(&self.out_dir)?;
+        let out_dir = absolute(&self.out_dir)?;
+        let manifest_file = out_dir.join(manifest_filename(&config, &version));
+        let mut manifest = Manifest {
+            name: config.raw_name().to_string(),
+            version: version.clone(),
+            crates,
+            artifacts: Default::default(),
+        };
+        // The platforms may be packed separately, e.g. on different CI machines, so keep
+        // the other platforms of the same version.
+        if let Ok(existing) = ubrn_common::read_from_file::<_, Manifest>(&manifest_file) {
+            if existing.version == version {
+                manifest.artifacts = existing.artifacts;
+                manifest
+                    .artifacts
+                    .retain(|a| !platforms.contains(&a.platform));
+            }
+        }
+
+        for platform in platforms {
+            let paths = platform_paths(&config, platform, &library_names);
+            let mut files = BTreeMap::new();
+            let mut targets = Vec::new();
+            for path in &paths {
+                let dir = project_root.join(path);
+                if !dir.exists() {
+                    bail!("{dir} does not exist: build it first, with `ubrn build {platform}`");
+                }
+                for file in list_files(&dir, &|_| false)? {
+                    let relative = file.strip_prefix(project_root)?.to_owned();
+                    files.insert(relative, sha256_file(&file)?);
+                }
+                for entry in dir.read_dir_utf8()? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        targets.push(entry.file_name().to_string());
+                    }
+                }
+            }
+            targets.sort();
+            targets.dedup();
+
+            let file = format!("{}-{version}-{platform}.tar.gz", config.cpp_filename());
+            let tarball = out_dir.join(&file);
+            let mut cmd = Command::new("tar");
+            cmd.arg("-czf")
+                .arg(&tarball)
+                .arg("-C")
+                .arg(project_root)
+                .args(&paths);
+            run_cmd_quietly(&mut cmd)?;
+            info!("Packed {tarball}");
+
+            manifest.artifacts.push(Artifact {
+                platform,
+                sha256: sha256_file(&tarball)?,
+                file,
+                targets,
+                paths,
+                files,
+            });
+        }
+
+        manifest
+            .artifacts
+            .sort_by_key(|a| matches!(a.platform, Platform::Ios));
+        let json = serde_json::to_string_pretty(&manifest)?;
+        write_file(&manifest_file, &json)
+    }
+}
+
+impl FetchArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        self.fetch().context(
+            "Could not fetch the prebuilt libraries; build them from source with `ubrn build` instead",
+        )
+    }
+
+    fn fetch(&self) -> Result<()> {
+        let config = self.config.load()?;
+        let project_root = config.project_root();
+        let version = package_version()?;
+        let cache_dir = project_root.join("node_modules/.cache/ubrn/fetch");
+        rm_dir(&cache_dir)?;
+        mk_dir(&cache_dir)?;
+
+        let manifest_filename = manifest_filename(&config, &version);
+        let manifest_file = cache_dir.join(&manifest_filename);
+        download(&self.from, &manifest_filename, &manifest_file)?;
+        let manifest: Manifest = ubrn_common::read_from_file(&manifest_file)?;
+        if manifest.version != version {
+            bail!(
+                "{manifest_filename} is for version {}, not {version}",
+                manifest.version
+            );
+        }
+        let library_names = manifest.crates.iter().map(|c| c.library_name.as_str());
+        let library_names = library_names.collect::<Vec<_>>();
+
+        let platforms = match (self.android, self.ios) {
+            (true, _) => vec![Platform::Android],
+            (_, true) => vec![Platform::Ios],
+            _ => manifest.artifacts.iter().map(|a| a.platform).collect(),
+        };
+        for platform in platforms {
+            let Some(artifact) = manifest.artifacts.iter().find(|a| a.platform == platform) else {
+                bail!("{manifest_filename} has no libraries for {platform}");
+            };
+            // Only write to where the build would put the libraries.
+            let expected = platform_paths(&config, platform, &library_names);
+            if let Some(path) = artifact.paths.iter().find(|p| !expected.contains(p)) {
+                bail!("{} contains {path}, which is not expected", artifact.file);
+            }
+
+            let tarball = cache_dir.join(&artifact.file);
+            download(&self.from, &artifact.file, &tarball)?;
+            let sha256 = sha256_file(&tarball)?;
+            if sha256 != artifact.sha256 {
+                bail!(
+                    "{} has the hash {sha256}, but {} was expected",
+                    artifact.file,
+                    artifact.sha256
+                );
+            }
+
+            let unpacked = cache_dir.join(platform.to_string());
+            mk_dir(&unpacked)?;
+            let mut cmd = Command::new("tar");
+            cmd.arg("-xzf").arg(&tarball).arg("-C").arg(&unpacked);
+            run_cmd_quietly(&mut cmd)?;
+            verify_files(&unpacked, artifact)?;
+
+            for path in &artifact.paths {
+                let dest = project_root.join(path);
+                rm_dir(&dest)?;
+                if let Some(parent) = dest.parent() {
+                    mk_dir(parent)?;
+                }
+                fs::rename(unpacked.join(path), &dest)
+                    .with_context(|| format!("Failed to move {path} into place"))?;
+                info!("Fetched {dest}");
+            }
+        }
+
+        rm_dir(&cache_dir)
+    }
+}
+
+/// The directories which the libraries for the platform are built into, relative to the
+/// project root.
+///
+/// The library names are used instead of the crate metadata, because the crates may not
+/// have been checked out when fetching.
+fn platform_paths(
+    config: &ProjectConfig,
+    platform: Platform,
+    library_names: &[&str],
+) -> Vec<Utf8PathBuf> {
+    let project_root = Utf8Path::new("");
+    match platform {
+        Platform::Android => vec![config.android.jni_libs(project_root)],
+        Platform::Ios => library_names
+            .iter()
+            .map(|name| {
+                let name = config.has_many_crates().then_some(*name);
+                config.ios.framework_path(project_root, name)
+            })
+            .collect(),
+    }
+}
+
+/// Check that the unpacked files are exactly those in the manifest, with the same hashes.
+fn verify_files(unpacked: &Utf8Path, artifact: &Artifact) -> Result<()> {
+    let mut found = BTreeMap::new();
+    for path in &artifact.paths {
+        let dir = unpacked.join(path);
+        if !dir.exists() {
+            bail!("{} does not contain {path}", artifact.file);
+        }
+        for file in list_files(&dir, &|_| false)? {
+            let relative = file.strip_prefix(unpacked)?.to_owned();
+            found.insert(relative, sha256_file(&file)?);
+        }
+    }
+    for (file, sha256) in &artifact.files {
+        match found.remove(file) {
+            Some(s) if &s == sha256 => (),
+            Some(s) => bail!("{file} has the hash {s}, but {sha256} was expected"),
+            None => bail!("{} does not contain {file}", artifact.file),
+        }
+    }
+    if let Some(file) = found.keys().next() {
+        bail!(
+            "{} contains {file}, which is not in the manifest",
+            artifact.file
+        );
+    }
+    Ok(())
+}
+
+/// Copy the file from the base URL or directory.
+fn download(from: &str, file: &str, dest: &Utf8Path) -> Result<()> {
+    if from.starts_with("https://") || from.starts_with("http://") {
+        let url = format!("{}/{file}", from.trim_end_matches('/'));
+        info!("Downloading {url}");
+        let mut cmd = Command::new("curl");
+        cmd.args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--output",
+        ])
+        .arg(dest)
+        .arg(&url);
+        run_cmd_quietly(&mut cmd)
+    } else {
+        let dir = Utf8Path::new(from.strip_prefix("file://").unwrap_or(from));
+        let from = dir.join(file);
+        if !from.exists() {
+            bail!("{from} does not exist");
+        }
+        cp_file(&from, dest)
+    }
+}
+
+fn manifest_filename(config: &ProjectConfig, version: &str) -> String {
+    format!("{}-{version}.json", config.cpp_filename())
+}
+
+/// The tarballs are versioned with the npm package, so the version installed can find its own.
+fn package_version() -> Result<String> {
+    let package_json = workspace::package_json();
+    match package_json.version() {
+        Some(version) => Ok(version.to_string()),
+        None => bail!("package.json needs a version"),
+    }
+}
+
+fn sha256_file(file: &Utf8Path) -> Result<String> {
+    let contents = fs::read(file).with_context(|| format!("Failed to read {file}"))?;
+    Ok(format!("{:x}", Sha256::digest(contents)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rust: &str) -> ProjectConfig {
+        let source = format!(
+            r#"
+name: my-lib
+repository: https://github.com/example/my-lib
+rust: {rust}
+android:
+  packageName: com.example.mylib
+ios:
+  frameworkName: MyFramework
+turboModule:
+  ts: src
+  specName: MyLib
+  name: MyLibSpec
+"#
+        );
+        serde_yaml::from_str(&source).unwrap()
+    }
+
+    #[test]
+    fn test_platform_paths() {
+        let one = config("{ directory: ./rust }");
+        assert_eq!(
+            platform_paths(&one, Platform::Android, &["mylib"]),
+            vec!["android/src/main/jniLibs"]
+        );
+        assert_eq!(
+            platform_paths(&one, Platform::Ios, &["mylib"]),
+            vec!["MyFramework.xcframework"]
+        );
+
+        // With more than one crate, each has its own xcframework, but they share jniLibs.
+        let many = config("[{ directory: ./crypto }, { directory: ./sync }]");
+        assert_eq!(
+            platform_paths(&many, Platform::Android, &["crypto", "sync"]),
+            vec!["android/src/main/jniLibs"]
+        );
+        assert_eq!(
+            platform_paths(&many, Platform::Ios, &["crypto", "sync"]),
+            vec![
+                "MyFramework-crypto.xcframework",
+                "MyFramework-sync.xcframework"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_files() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("ubrn-verify-files-{}", std::process::id()));
+        let unpacked = Utf8PathBuf::try_from(dir)?;
+        if unpacked.exists() {
+            rm_dir(&unpacked)?;
+        }
+        let jni_libs = Utf8PathBuf::from("android/src/main/jniLibs");
+        let library = jni_libs.join("arm64-v8a/libmylib.a");
+        mk_dir(unpacked.join(&jni_libs).join("arm64-v8a"))?;
+        fs::write(unpacked.join(&library), "library")?;
+
+        let mut artifact = Artifact {
+            platform: Platform::Android,
+            file: "my-lib-android.tar.gz".into(),
+            sha256: Default::default(),
+            targets: vec!["arm64-v8a".into()],
+            paths: vec![jni_libs.clone()],
+            files: BTreeMap::from([(library.clone(), sha256_file(&unpacked.join(&library))?)]),
+        };
+        verify_files(&unpacked, &artifact)?;
+
+        let error =
+            |artifact: &Artifact| verify_files(&unpacked, artifact).unwrap_err().to_string();
+
+        // A file which isn't in the manifest.
+        let extra = jni_libs.join("x86_64/libmylib.a");
+        mk_dir(unpacked.join(&jni_libs).join("x86_64"))?;
+        fs::write(unpacked.join(&extra), "library")?;
+        assert_eq!(
+            error(&artifact),
+            format!("my-lib-android.tar.gz contains {extra}, which is not in the manifest")
+        );
+        fs::remove_file(unpacked.join(&extra))?;
+
+        // A file which has been changed.
+        artifact.files.insert(library.clone(), "0000".into());
+        assert!(error(&artifact).starts_with(&format!("{library} has the hash ")));
+
+        // A file which is missing.
+        artifact.files.clear();
+        artifact
+            .files
+            .insert(jni_libs.join("x86/libmylib.a"), "0000".into());
+        assert_eq!(
+            error(&artifact),
+            format!("my-lib-android.tar.gz does not contain {jni_libs}/x86/libmylib.a")
+        );
+
+        // A directory which is missing.
+        artifact.paths.push("MyFramework.xcframework".into());
+        assert_eq!(
+            error(&artifact),
+            "my-lib-android.tar.gz does not contain MyFramework.xcframework"
+        );
+
+        rm_dir(&unpacked)?;
+        Ok(())
+    }
+}
@@ -3,78 +3,353 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
-use std::process::Command;
+use std::{collections::BTreeMap, process::Command};
 
 use anyhow::Result;
 use camino::{Utf8Path, Utf8PathBuf};
 use clap::Args;
-use serde::Deserialize;
-use ubrn_common::run_cmd;
+use log::info;
+use serde::{Deserialize, Serialize};
+use ubrn_common::{run_cmd, run_cmd_for_output, write_file};
 
-use crate::{config::ProjectConfig, AsConfig};
+use crate::{
+    config::{ConfigArgs, ProjectConfig},
+    AsConfig,
+};
 
 #[derive(Args, Clone, Debug, Deserialize)]
 pub(crate) struct GitRepoArgs {
     /// The repository where to get the crate
     pub(crate) repo: String,
-    /// The branch or tag which to checkout
+    /// The branch which to checkout
     #[clap(long, default_value = "main")]
     #[serde(default = "GitRepoArgs::default_branch")]
     pub(crate) branch: String,
+    /// The tag which to checkout, instead of a branch
+    #[clap(long, conflicts_with_all = ["branch", "rev"])]
+    #[serde(default)]
+    pub(crate) tag: Option<String>,
+    /// The commit which to checkout, instead of a branch
+    #[clap(long, conflicts_with_all = ["branch"])]
+    #[serde(default)]
+    pub(crate) rev: Option<String>,
 }
 
 impl GitRepoArgs {
-    fn default_branch() -> String {
+    pub(crate) fn default_branch() -> String {
         "main".into()
     }
 }
 
 #[derive(Debug, Args)]
 pub(crate) struct CheckoutArgs {
-    #[clap(long, conflicts_with_all = ["repo"])]
-    config: Option<Utf8PathBuf>,
+    #[clap(flatten)]
+    config: ConfigArgs,
 
     #[clap(flatten)]
     repo: Option<GitRepoArgs>,
 }
 
-impl TryFrom<ProjectConfig> for GitRepoArgs {
+impl TryFrom<ProjectConfig> for Vec<GitRepoArgs> {
     type Error = anyhow::Error;
 
+    /// The git repos of all the crates. Crates in the same repo share one checkout, so
+    /// must use the same branch, tag or rev.
     fn try_from(value: ProjectConfig) -> Result<Self> {
-        value.crate_.src.try_into()
+        let mut repos: Vec<GitRepoArgs> = Vec::new();
+        for crate_ in value.crates {
+            if let Ok(args) = GitRepoArgs::try_from(crate_.src) {
+                match repos.iter().find(|r| r.repo == args.repo) {
+                    Some(r) if r.reference() != args.reference() => anyhow::bail!(
+                        "Crates from {} use both the {} and the {}",
+                        args.repo,
+                        r.reference(),
+                        args.reference()
+                    ),
+                    Some(_) => (),
+                    None => repos.push(args),
+                }
+            }
+        }
+        if repos.is_empty() {
+            anyhow::bail!("Nothing to do");
+        }
+        Ok(repos)
     }
 }
 
-impl AsConfig<GitRepoArgs> for CheckoutArgs {
-    fn config_file(&self) -> Option<Utf8PathBuf> {
-        self.config.clone()
+impl AsConfig<Vec<GitRepoArgs>> for CheckoutArgs {
+    fn config(&self) -> &ConfigArgs {
+        &self.config
     }
 
-    fn get(&self) -> Option<GitRepoArgs> {
-        self.repo.clone()
+    fn get(&self) -> Option<Vec<GitRepoArgs>> {
+        self.repo.clone().map(|r| vec![r])
     }
 }
 
 impl GitRepoArgs {
     pub(crate) fn directory(&self, project_root: &Utf8Path) -> Result<Utf8PathBuf> {
+        Ok(project_root
+            .join("rust_modules")
+            .join(Self::directory_name(&self.repo)))
+    }
+
+    /// The name of the directory in `rust_modules` which the repo is checked out into.
+    ///
+    /// This is the last part of the URL, so two repos with the same name would share a
+    /// directory: the config file is checked for this when it is loaded.
+    pub(crate) fn directory_name(repo: &str) -> &str {
         // Use Utf8Path for URL operations is a little bit hacky,
         // but as we only need URL for this operation, we can avoid
         // dragging in another dependency.
-        let url_path = Utf8Path::new(self.repo.as_str());
-        let repo_name = url_path.file_name().unwrap();
-        let repo_name = repo_name.strip_suffix(".git").unwrap_or(repo_name);
-        Ok(project_root.join("rust_modules").join(repo_name))
+        let url_path = Utf8Path::new(repo);
+        let repo_name = url_path.file_name().unwrap_or(repo);
+        repo_name.strip_suffix(".git").unwrap_or(repo_name)
+    }
+
+    /// What was asked for, as recorded in the lockfile.
+    fn reference(&self) -> String {
+        match (&self.rev, &self.tag) {
+            (Some(rev), _) => format!("rev {rev}"),
+            (_, Some(tag)) => format!("tag {tag}"),
+            _ => format!("branch {}", self.branch),
+        }
+    }
+
+    /// What was asked for, as understood by `git rev-parse` after a fetch.
+    ///
+    /// A `branch` has always been allowed to be a tag, so both are tried.
+    fn revisions(&self) -> Vec<String> {
+        match (&self.rev, &self.tag) {
+            (Some(rev), _) => vec![rev.clone()],
+            (_, Some(tag)) => vec![format!("refs/tags/{tag}")],
+            _ => vec![
+                format!("refs/remotes/origin/{}", self.branch),
+                format!("refs/tags/{}", self.branch),
+            ],
+        }
     }
 
+    /// Checkout the commit recorded in the lockfile, or if there isn't one, the
+    /// latest commit of the branch, tag or rev, and record it in the lockfile.
     pub(crate) fn checkout(&self, project_root: &Utf8Path) -> Result<()> {
-        let mut cmd = Command::new("git");
-        cmd.arg("clone")
+        let mut lock = LockFile::read(project_root)?;
+        let commit = match lock.get(self) {
+            Some(commit) => {
+                self.fetch(project_root, Some(&commit))?;
+                commit
+            }
+            None => {
+                self.fetch(project_root, None)?;
+                self.resolve(project_root)?
+            }
+        };
+        self.checkout_commit(project_root, &commit)?;
+        lock.insert(self, commit);
+        lock.write(project_root)
+    }
+
+    /// Move the lockfile forward to the latest commit of the branch, tag or rev.
+    pub(crate) fn update(&self, project_root: &Utf8Path) -> Result<()> {
+        let mut lock = LockFile::read(project_root)?;
+        self.fetch(project_root, None)?;
+        let commit = self.resolve(project_root)?;
+        match lock.get(self) {
+            Some(old) if old == commit => info!("{} is already at {commit}", self.repo),
+            Some(old) => info!("Updating {} from {old} to {commit}", self.repo),
+            None => info!("Locking {} at {commit}", self.repo),
+        }
+        self.checkout_commit(project_root, &commit)?;
+        lock.insert(self, commit);
+        lock.write(project_root)
+    }
+
+    /// Clone the repo, or if it has already been cloned, fetch into it.
+    ///
+    /// If the commit is already present, then nothing is fetched.
+    fn fetch(&self, project_root: &Utf8Path, commit: Option<&str>) -> Result<()> {
+        let directory = self.directory(project_root)?;
+        if !directory.exists() {
+            let mut cmd = Command::new("git");
+            cmd.arg("clone")
+                .arg("--no-checkout")
+                .arg(&self.repo)
+                .arg(&directory);
+            return run_cmd(&mut cmd);
+        }
+        if commit.is_some_and(|c| self.rev_parse(project_root, c).is_ok()) {
+            return Ok(());
+        }
+        let mut cmd = self.git(project_root)?;
+        cmd.arg("fetch")
+            .arg("--tags")
+            .arg("--force")
             .arg(&self.repo)
-            .arg(self.directory(project_root)?)
-            .arg("--single-branch")
-            .arg("--branch")
-            .arg(&self.branch);
+            .arg("+refs/heads/*:refs/remotes/origin/*");
+        run_cmd(&mut cmd)
+    }
+
+    fn resolve(&self, project_root: &Utf8Path) -> Result<String> {
+        self.revisions()
+            .iter()
+            .find_map(|r| self.rev_parse(project_root, r).ok())
+            .ok_or_else(|| anyhow::anyhow!("Cannot find {} in {}", self.reference(), self.repo))
+    }
+
+    fn rev_parse(&self, project_root: &Utf8Path, revision: &str) -> Result<String> {
+        let mut cmd = self.git(project_root)?;
+        cmd.arg("rev-parse")
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(format!("{revision}^{{commit}}"));
+        Ok(run_cmd_for_output(&mut cmd)?.trim().to_string())
+    }
+
+    /// Checkout the commit, discarding any local changes.
+    fn checkout_commit(&self, project_root: &Utf8Path, commit: &str) -> Result<()> {
+        let mut cmd = self.git(project_root)?;
+        cmd.arg("checkout")
+            .arg("--force")
+            .arg("--detach")
+            .arg(commit);
         run_cmd(&mut cmd)
     }
+
+    fn git(&self, project_root: &Utf8Path) -> Result<Command> {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(self.directory(project_root)?);
+        Ok(cmd)
+    }
+}
+
+/// The `ubrn.lock` file, which records the commit checked out for each git repo.
+///
+/// This should be checked in, so that everyone building the project uses the same
+/// Rust code.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct LockFile {
+    #[serde(default)]
+    repos: BTreeMap<String, LockedRepo>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct LockedRepo {
+    reference: String,
+    commit: String,
+}
+
+impl LockFile {
+    fn path(project_root: &Utf8Path) -> Utf8PathBuf {
+        project_root.join("ubrn.lock")
+    }
+
+    fn read(project_root: &Utf8Path) -> Result<Self> {
+        let file = Self::path(project_root);
+        if file.exists() {
+            ubrn_common::read_from_file(file)
+        } else {
+            Ok(Default::default())
+        }
+    }
+
+    fn write(&self, project_root: &Utf8Path) -> Result<()> {
+        let file = Self::path(project_root);
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        if std::fs::read_to_string(&file).ok().as_deref() != Some(contents.as_str()) {
+            write_file(&file, &contents)?;
+        }
+        Ok(())
+    }
+
+    /// The locked commit for the repo, if the branch, tag or rev has not
+    /// changed since it was locked.
+    fn get(&self, repo: &GitRepoArgs) -> Option<String> {
+        self.repos
+            .get(&repo.repo)
+            .filter(|locked| locked.reference == repo.reference())
+            .map(|locked| locked.commit.clone())
+    }
+
+    fn insert(&mut self, repo: &GitRepoArgs, commit: String) {
+        let reference = repo.reference();
+        self.repos
+            .insert(repo.repo.clone(), LockedRepo { reference, commit });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(branch: &str, tag: Option<&str>, rev: Option<&str>) -> GitRepoArgs {
+        GitRepoArgs {
+            repo: "https://github.com/example/sync.git".into(),
+            branch: branch.into(),
+            tag: tag.map(str::to_owned),
+            rev: rev.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_reference() {
+        assert_eq!(repo("main", None, None).reference(), "branch main");
+        assert_eq!(repo("main", Some("v1.0"), None).reference(), "tag v1.0");
+        assert_eq!(repo("main", None, Some("abc123")).reference(), "rev abc123");
+        // A rev is more specific than a tag.
+        assert_eq!(
+            repo("main", Some("v1.0"), Some("abc123")).reference(),
+            "rev abc123"
+        );
+    }
+
+    #[test]
+    fn test_revisions() {
+        assert_eq!(
+            repo("develop", None, None).revisions(),
+            vec!["refs/remotes/origin/develop", "refs/tags/develop"]
+        );
+        assert_eq!(
+            repo("main", Some("v1.0"), None).revisions(),
+            vec!["refs/tags/v1.0"]
+        );
+        assert_eq!(
+            repo("main", None, Some("abc123")).revisions(),
+            vec!["abc123"]
+        );
+    }
+
+    #[test]
+    fn test_directory_name() {
+        for url in [
+            "https://github.com/example/sync.git",
+            "https://github.com/example/sync",
+            "git@github.com:example/sync.git",
+            "../sync",
+        ] {
+            assert_eq!(GitRepoArgs::directory_name(url), "sync", "{url}");
+        }
+    }
+
+    #[test]
+    fn test_lock_file_get() {
+        let mut lock = LockFile::default();
+        let main = repo("main", None, None);
+        assert_eq!(lock.get(&main), None);
+
+        lock.insert(&main, "1111".into());
+        assert_eq!(lock.get(&main).as_deref(), Some("1111"));
+
+        // The locked commit is ignored once the branch, tag or rev changes.
+        assert_eq!(lock.get(&repo("develop", None, None)), None);
+        assert_eq!(lock.get(&repo("main", Some("v1.0"), None)), None);
+        assert_eq!(lock.get(&repo("main", None, Some("1111"))), None);
+
+        // ... and is replaced when the new one is locked.
+        let tag = repo("main", Some("v1.0"), None);
+        lock.insert(&tag, "2222".into());
+        assert_eq!(lock.get(&tag).as_deref(), Some("2222"));
+        assert_eq!(lock.get(&main), None);
+    }
 }
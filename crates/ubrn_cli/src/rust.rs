@@ -10,7 +10,7 @@ use anyhow::{Error, Result};
 use camino::{Utf8Path, Utf8PathBuf};
 use ubrn_common::CrateMetadata;
 
-use crate::{repo::GitRepoArgs, workspace};
+use crate::{building::CargoSettings, repo::GitRepoArgs, workspace};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +22,9 @@ pub(crate) struct CrateConfig {
     pub(crate) manifest_path: String,
     #[serde(flatten)]
     pub(crate) src: RustSource,
+
+    #[serde(flatten)]
+    pub(crate) cargo: CargoSettings,
 }
 
 impl CrateConfig {
@@ -101,6 +104,7 @@ impl TryFrom<CrateMetadata> for CrateConfig {
             src: RustSource::OnDisk(OnDiskArgs {
                 src: ".".to_string(),
             }),
+            cargo: Default::default(),
         })
     }
 }
@@ -0,0 +1,353 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use ubrn_common::{dry_run, write_file};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SymbolsConfig {
+    #[serde(default = "SymbolsConfig::default_directory")]
+    pub(crate) directory: String,
+
+    #[serde(default = "SymbolsConfig::default_split")]
+    pub(crate) split: bool,
+}
+
+impl SymbolsConfig {
+    fn default_directory() -> String {
+        "build/symbols".to_string()
+    }
+
+    fn default_split() -> bool {
+        false
+    }
+}
+
+impl SymbolsConfig {
+    /// The directory for the symbols of one platform, e.g. `build/symbols/android`.
+    pub(crate) fn directory(&self, project_root: &Utf8Path, platform: &str) -> Utf8PathBuf {
+        project_root.join(&self.directory).join(platform)
+    }
+}
+
+/// A list of the debug symbols kept for each target, for upload to a crash reporter.
+///
+/// This is written as `symbols.json` at the top of the symbols directory for the platform.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SymbolsManifest {
+    library: String,
+    profile: String,
+    symbols: Vec<SymbolsEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolsEntry {
+    target: String,
+    /// The symbols file or dSYM bundle, relative to the manifest.
+    file: Utf8PathBuf,
+    /// The GNU build-id for ELF files, or the UUID of each architecture for Mach-O.
+    build_ids: Vec<String>,
+}
+
+impl SymbolsManifest {
+    pub(crate) fn new(library: &str, profile: &str) -> Self {
+        Self {
+            library: library.to_string(),
+            profile: profile.to_string(),
+            symbols: Default::default(),
+        }
+    }
+
+    /// Add the symbols for the target, reading the build-ids from the library.
+    pub(crate) fn add(
+        &mut self,
+        target: impl ToString,
+        dir: &Utf8Path,
+        file: &Utf8Path,
+        library: &Utf8Path,
+    ) -> Result<()> {
+        let build_ids = match std::fs::read(library) {
+            Ok(bytes) => elf_build_id(&bytes)
+                .map(|id| vec![id])
+                .unwrap_or_else(|| macho_uuids(&bytes)),
+            // In a dry run, the library may not have been built.
+            Err(_) if dry_run() => return Ok(()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {library}")),
+        };
+        if build_ids.is_empty() {
+            warn!("No build-id found in {library}");
+        }
+        let file = pathdiff::diff_utf8_paths(file, dir).unwrap_or_else(|| file.to_owned());
+        self.symbols.push(SymbolsEntry {
+            target: target.to_string(),
+            file,
+            build_ids,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn write(mut self, dir: &Utf8Path) -> Result<()> {
+        self.symbols.sort_by(|a, b| a.target.cmp(&b.target));
+        let file = dir.join("symbols.json");
+        let mut contents = serde_json::to_string_pretty(&self)?;
+        contents.push('\n');
+        write_file(&file, &contents)
+    }
+}
+
+const NT_GNU_BUILD_ID: u32 = 3;
+const SHT_NOTE: u32 = 7;
+
+/// Find the GNU build-id in a little-endian ELF file, as a hex string.
+///
+/// All the Android ABIs are little-endian, so big-endian files are not supported.
+fn elf_build_id(bytes: &[u8]) -> Option<String> {
+    if bytes.get(0..4)? != b"\x7fELF" || *bytes.get(5)? != 1 {
+        return None;
+    }
+    let is_64 = *bytes.get(4)? == 2;
+    let (shoff, shentsize, shnum) = if is_64 {
+        (
+            read_u64(bytes, 0x28)?,
+            read_u16(bytes, 0x3a)?,
+            read_u16(bytes, 0x3c)?,
+        )
+    } else {
+        (
+            read_u32(bytes, 0x20)? as u64,
+            read_u16(bytes, 0x2e)?,
+            read_u16(bytes, 0x30)?,
+        )
+    };
+    (0..shnum as u64).find_map(|i| {
+        let start = shoff.checked_add(i.checked_mul(shentsize as u64)?)?;
+        let header = bytes.get(usize::try_from(start).ok()?..)?;
+        if read_u32(header, 4)? != SHT_NOTE {
+            return None;
+        }
+        let (offset, size) = if is_64 {
+            (read_u64(header, 0x18)?, read_u64(header, 0x20)?)
+        } else {
+            (
+                read_u32(header, 0x10)? as u64,
+                read_u32(header, 0x14)? as u64,
+            )
+        };
+        let end = offset.checked_add(size)?;
+        let notes = bytes.get(usize::try_from(offset).ok()?..usize::try_from(end).ok()?)?;
+        find_build_id_note(notes)
+    })
+}
+
+fn find_build_id_note(mut notes: &[u8]) -> Option<String> {
+    while notes.len() >= 12 {
+        let namesz = read_u32(notes, 0)? as usize;
+        let descsz = read_u32(notes, 4)? as usize;
+        let kind = read_u32(notes, 8)?;
+        let desc_start = 12 + align4(namesz);
+        let name = notes.get(12..12 + namesz)?;
+        let desc = notes.get(desc_start..desc_start + descsz)?;
+        if kind == NT_GNU_BUILD_ID && name == b"GNU\0" {
+            return Some(hex(desc));
+        }
+        notes = notes.get(desc_start + align4(descsz)..)?;
+    }
+    None
+}
+
+const MH_MAGIC_64: u32 = 0xfeedfacf;
+const FAT_MAGIC: u32 = 0xcafebabe;
+const LC_UUID: u32 = 0x1b;
+
+/// Find the UUIDs of a Mach-O file, one for each architecture of a fat file.
+fn macho_uuids(bytes: &[u8]) -> Vec<String> {
+    if read_u32_be(bytes, 0) == Some(FAT_MAGIC) {
+        let count = read_u32_be(bytes, 4).unwrap_or_default() as usize;
+        (0..count)
+            .filter_map(|i| {
+                let arch = 8 + i * 20;
+                let offset = read_u32_be(bytes, arch + 8)? as usize;
+                let size = read_u32_be(bytes, arch + 12)? as usize;
+                macho_uuid(bytes.get(offset..offset.checked_add(size)?)?)
+            })
+            .collect()
+    } else {
+        macho_uuid(bytes).into_iter().collect()
+    }
+}
+
+fn macho_uuid(bytes: &[u8]) -> Option<String> {
+    if read_u32(bytes, 0)? != MH_MAGIC_64 {
+        return None;
+    }
+    let ncmds = read_u32(bytes, 16)?;
+    let mut offset = 32;
+    for _ in 0..ncmds {
+        let cmd = read_u32(bytes, offset)?;
+        let cmdsize = read_u32(bytes, offset + 4)? as usize;
+        if cmd == LC_UUID {
+            return bytes.get(offset + 8..offset + 24).map(hex);
+        }
+        // A load command is at least as big as its own header; anything
+        // smaller would never move past this one.
+        if cmdsize < 8 {
+            return None;
+        }
+        offset = offset.checked_add(cmdsize)?;
+    }
+    None
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset.checked_add(8)?)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_id_note(id: &[u8]) -> Vec<u8> {
+        let mut note = Vec::new();
+        note.extend(4u32.to_le_bytes());
+        note.extend((id.len() as u32).to_le_bytes());
+        note.extend(NT_GNU_BUILD_ID.to_le_bytes());
+        note.extend(b"GNU\0");
+        note.extend(id);
+        note
+    }
+
+    /// A 64 bit ELF file with a null section, and then a note section.
+    fn elf64(notes: &[u8]) -> Vec<u8> {
+        let shoff = 64 + notes.len();
+        let mut elf = vec![0; 64];
+        elf[0..4].copy_from_slice(b"\x7fELF");
+        elf[4] = 2;
+        elf[5] = 1;
+        elf[0x28..0x30].copy_from_slice(&(shoff as u64).to_le_bytes());
+        elf[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        elf[0x3c..0x3e].copy_from_slice(&2u16.to_le_bytes());
+        elf.extend(notes);
+        elf.extend([0; 64]);
+        let mut section = vec![0; 64];
+        section[4..8].copy_from_slice(&SHT_NOTE.to_le_bytes());
+        section[0x18..0x20].copy_from_slice(&64u64.to_le_bytes());
+        section[0x20..0x28].copy_from_slice(&(notes.len() as u64).to_le_bytes());
+        elf.extend(section);
+        elf
+    }
+
+    #[test]
+    fn test_elf_build_id() {
+        let mut notes = Vec::new();
+        // An unrelated note first, with a name that needs padding.
+        notes.extend(2u32.to_le_bytes());
+        notes.extend(4u32.to_le_bytes());
+        notes.extend(1u32.to_le_bytes());
+        notes.extend(b"X\0\0\0");
+        notes.extend([1, 2, 3, 4]);
+        notes.extend(build_id_note(&[0xde, 0xad, 0xbe, 0xef, 0x01]));
+
+        assert_eq!(elf_build_id(&elf64(&notes)), Some("deadbeef01".to_string()));
+        assert_eq!(elf_build_id(&elf64(&[])), None);
+        assert_eq!(elf_build_id(b"!<arch>\n"), None);
+    }
+
+    #[test]
+    fn test_elf_build_id_malformed() {
+        let notes = build_id_note(&[1, 2, 3, 4]);
+
+        // The section header table is past the end of the address space.
+        let mut elf = elf64(&notes);
+        elf[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(elf_build_id(&elf), None);
+
+        // The note section ends past the end of the address space.
+        let mut elf = elf64(&notes);
+        let section = elf.len() - 64;
+        elf[section + 0x18..section + 0x20].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(elf_build_id(&elf), None);
+        let mut elf = elf64(&notes);
+        elf[section + 0x20..section + 0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(elf_build_id(&elf), None);
+    }
+
+    #[test]
+    fn test_macho_uuids() {
+        let uuid: Vec<u8> = (0..16).collect();
+        let mut thin = vec![0; 32];
+        thin[0..4].copy_from_slice(&MH_MAGIC_64.to_le_bytes());
+        thin[16..20].copy_from_slice(&1u32.to_le_bytes());
+        thin.extend(LC_UUID.to_le_bytes());
+        thin.extend(24u32.to_le_bytes());
+        thin.extend(&uuid);
+        let expected = "000102030405060708090a0b0c0d0e0f".to_string();
+        assert_eq!(macho_uuids(&thin), vec![expected.clone()]);
+
+        let mut fat = Vec::new();
+        fat.extend(FAT_MAGIC.to_be_bytes());
+        fat.extend(1u32.to_be_bytes());
+        fat.extend([0; 8]);
+        fat.extend(28u32.to_be_bytes());
+        fat.extend((thin.len() as u32).to_be_bytes());
+        fat.extend([0; 4]);
+        fat.extend(&thin);
+        assert_eq!(macho_uuids(&fat), vec![expected]);
+    }
+
+    #[test]
+    fn test_macho_uuids_malformed() {
+        // A load command of size 0, with a huge number of commands.
+        let mut thin = vec![0; 32];
+        thin[0..4].copy_from_slice(&MH_MAGIC_64.to_le_bytes());
+        thin[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        thin.extend(0x19u32.to_le_bytes());
+        thin.extend(0u32.to_le_bytes());
+        assert!(macho_uuids(&thin).is_empty());
+
+        // A fat file with an architecture past the end of the address space.
+        let mut fat = Vec::new();
+        fat.extend(FAT_MAGIC.to_be_bytes());
+        fat.extend(1u32.to_be_bytes());
+        fat.extend([0; 8]);
+        fat.extend(u32::MAX.to_be_bytes());
+        fat.extend(u32::MAX.to_be_bytes());
+        fat.extend([0; 4]);
+        assert!(macho_uuids(&fat).is_empty());
+    }
+}
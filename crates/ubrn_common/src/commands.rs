@@ -4,11 +4,52 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 use anyhow::Result;
-use std::process::{Command, Stdio};
+use log::{debug, info, trace};
+use std::{
+    io::{BufRead, BufReader, Read},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    json_messages,
+    logging::{capture_output, quiet, CHILD_STDERR, CHILD_STDOUT},
+};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Print what would be done, instead of doing it.
+///
+/// Commands run with `run_cmd`, `run_cmd_quietly` and `run_cmd_with_prefix`, and the
+/// formatters run by `fmt::format_files`, are printed but not run, and no files or
+/// directories are written or removed. Commands which only read (`run_cmd_for_output`)
+/// are still run, so that the plan is accurate.
+pub fn set_dry_run(dry_run: bool) {
+    DRY_RUN.store(dry_run, Ordering::SeqCst);
+}
+
+pub fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::SeqCst)
+}
 
 pub fn run_cmd(cmd: &mut Command) -> Result<()> {
-    eprintln!("Running {:?}", *cmd);
+    if dry_run() {
+        info!("Would run {:?}", *cmd);
+        return Ok(());
+    }
+    info!("Running {:?}", *cmd);
     cmd.stdin(Stdio::inherit());
+    if capture_output() {
+        return run_captured(cmd, "");
+    }
+    if json_messages() {
+        // Keep stdout for the JSON messages.
+        cmd.stdout(std::io::stderr());
+    }
 
     let status = cmd.status()?;
 
@@ -21,15 +62,103 @@ pub fn run_cmd(cmd: &mut Command) -> Result<()> {
 
 /// Run the given command, and only output if there is an error.
 pub fn run_cmd_quietly(cmd: &mut Command) -> Result<()> {
+    if dry_run() {
+        info!("Would run {:?}", *cmd);
+        return Ok(());
+    }
+    debug!("Running {:?}", *cmd);
     cmd.stdin(Stdio::inherit());
     let output = cmd.output().expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
 
     if !output.status.success() {
-        eprintln!("Running {:?}", *cmd);
-        eprintln!("{}", String::from_utf8_lossy(&output.stdout));
-        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
-        anyhow::bail!("FAILED");
+        anyhow::bail!("Failed to run command {:?}:\n{stdout}\n{stderr}", *cmd);
+    }
+    for line in stdout.lines().chain(stderr.lines()) {
+        debug!("{line}");
     }
 
     Ok(())
 }
+
+/// Run the given command, returning its stdout.
+pub fn run_cmd_for_output(cmd: &mut Command) -> Result<String> {
+    trace!("Running {:?}", *cmd);
+    let output = cmd.stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to run command {:?}: {}",
+            *cmd,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Run the given command, prefixing each line of its output with `[prefix]`.
+///
+/// This is useful when more than one command is running at the same time,
+/// and their output is interleaved.
+pub fn run_cmd_with_prefix(cmd: &mut Command, prefix: &str) -> Result<()> {
+    if dry_run() {
+        info!("[{prefix}] Would run {:?}", *cmd);
+        return Ok(());
+    }
+    info!("[{prefix}] Running {:?}", *cmd);
+    cmd.stdin(Stdio::null());
+    run_captured(cmd, &format!("[{prefix}] "))
+}
+
+/// Run the given command, logging each line of its output, so that it goes to the log
+/// file as well as to the console.
+///
+/// If the console is quiet, the output is added to the error if the command fails.
+fn run_captured(cmd: &mut Command, prefix: &str) -> Result<()> {
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let output = Mutex::new(Vec::new());
+    thread::scope(|s| {
+        for (pipe, target) in [
+            (Box::new(stdout) as Box<dyn Read + Send>, CHILD_STDOUT),
+            (Box::new(stderr), CHILD_STDERR),
+        ] {
+            let output = &output;
+            s.spawn(move || {
+                for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                    info!(target: target, "{prefix}{line}");
+                    if quiet() {
+                        output
+                            .lock()
+                            .expect("Nothing panics while holding the lock")
+                            .push(line);
+                    }
+                }
+            });
+        }
+    });
+
+    let status = child.wait()?;
+    if !status.success() {
+        let output = output
+            .into_inner()
+            .expect("Nothing panics while holding the lock");
+        if output.is_empty() {
+            anyhow::bail!("Failed to run command");
+        }
+        anyhow::bail!("Failed to run command {:?}:\n{}", *cmd, output.join("\n"));
+    }
+
+    Ok(())
+}
+
+/// Tests which change or depend on the dry run flag hold this, so that they don't see
+/// each other's changes.
+#[cfg(test)]
+pub(crate) fn lock_dry_run() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
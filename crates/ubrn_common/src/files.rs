@@ -7,8 +7,11 @@ use std::fs;
 
 use anyhow::{bail, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
+use log::info;
 use serde::Deserialize;
 
+use crate::dry_run;
+
 /// Finds a file in the given directory.
 ///
 /// If None exists, then search in the parent directory, recursively until it is found.
@@ -43,6 +46,38 @@ pub fn find<P: AsRef<Utf8Path>>(directory: P, filename: &str) -> Option<Utf8Path
     Some(path)
 }
 
+/// List the files in the directory and its subdirectories, apart from those for which
+/// `skip` is true.
+pub fn list_files(dir: &Utf8Path, skip: &dyn Fn(&Utf8Path) -> bool) -> Result<Vec<Utf8PathBuf>> {
+    let mut files = Vec::new();
+    list_files_into(dir, skip, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn list_files_into(
+    dir: &Utf8Path,
+    skip: &dyn Fn(&Utf8Path) -> bool,
+    files: &mut Vec<Utf8PathBuf>,
+) -> Result<()> {
+    for entry in dir
+        .read_dir_utf8()
+        .with_context(|| format!("Failed to read {dir}"))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if skip(path) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            list_files_into(path, skip, files)?;
+        } else {
+            files.push(path.to_owned());
+        }
+    }
+    Ok(())
+}
+
 pub fn file_paths(pattern: &str) -> Result<Vec<std::ffi::OsString>, anyhow::Error> {
     let files = glob::glob(pattern)?;
     let files: Vec<_> = files
@@ -65,9 +100,26 @@ pub fn cd(dir: &Utf8Path) -> Result<()> {
     Ok(())
 }
 
+/// The canonical path, or if it doesn't exist, e.g. in a dry run, the path relative to
+/// the current directory.
+pub fn absolute<P: AsRef<Utf8Path>>(path: P) -> Result<Utf8PathBuf> {
+    let path = path.as_ref();
+    Ok(if path.exists() {
+        path.canonicalize_utf8()?
+    } else {
+        pwd()?.join(path)
+    })
+}
+
 pub fn rm_dir<P: AsRef<Utf8Path>>(dir: P) -> Result<()> {
-    if dir.as_ref().exists() {
-        fs::remove_dir_all(dir.as_ref())?;
+    let dir = dir.as_ref();
+    if dir.exists() {
+        if dry_run() {
+            info!("Would remove {dir}");
+        } else {
+            info!("rm -Rf {dir}");
+            fs::remove_dir_all(dir)?;
+        }
     }
     Ok(())
 }
@@ -80,12 +132,35 @@ pub fn mk_dir<P: AsRef<Utf8Path>>(dir: P) -> Result<()> {
         } else {
             bail!("{dir} is supposed to be a directory but is not")
         }
+    } else if dry_run() {
+        info!("Would create {dir}");
+        Ok(())
     } else {
         fs::create_dir_all(dir)?;
         Ok(())
     }
 }
 
+pub fn cp_file(from: &Utf8Path, to: &Utf8Path) -> Result<()> {
+    if dry_run() {
+        info!("Would copy {from} to {to}");
+    } else {
+        info!("cp {from} {to}");
+        fs::copy(from, to).with_context(|| format!("Failed to copy {from} to {to}"))?;
+    }
+    Ok(())
+}
+
+pub fn write_file(file: &Utf8Path, contents: &str) -> Result<()> {
+    if dry_run() {
+        info!("Would write {file}");
+    } else {
+        fs::write(file, contents).with_context(|| format!("Failed to write {file}"))?;
+        info!("Wrote {file}");
+    }
+    Ok(())
+}
+
 pub fn read_from_file<P, T>(file: P) -> Result<T>
 where
     P: AsRef<Utf8Path>,
@@ -3,12 +3,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
-use anyhow::Result;
-use camino::Utf8Path;
-use std::process::Command;
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use log::info;
+use std::{fs, process::Command};
 use which::which;
 
-use crate::{file_paths, resolve};
+use crate::{dry_run, file_paths, resolve, run_cmd_quietly, timed};
 
 pub fn clang_format<P: AsRef<Utf8Path>>(path: P, check_only: bool) -> Result<Option<Command>> {
     if which("clang-format").is_err() {
@@ -45,3 +46,117 @@ pub fn prettier<P: AsRef<Utf8Path>>(out_dir: P, check_only: bool) -> Result<Opti
         None
     })
 }
+
+/// Format the contents of many files with a single run of the formatter, before they
+/// are written, so they can be compared with what is already on disk.
+///
+/// The files are written to a scratch directory inside `dir`, so that the formatter finds
+/// the same configuration as it would for the files themselves, and read back once the
+/// formatter has run. The scratch directory is removed afterwards.
+///
+/// In a dry run, the formatter is not run, and the contents are left unformatted.
+pub fn format_files(
+    dir: &Utf8Path,
+    files: &mut [(Utf8PathBuf, String)],
+    formatter: impl FnOnce(&Utf8Path) -> Result<Option<Command>>,
+) -> Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let scratch = dir.join(format!(".ubrn-format-{}", std::process::id()));
+    if dry_run() {
+        if let Some(cmd) = formatter(dir)? {
+            info!("Would run {:?}", cmd);
+        }
+        return Ok(());
+    }
+    let result = format_in(&scratch, dir, files, formatter);
+    if scratch.exists() {
+        fs::remove_dir_all(&scratch).with_context(|| format!("Failed to remove {scratch}"))?;
+    }
+    result
+}
+
+fn format_in(
+    scratch: &Utf8Path,
+    dir: &Utf8Path,
+    files: &mut [(Utf8PathBuf, String)],
+    formatter: impl FnOnce(&Utf8Path) -> Result<Option<Command>>,
+) -> Result<()> {
+    let scratch_paths = files
+        .iter()
+        .map(|(path, contents)| {
+            let relative = path
+                .strip_prefix(dir)
+                .with_context(|| format!("{path} is not in {dir}"))?;
+            let scratch_path = scratch.join(relative);
+            if let Some(parent) = scratch_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&scratch_path, contents)
+                .with_context(|| format!("Failed to write {scratch_path}"))?;
+            Ok(scratch_path)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let Some(mut cmd) = formatter(scratch)? else {
+        return Ok(());
+    };
+    timed(
+        &format!("formatting {} files in {dir}", files.len()),
+        || run_cmd_quietly(&mut cmd),
+    )?;
+    for ((_, contents), scratch_path) in files.iter_mut().zip(scratch_paths) {
+        *contents = fs::read_to_string(&scratch_path)
+            .with_context(|| format!("Failed to read {scratch_path}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lock_dry_run, set_dry_run};
+
+    /// A formatter which appends the name of each file to its contents.
+    fn formatter(dir: &Utf8Path) -> Result<Option<Command>> {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(r#"find . -type f | while read f; do echo "$f" >> "$f"; done"#)
+            .current_dir(dir);
+        Ok(Some(cmd))
+    }
+
+    #[test]
+    fn test_format_files() -> Result<()> {
+        let _lock = lock_dry_run();
+        let dir = std::env::temp_dir().join(format!("ubrn-format-files-{}", std::process::id()));
+        let dir = Utf8PathBuf::try_from(dir)?;
+        fs::create_dir_all(&dir)?;
+        let mut files = vec![
+            (dir.join("module.ts"), "one\n".to_string()),
+            (dir.join("nested/module.ts"), "two\n".to_string()),
+        ];
+
+        set_dry_run(true);
+        let result = format_files(&dir, &mut files, formatter);
+        set_dry_run(false);
+        result?;
+        assert_eq!(files[0].1, "one\n");
+
+        format_files(&dir, &mut files, formatter)?;
+        assert_eq!(files[0].1, "one\n./module.ts\n");
+        assert_eq!(files[1].1, "two\n./nested/module.ts\n");
+        // Only the scratch directory was written to, and it has been removed.
+        assert_eq!(fs::read_dir(&dir)?.count(), 0);
+
+        let mut outside = vec![(Utf8PathBuf::from("/elsewhere/module.ts"), String::new())];
+        let error = format_files(&dir, &mut outside, formatter).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("/elsewhere/module.ts is not in {dir}")
+        );
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::fmt::Display;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use log::info;
+use serde::Serialize;
+
+use crate::{dry_run, emit, mk_dir, Message};
+
+/// What happened, or would happen, to a generated file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteStatus {
+    Created,
+    Updated,
+    Unchanged,
+    Excluded,
+}
+
+impl WriteStatus {
+    fn is_out_of_date(&self) -> bool {
+        matches!(self, Self::Created | Self::Updated)
+    }
+}
+
+impl Display for WriteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Unchanged => "unchanged",
+            Self::Excluded => "excluded",
+        })
+    }
+}
+
+/// Writes generated files, but only if their contents have changed.
+///
+/// Leaving unchanged files alone keeps their modification times, so Gradle, CocoaPods and
+/// Metro don't rebuild everything on every run.
+///
+/// In check mode, nothing is written: `finish` fails if any file is out of date.
+#[derive(Debug, Default)]
+pub struct GeneratedFiles {
+    check_only: bool,
+    files: Vec<(Utf8PathBuf, WriteStatus)>,
+}
+
+impl GeneratedFiles {
+    pub fn new(check_only: bool) -> Self {
+        Self {
+            check_only,
+            ..Default::default()
+        }
+    }
+
+    pub fn check_only(&self) -> bool {
+        self.check_only
+    }
+
+    /// Write the contents to the path, unless the file already has exactly those contents.
+    pub fn write(&mut self, path: &Utf8Path, contents: &str) -> Result<WriteStatus> {
+        let status = if !path.exists() {
+            WriteStatus::Created
+        } else if std::fs::read_to_string(path).ok().as_deref() == Some(contents) {
+            WriteStatus::Unchanged
+        } else {
+            WriteStatus::Updated
+        };
+        if status.is_out_of_date() && !self.check_only && !dry_run() {
+            if let Some(parent) = path.parent() {
+                mk_dir(parent)?;
+            }
+            std::fs::write(path, contents).with_context(|| format!("Failed to write {path}"))?;
+        }
+        self.push(path, status);
+        Ok(status)
+    }
+
+    /// Record that the file was not written because the configuration excludes it.
+    pub fn exclude(&mut self, path: &Utf8Path) {
+        self.push(path, WriteStatus::Excluded);
+    }
+
+    fn push(&mut self, path: &Utf8Path, status: WriteStatus) {
+        emit(Message::GeneratedFile {
+            path: path.to_owned(),
+            status,
+        });
+        self.files.push((path.to_owned(), status));
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.files.append(&mut other.files);
+    }
+
+    fn count(&self, status: WriteStatus) -> usize {
+        self.files.iter().filter(|(_, s)| *s == status).count()
+    }
+
+    fn out_of_date(&self) -> impl Iterator<Item = &(Utf8PathBuf, WriteStatus)> {
+        self.files.iter().filter(|(_, s)| s.is_out_of_date())
+    }
+
+    /// Print a summary of the generated files and, in check mode, fail if any
+    /// of them are out of date.
+    ///
+    /// In a dry run, every file is listed, including those which are unchanged or excluded.
+    pub fn finish(&self) -> Result<()> {
+        let verb = if self.check_only || dry_run() {
+            "would be "
+        } else {
+            ""
+        };
+        for (path, status) in &self.files {
+            match status {
+                _ if status.is_out_of_date() => info!("  {verb}{status}: {path}"),
+                WriteStatus::Excluded if dry_run() => {
+                    info!("  excluded by noOverwrite: {path}")
+                }
+                _ if dry_run() => info!("  {status}: {path}"),
+                _ => (),
+            }
+        }
+        info!(
+            "Generated files: {} {verb}created, {} {verb}updated, {} unchanged, {} excluded",
+            self.count(WriteStatus::Created),
+            self.count(WriteStatus::Updated),
+            self.count(WriteStatus::Unchanged),
+            self.count(WriteStatus::Excluded),
+        );
+        let out_of_date = self.out_of_date().count();
+        if self.check_only && out_of_date > 0 {
+            anyhow::bail!(
+                "{out_of_date} generated file(s) are out of date. Run again without --check to update them"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lock_dry_run, set_dry_run};
+
+    fn temp_dir(name: &str) -> Utf8PathBuf {
+        let dir = std::env::temp_dir().join(format!("ubrn-{name}-{}", std::process::id()));
+        let dir = Utf8PathBuf::try_from(dir).unwrap();
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_write_statuses() -> Result<()> {
+        let _lock = lock_dry_run();
+        let dir = temp_dir("generated-files");
+        let path = dir.join("src/generated/module.ts");
+        let mut files = GeneratedFiles::new(false);
+
+        assert_eq!(files.write(&path, "one")?, WriteStatus::Created);
+        assert_eq!(std::fs::read_to_string(&path)?, "one");
+
+        let modified = path.metadata()?.modified()?;
+        assert_eq!(files.write(&path, "one")?, WriteStatus::Unchanged);
+        assert_eq!(path.metadata()?.modified()?, modified);
+
+        assert_eq!(files.write(&path, "two")?, WriteStatus::Updated);
+        assert_eq!(std::fs::read_to_string(&path)?, "two");
+
+        let excluded = dir.join("module.podspec");
+        files.exclude(&excluded);
+        assert!(!excluded.exists());
+
+        assert_eq!(files.count(WriteStatus::Created), 1);
+        assert_eq!(files.count(WriteStatus::Unchanged), 1);
+        assert_eq!(files.count(WriteStatus::Updated), 1);
+        assert_eq!(files.count(WriteStatus::Excluded), 1);
+        files.finish()?;
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_only() -> Result<()> {
+        let _lock = lock_dry_run();
+        let dir = temp_dir("generated-files-check");
+        let unchanged = dir.join("unchanged.ts");
+        let changed = dir.join("changed.ts");
+        let missing = dir.join("missing.ts");
+        GeneratedFiles::new(false).write(&unchanged, "same")?;
+        GeneratedFiles::new(false).write(&changed, "old")?;
+
+        let mut files = GeneratedFiles::new(true);
+        assert_eq!(files.write(&unchanged, "same")?, WriteStatus::Unchanged);
+        files.exclude(&dir.join("excluded.ts"));
+        files.finish()?;
+
+        // Nothing is written, but the files which are out of date fail the check.
+        assert_eq!(files.write(&changed, "new")?, WriteStatus::Updated);
+        assert_eq!(files.write(&missing, "new")?, WriteStatus::Created);
+        assert_eq!(std::fs::read_to_string(&changed)?, "old");
+        assert!(!missing.exists());
+        let error = files.finish().unwrap_err().to_string();
+        assert!(
+            error.starts_with("2 generated file(s) are out of date"),
+            "{error}"
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run() -> Result<()> {
+        let _lock = lock_dry_run();
+        let dir = temp_dir("generated-files-dry-run");
+        let path = dir.join("module.ts");
+        let mut files = GeneratedFiles::new(false);
+
+        set_dry_run(true);
+        let status = files.write(&path, "contents");
+        set_dry_run(false);
+
+        assert_eq!(status?, WriteStatus::Created);
+        assert!(!dir.exists());
+        Ok(())
+    }
+}
@@ -6,10 +6,18 @@
 mod commands;
 mod files;
 pub mod fmt;
+mod generated;
+mod logging;
+mod messages;
 mod rust_crate;
 mod serde;
+mod watch;
 
 pub use commands::*;
 pub use files::*;
+pub use generated::*;
+pub use logging::{init_logging, timed};
+pub use messages::*;
 pub use rust_crate::*;
 pub use serde::*;
+pub use watch::*;
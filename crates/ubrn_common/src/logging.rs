@@ -0,0 +1,140 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{
+    fs::File,
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Instant, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+use log::{debug, Level, LevelFilter, Log, Metadata, Record};
+
+use crate::json_messages;
+
+/// The log target for lines of output from a command's stdout.
+pub(crate) const CHILD_STDOUT: &str = "ubrn::stdout";
+/// The log target for lines of output from a command's stderr.
+pub(crate) const CHILD_STDERR: &str = "ubrn::stderr";
+
+static CONSOLE_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+static HAS_LOG_FILE: AtomicBool = AtomicBool::new(false);
+static START: OnceLock<Instant> = OnceLock::new();
+
+struct Logger {
+    console: LevelFilter,
+    file: Option<Mutex<File>>,
+}
+
+/// Send everything logged to the console, and optionally to a file.
+///
+/// On the console:
+/// - `Info` is the progress of the command, and the output of the commands it runs. This
+///   goes to stdout, unless stdout is for JSON messages.
+/// - `Warn` and `Error` go to stderr.
+/// - `Debug`, e.g. how long each phase took, and `Trace` go to stderr.
+///
+/// When `Debug` is shown, every line is prefixed with the time since the start.
+///
+/// The file gets every level, with a timestamp, whatever is shown on the console. To do
+/// that, the output of commands is captured line by line, instead of going straight to
+/// the terminal.
+pub fn init_logging(console: LevelFilter, log_file: Option<&Utf8Path>) -> Result<()> {
+    START.get_or_init(Instant::now);
+    let file = match log_file {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("Failed to create {path}"))?;
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+    CONSOLE_LEVEL.store(console as usize, Ordering::SeqCst);
+    HAS_LOG_FILE.store(file.is_some(), Ordering::SeqCst);
+    log::set_max_level(if file.is_some() {
+        LevelFilter::Trace
+    } else {
+        console
+    });
+    log::set_boxed_logger(Box::new(Logger { console, file }))?;
+    Ok(())
+}
+
+/// Is the progress of the command hidden?
+pub(crate) fn quiet() -> bool {
+    console_level() < LevelFilter::Info
+}
+
+/// Should the output of commands be captured, instead of going straight to the terminal?
+pub(crate) fn capture_output() -> bool {
+    quiet() || HAS_LOG_FILE.load(Ordering::SeqCst)
+}
+
+fn console_level() -> LevelFilter {
+    match CONSOLE_LEVEL.load(Ordering::SeqCst) {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Run one phase of the build, e.g. cargo or bindgen, logging how long it took.
+pub fn timed<T>(phase: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    debug!("{phase}: started");
+    let result = f();
+    debug!("{phase}: finished in {:.2?}", start.elapsed());
+    result
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.console || self.file.is_some()
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(file) = &self.file {
+            let now = humantime::format_rfc3339_millis(SystemTime::now());
+            let mut file = file.lock().expect("Nothing panics while holding the lock");
+            // There's nowhere to report a failure to write the log.
+            let _ = writeln!(file, "{now} {:<5} {}", record.level(), record.args());
+        }
+        if record.level() > self.console {
+            return;
+        }
+        let message = record.args();
+        let elapsed = if self.console >= LevelFilter::Debug {
+            let elapsed = START.get_or_init(Instant::now).elapsed();
+            format!("[{:>8.3}s] ", elapsed.as_secs_f64())
+        } else {
+            String::new()
+        };
+        match record.level() {
+            Level::Error => eprintln!("{elapsed}Error: {message}"),
+            Level::Warn => eprintln!("{elapsed}Warning: {message}"),
+            Level::Info if record.target() == CHILD_STDERR || json_messages() => {
+                eprintln!("{elapsed}{message}")
+            }
+            Level::Info => println!("{elapsed}{message}"),
+            Level::Debug | Level::Trace => eprintln!("{elapsed}{message}"),
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file
+                .lock()
+                .expect("Nothing panics while holding the lock")
+                .flush();
+        }
+    }
+}
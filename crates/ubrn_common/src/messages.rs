@@ -0,0 +1,232 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+
+use camino::Utf8PathBuf;
+use serde::Serialize;
+
+use crate::WriteStatus;
+
+static JSON_MESSAGES: AtomicBool = AtomicBool::new(false);
+static EMITTED: Mutex<Vec<Message>> = Mutex::new(Vec::new());
+
+/// Write machine-readable messages to stdout, one JSON object per line.
+///
+/// Human-readable progress, and the output of any commands run, goes to stderr instead,
+/// so that stdout only contains JSON.
+pub fn set_json_messages(json: bool) {
+    JSON_MESSAGES.store(json, Ordering::SeqCst);
+}
+
+pub fn json_messages() -> bool {
+    JSON_MESSAGES.load(Ordering::SeqCst)
+}
+
+/// Something which was built, copied or written.
+///
+/// Each is serialized with a `reason` field, naming the kind of message, e.g.
+/// `{"reason":"target-started","libraryName":"my_crate","target":"arm64-v8a"}`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message {
+    #[serde(rename_all = "camelCase")]
+    TargetStarted {
+        library_name: String,
+        target: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TargetFinished {
+        library_name: String,
+        target: String,
+        /// The library which was built, if the build succeeded.
+        path: Option<Utf8PathBuf>,
+        duration: f64,
+        /// True if cargo wasn't run, because an existing library was used.
+        fresh: bool,
+        error: Option<String>,
+    },
+    Copied {
+        from: Utf8PathBuf,
+        to: Utf8PathBuf,
+    },
+    Lipo {
+        inputs: Vec<Utf8PathBuf>,
+        output: Utf8PathBuf,
+    },
+    Xcframework {
+        path: Utf8PathBuf,
+        libraries: Vec<Utf8PathBuf>,
+    },
+    GeneratedFile {
+        path: Utf8PathBuf,
+        status: WriteStatus,
+    },
+    Aar {
+        path: Utf8PathBuf,
+    },
+    BuildFinished(Summary),
+}
+
+/// The last message of a build, collecting together everything which was produced.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    success: bool,
+    error: Option<String>,
+    duration: f64,
+    libraries: Vec<Library>,
+    copies: Vec<Copy>,
+    lipo_outputs: Vec<Utf8PathBuf>,
+    xcframeworks: Vec<Utf8PathBuf>,
+    generated_files: Vec<GeneratedFile>,
+    aars: Vec<Utf8PathBuf>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Library {
+    library_name: String,
+    target: String,
+    path: Utf8PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Copy {
+    from: Utf8PathBuf,
+    to: Utf8PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct GeneratedFile {
+    path: Utf8PathBuf,
+    status: WriteStatus,
+}
+
+impl Summary {
+    fn new(messages: Vec<Message>, duration: Duration, error: Option<String>) -> Self {
+        let mut summary = Self {
+            success: error.is_none(),
+            error,
+            duration: duration.as_secs_f64(),
+            ..Default::default()
+        };
+        for message in messages {
+            match message {
+                Message::TargetFinished {
+                    library_name,
+                    target,
+                    path: Some(path),
+                    ..
+                } => summary.libraries.push(Library {
+                    library_name,
+                    target,
+                    path,
+                }),
+                Message::Copied { from, to } => summary.copies.push(Copy { from, to }),
+                Message::Lipo { output, .. } => summary.lipo_outputs.push(output),
+                Message::Xcframework { path, .. } => summary.xcframeworks.push(path),
+                Message::GeneratedFile { path, status } => {
+                    summary.generated_files.push(GeneratedFile { path, status })
+                }
+                Message::Aar { path } => summary.aars.push(path),
+                _ => (),
+            }
+        }
+        summary
+    }
+}
+
+/// Print the message as a line of JSON, if JSON messages are on, and keep it for the summary.
+pub fn emit(message: Message) {
+    if !json_messages() {
+        return;
+    }
+    print(&message);
+    emitted().push(message);
+}
+
+/// Emit the summary of everything emitted since the last summary.
+pub fn emit_summary(duration: Duration, error: Option<String>) {
+    if !json_messages() {
+        return;
+    }
+    let messages = std::mem::take(&mut *emitted());
+    print(&Message::BuildFinished(Summary::new(
+        messages, duration, error,
+    )));
+}
+
+fn emitted() -> MutexGuard<'static, Vec<Message>> {
+    EMITTED
+        .lock()
+        .expect("Nothing panics while holding the lock")
+}
+
+fn print(message: &Message) {
+    let json = serde_json::to_string(message).expect("Messages can always be serialized");
+    println!("{json}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let messages = vec![
+            Message::TargetStarted {
+                library_name: "my_crate".into(),
+                target: "arm64-v8a".into(),
+            },
+            Message::TargetFinished {
+                library_name: "my_crate".into(),
+                target: "arm64-v8a".into(),
+                path: Some("target/libmy_crate.a".into()),
+                duration: 1.5,
+                fresh: false,
+                error: None,
+            },
+            Message::Copied {
+                from: "target/libmy_crate.a".into(),
+                to: "android/src/main/jniLibs/arm64-v8a/libmy_crate.a".into(),
+            },
+            Message::GeneratedFile {
+                path: "src/index.ts".into(),
+                status: WriteStatus::Unchanged,
+            },
+        ];
+        let summary = Message::BuildFinished(Summary::new(messages, Duration::from_secs(2), None));
+        let json = serde_json::to_value(summary).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "reason": "build-finished",
+                "success": true,
+                "error": null,
+                "duration": 2.0,
+                "libraries": [
+                    { "libraryName": "my_crate", "target": "arm64-v8a", "path": "target/libmy_crate.a" },
+                ],
+                "copies": [
+                    {
+                        "from": "target/libmy_crate.a",
+                        "to": "android/src/main/jniLibs/arm64-v8a/libmy_crate.a",
+                    },
+                ],
+                "lipoOutputs": [],
+                "xcframeworks": [],
+                "generatedFiles": [{ "path": "src/index.ts", "status": "unchanged" }],
+                "aars": [],
+            })
+        );
+    }
+}
@@ -3,13 +3,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
-use std::process::Command;
+use std::{
+    collections::{BTreeSet, HashSet},
+    process::Command,
+};
 
 use anyhow::Result;
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_metadata::{Metadata, MetadataCommand};
+use serde::Deserialize;
 
-use crate::run_cmd_quietly;
+use crate::{dry_run, run_cmd_quietly};
 
 #[derive(Debug, Clone)]
 pub struct CrateMetadata {
@@ -17,19 +21,44 @@ pub struct CrateMetadata {
     pub(crate) crate_dir: Utf8PathBuf,
     pub(crate) target_dir: Utf8PathBuf,
     pub(crate) library_name: String,
+    pub(crate) library_type: LibraryType,
+    pub(crate) workspace_root: Utf8PathBuf,
+    pub(crate) source_dirs: Vec<Utf8PathBuf>,
+    pub(crate) version: String,
 }
 
-impl CrateMetadata {
-    pub fn profile<'a>(release: bool) -> &'a str {
-        if release {
-            "release"
-        } else {
-            "debug"
+/// How the Rust library is linked into the app: as a `staticlib`, or as a
+/// `cdylib` which can be shared with other native code.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LibraryType {
+    #[default]
+    Static,
+    Shared,
+}
+
+impl LibraryType {
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Self::Shared)
+    }
+
+    /// The `crate-type` in the `[lib]` section of `Cargo.toml` which produces this library.
+    pub fn crate_type(&self) -> &'static str {
+        match self {
+            Self::Static => "staticlib",
+            Self::Shared => "cdylib",
         }
     }
+}
 
+impl CrateMetadata {
+    /// The path of the library built with the given cargo profile.
+    ///
+    /// Cargo puts the `dev` and `test` profiles into `debug`, and `bench` into `release`;
+    /// every other profile, including custom ones, gets a directory of its own name.
     pub fn library_path(&self, target: Option<&str>, profile: &str) -> Utf8PathBuf {
         let library_name = self.library_file(target);
+        let profile = profile_dir(profile);
         match target {
             Some(t) => self.target_dir.join(t).join(profile).join(library_name),
             None => self.target_dir.join(profile).join(library_name),
@@ -37,14 +66,19 @@ impl CrateMetadata {
     }
 
     pub fn library_path_exists(&self, path: &Utf8Path) -> Result<()> {
-        if !path.exists() {
-            anyhow::bail!("Library doesn't exist. This may be because `staticlib` is not in the `crate-type` list in the [lib] entry of Cargo.toml: {}", self.manifest_path());
+        // In a dry run, cargo wasn't run, so the library may not have been built yet.
+        if !path.exists() && !dry_run() {
+            anyhow::bail!(
+                "Library doesn't exist. This may be because `{}` is not in the `crate-type` list in the [lib] entry of Cargo.toml: {}",
+                self.library_type.crate_type(),
+                self.manifest_path()
+            );
         }
         Ok(())
     }
 
     pub fn library_file(&self, target: Option<&str>) -> String {
-        let ext = so_extension(target);
+        let ext = so_extension(target, self.library_type);
         format!("lib{}.{ext}", &self.library_name)
     }
 
@@ -52,6 +86,26 @@ impl CrateMetadata {
         &self.target_dir
     }
 
+    /// A copy of this metadata, but with cargo building into a different target directory.
+    pub fn with_target_dir(&self, target_dir: Utf8PathBuf) -> Self {
+        Self {
+            target_dir,
+            ..self.clone()
+        }
+    }
+
+    /// A copy of this metadata, but for a library built as the given type.
+    pub fn with_library_type(&self, library_type: LibraryType) -> Self {
+        Self {
+            library_type,
+            ..self.clone()
+        }
+    }
+
+    pub fn library_type(&self) -> LibraryType {
+        self.library_type
+    }
+
     pub fn crate_dir(&self) -> &Utf8Path {
         &self.crate_dir
     }
@@ -70,6 +124,22 @@ impl CrateMetadata {
         &self.manifest_path
     }
 
+    /// The version of the crate, from its `Cargo.toml`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The `Cargo.lock` of the crate's workspace.
+    pub fn lock_file(&self) -> Utf8PathBuf {
+        self.workspace_root.join("Cargo.lock")
+    }
+
+    /// The directories of the crate, and of each of the local crates which it depends on,
+    /// i.e. those which are not from a registry or a git repository.
+    pub fn source_dirs(&self) -> &[Utf8PathBuf] {
+        &self.source_dirs
+    }
+
     pub fn cargo_clean(&self) -> Result<()> {
         let mut cmd = Command::new("cargo");
         run_cmd_quietly(cmd.arg("clean").current_dir(&self.crate_dir))?;
@@ -77,23 +147,36 @@ impl CrateMetadata {
     }
 }
 
-pub fn so_extension<'a>(target: Option<&str>) -> &'a str {
+fn profile_dir(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        p => p,
+    }
+}
+
+pub fn so_extension<'a>(target: Option<&str>, library_type: LibraryType) -> &'a str {
     match target {
-        Some(t) => so_extension_from_target(t),
+        Some(t) => so_extension_from_target(t, library_type),
         _ => so_extension_from_cfg(),
     }
 }
 
-fn so_extension_from_target<'a>(target: &str) -> &'a str {
+fn so_extension_from_target<'a>(target: &str, library_type: LibraryType) -> &'a str {
     if target.contains("windows") {
         "dll"
     } else if target.contains("darwin") {
         "dylib"
     } else if target.contains("ios") {
-        "a"
+        match library_type {
+            LibraryType::Static => "a",
+            LibraryType::Shared => "dylib",
+        }
     } else if target.contains("android") {
-        // We're using staticlib files here. cargo ndk use .so files.
-        "a"
+        match library_type {
+            LibraryType::Static => "a",
+            LibraryType::Shared => "so",
+        }
     } else {
         unimplemented!("Building targeting only on android and ios supported right now")
     }
@@ -135,14 +218,25 @@ impl TryFrom<Utf8PathBuf> for CrateMetadata {
             .manifest_path(&manifest_path)
             .exec()?;
 
-        let library_name = guess_library_name(&metadata, &manifest_path.canonicalize_utf8()?);
-        let target_dir = metadata.target_directory;
+        let canonical_manifest_path = manifest_path.canonicalize_utf8()?;
+        let library_name = guess_library_name(&metadata, &canonical_manifest_path);
+        let source_dirs = find_source_dirs(&metadata, &canonical_manifest_path);
+        let version = metadata
+            .packages
+            .iter()
+            .find(|package| package.manifest_path == canonical_manifest_path)
+            .map(|package| package.version.to_string())
+            .unwrap_or_default();
 
         Ok(Self {
             manifest_path,
             library_name,
-            target_dir,
+            target_dir: metadata.target_directory,
             crate_dir,
+            library_type: Default::default(),
+            workspace_root: metadata.workspace_root,
+            source_dirs,
+            version,
         })
     }
 }
@@ -180,3 +274,90 @@ fn find_package_name(metadata: &Metadata, manifest_path: &Utf8Path) -> Option<St
         .find(|package| package.manifest_path == *manifest_path)
         .map(|package| package.name.clone())
 }
+
+fn find_source_dirs(metadata: &Metadata, manifest_path: &Utf8Path) -> Vec<Utf8PathBuf> {
+    let Some(root) = metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path == *manifest_path)
+    else {
+        return Vec::new();
+    };
+    let Some(resolve) = &metadata.resolve else {
+        return vec![manifest_dir(&root.manifest_path)];
+    };
+
+    // Walk the dependency graph from the crate, keeping the local packages.
+    let mut seen = HashSet::from([&root.id]);
+    let mut queue = vec![&root.id];
+    let mut dirs = BTreeSet::new();
+    while let Some(id) = queue.pop() {
+        let Some(package) = metadata.packages.iter().find(|p| p.id == *id) else {
+            continue;
+        };
+        if package.source.is_none() {
+            dirs.insert(manifest_dir(&package.manifest_path));
+        }
+        let node = resolve.nodes.iter().find(|n| n.id == *id);
+        for dep in node.into_iter().flat_map(|n| &n.dependencies) {
+            if seen.insert(dep) {
+                queue.push(dep);
+            }
+        }
+    }
+    dirs.into_iter().collect()
+}
+
+fn manifest_dir(manifest_path: &Utf8Path) -> Utf8PathBuf {
+    manifest_path
+        .parent()
+        .expect("A valid parent for the crate manifest")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> CrateMetadata {
+        CrateMetadata {
+            manifest_path: "/project/rust/Cargo.toml".into(),
+            crate_dir: "/project/rust".into(),
+            target_dir: "/project/target".into(),
+            library_name: "my_crate".into(),
+            library_type: LibraryType::Shared,
+            workspace_root: "/project/rust".into(),
+            source_dirs: vec![],
+            version: "0.1.0".into(),
+        }
+    }
+
+    #[test]
+    fn test_profile_dir() {
+        assert_eq!(profile_dir("dev"), "debug");
+        assert_eq!(profile_dir("test"), "debug");
+        assert_eq!(profile_dir("release"), "release");
+        assert_eq!(profile_dir("bench"), "release");
+        assert_eq!(profile_dir("release-small"), "release-small");
+        assert_eq!(profile_dir("dev-fast"), "dev-fast");
+    }
+
+    #[test]
+    fn test_library_path_with_custom_profile() {
+        let metadata = metadata();
+        let android = Some("aarch64-linux-android");
+        assert_eq!(
+            metadata.library_path(android, "dev"),
+            "/project/target/aarch64-linux-android/debug/libmy_crate.so"
+        );
+        assert_eq!(
+            metadata.library_path(android, "release-small"),
+            "/project/target/aarch64-linux-android/release-small/libmy_crate.so"
+        );
+        let ios = metadata.with_library_type(LibraryType::Static);
+        assert_eq!(
+            ios.library_path(Some("aarch64-apple-ios"), "bench"),
+            "/project/target/aarch64-apple-ios/release/libmy_crate.a"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{collections::BTreeMap, fs, thread, time::Duration, time::SystemTime};
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use log::{error, info};
+
+/// Directories which never contain source files worth watching.
+const IGNORED_DIRS: &[&str] = &["target", "node_modules", "build"];
+
+/// Watches files and directories for changes by polling their modification times.
+///
+/// Polling is cheap enough for the size of a crate, and works the same on every
+/// platform, including inside containers and on network drives.
+#[derive(Debug)]
+pub struct Watcher {
+    paths: Vec<Utf8PathBuf>,
+    ignored: Vec<Utf8PathBuf>,
+    interval: Duration,
+    debounce: Duration,
+}
+
+type Snapshot = BTreeMap<Utf8PathBuf, Option<SystemTime>>;
+
+impl Watcher {
+    pub fn new(paths: Vec<Utf8PathBuf>) -> Self {
+        Self {
+            paths,
+            ignored: Default::default(),
+            interval: Duration::from_millis(500),
+            debounce: Duration::from_millis(300),
+        }
+    }
+
+    /// Do not watch anything in this directory, e.g. a cargo target directory
+    /// or a directory that the watched command writes into.
+    pub fn ignore(mut self, path: &Utf8Path) -> Self {
+        self.ignored.push(path.to_owned());
+        self
+    }
+
+    /// Run the callback, and then run it again every time a watched file changes.
+    ///
+    /// Errors from the callback are printed, but do not stop the watcher. This only
+    /// returns if the watched files cannot be read.
+    pub fn run<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut() -> Result<()>,
+    {
+        let mut snapshot = self.snapshot();
+        loop {
+            if let Err(e) = callback() {
+                error!("{e:?}");
+            }
+            info!(
+                "Watching {} files for changes. Press Ctrl-C to stop.",
+                snapshot.len()
+            );
+            snapshot = self.wait_for_change(snapshot);
+        }
+    }
+
+    /// Block until something has changed, and then until nothing has changed for
+    /// the debounce period, so that a burst of saves causes one rebuild.
+    fn wait_for_change(&self, before: Snapshot) -> Snapshot {
+        let mut current = before.clone();
+        while current == before {
+            thread::sleep(self.interval);
+            current = self.snapshot();
+        }
+        for path in changes(&before, &current) {
+            info!("Changed: {path}");
+        }
+        loop {
+            thread::sleep(self.debounce);
+            let next = self.snapshot();
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        for path in &self.paths {
+            if path.is_dir() {
+                self.walk(path, &mut snapshot);
+            } else {
+                snapshot.insert(path.clone(), modified(path));
+            }
+        }
+        snapshot
+    }
+
+    fn walk(&self, dir: &Utf8Path, snapshot: &mut Snapshot) {
+        let Ok(entries) = dir.read_dir_utf8() else {
+            return;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name();
+            if name.starts_with('.') || self.ignored.iter().any(|i| path.starts_with(i)) {
+                continue;
+            }
+            if path.is_dir() {
+                if !IGNORED_DIRS.contains(&name) {
+                    self.walk(path, snapshot);
+                }
+            } else {
+                snapshot.insert(path.to_owned(), modified(path));
+            }
+        }
+    }
+}
+
+fn modified(path: &Utf8Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn changes<'a>(before: &'a Snapshot, after: &'a Snapshot) -> impl Iterator<Item = &'a Utf8PathBuf> {
+    let removed = before.keys().filter(|path| !after.contains_key(*path));
+    let changed = after
+        .iter()
+        .filter(|(path, time)| before.get(*path) != Some(*time))
+        .map(|(path, _)| path);
+    changed.chain(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> Utf8PathBuf {
+        let dir = std::env::temp_dir().join(format!("ubrn-{name}-{}", std::process::id()));
+        let dir = Utf8PathBuf::try_from(dir).unwrap();
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        dir
+    }
+
+    fn touch(path: &Utf8Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, path.as_str()).unwrap();
+    }
+
+    #[test]
+    fn test_watched_paths() -> Result<()> {
+        let dir = temp_dir("watcher");
+        let crate_dir = dir.join("rust");
+        for file in [
+            "src/lib.rs",
+            "src/nested/mod.rs",
+            "Cargo.toml",
+            "target/debug/libmylib.a",
+            "node_modules/dep/index.js",
+            "build/symbols/lib.sym",
+            ".git/HEAD",
+            "src/.hidden.rs",
+            "generated/module.ts",
+        ] {
+            touch(&crate_dir.join(file));
+        }
+        let uniffi_toml = dir.join("uniffi.toml");
+        touch(&uniffi_toml);
+        let missing = dir.join("ubrn.config.yaml");
+
+        let watcher = Watcher::new(vec![
+            crate_dir.clone(),
+            uniffi_toml.clone(),
+            missing.clone(),
+        ])
+        .ignore(&crate_dir.join("generated"));
+        let snapshot = watcher.snapshot();
+        let mut expected = vec![
+            crate_dir.join("Cargo.toml"),
+            crate_dir.join("src/lib.rs"),
+            crate_dir.join("src/nested/mod.rs"),
+            uniffi_toml,
+            missing.clone(),
+        ];
+        expected.sort();
+        assert_eq!(snapshot.keys().cloned().collect::<Vec<_>>(), expected);
+
+        // Files which are given explicitly are watched even before they exist.
+        assert_eq!(snapshot[&missing], None);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_changes() {
+        let time = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let before = Snapshot::from([
+            ("same.rs".into(), time(1)),
+            ("changed.rs".into(), time(1)),
+            ("removed.rs".into(), time(1)),
+            ("created.toml".into(), None),
+        ]);
+        let after = Snapshot::from([
+            ("same.rs".into(), time(1)),
+            ("changed.rs".into(), time(2)),
+            ("created.toml".into(), time(2)),
+            ("added.rs".into(), time(2)),
+        ]);
+        let changed: Vec<_> = changes(&before, &after).map(|p| p.as_str()).collect();
+        assert_eq!(
+            changed,
+            vec!["added.rs", "changed.rs", "created.toml", "removed.rs"]
+        );
+    }
+}
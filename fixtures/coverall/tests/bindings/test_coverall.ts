@@ -27,6 +27,7 @@ import coverall, {
   Repair,
   PatchInterface,
   Color,
+  SimpleDict,
 } from "../../generated/coverall";
 import { test } from "@/asserts";
 import { console } from "@/hermes";
@@ -97,6 +98,17 @@ test("test create_none_dict() with default values", (t) => {
   t.assertEqual(d.coveralls, undefined);
 });
 
+test("Records round trip through JSON", (t) => {
+  const d = createNoneDict();
+  const json = JSON.stringify(SimpleDict.toJSON(d));
+  const copy = SimpleDict.fromJSON(JSON.parse(json));
+  t.assertEqual(copy.unsigned64, BigInt("0xffffffffffffffff"));
+  t.assertEqual(copy.someBytes.byteLength, "some_bytes".length);
+  t.assertEqual(copy.maybeText, undefined);
+  t.assertTrue(SimpleDict.equals(d, copy));
+  t.assertFalse(SimpleDict.equals(d, { ...copy, maybeSigned8: 0 }));
+});
+
 test("arc", (t) => {
   const coveralls = new Coveralls("test_arcs");
   t.assertEqual(getNumAlive(), BigInt("1"));
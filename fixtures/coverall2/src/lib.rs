@@ -3,6 +3,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
+use std::collections::HashMap;
 
 #[uniffi::export]
 /// This makes the byte array in rust, and the test in JS will compare it there.
@@ -27,4 +28,33 @@ pub fn identity_array_buffer_forced_read(bytes: Option<Vec<u8>>) -> Option<Vec<u
     bytes
 }
 
+/// With `checkArguments` in `uniffi.toml`, the integers in these are checked before they
+/// are passed to Rust.
+#[derive(uniffi::Record)]
+pub struct CheckedIntegers {
+    pub maybe_u8: Option<u8>,
+    pub i32s: Vec<i32>,
+    pub u16s: HashMap<String, u16>,
+}
+
+#[uniffi::export]
+pub fn identity_checked_integers(value: CheckedIntegers) -> CheckedIntegers {
+    value
+}
+
+#[uniffi::export]
+pub fn identity_optional_u8(value: Option<u8>) -> Option<u8> {
+    value
+}
+
+#[uniffi::export]
+pub fn identity_i32s(values: Vec<i32>) -> Vec<i32> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_u16_map(values: HashMap<String, u16>) -> HashMap<String, u16> {
+    values
+}
+
 uniffi::setup_scaffolding!();
@@ -4,13 +4,56 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/
  */
 import {
+  CheckedIntegers,
   identityArrayBuffer,
   identityArrayBufferForcedRead,
+  identityCheckedIntegers,
+  identityI32s,
+  identityOptionalU8,
+  identityU16Map,
   wellKnownArrayBuffer,
 } from "../../generated/uniffi_coverall2";
 import { test } from "@/asserts";
 import { console } from "@/hermes";
 
+// checkArguments is on in uniffi.toml.
+test("Integers in optionals, sequences and maps are checked", (t) => {
+  const invalid = (message: string) => (e: any) =>
+    e instanceof Error && e.message.startsWith(message);
+
+  t.assertEqual(identityOptionalU8(undefined), undefined);
+  t.assertEqual(identityOptionalU8(255), 255);
+  t.assertThrows(invalid("identityOptionalU8: value must be"), () =>
+    identityOptionalU8(256),
+  );
+
+  t.assertEqual(identityI32s([-1, 2]).length, 2);
+  t.assertThrows(invalid("identityI32s: values[1] must be"), () =>
+    identityI32s([1, 2.5]),
+  );
+
+  t.assertEqual(identityU16Map(new Map([["a", 65535]])).get("a"), 65535);
+  t.assertThrows(invalid("identityU16Map: values.get(a) must be"), () =>
+    identityU16Map(new Map([["a", -1]])),
+  );
+
+  const valid = CheckedIntegers.create({
+    maybeU8: 1,
+    i32s: [1],
+    u16s: new Map([["a", 1]]),
+  });
+  t.assertEqual(identityCheckedIntegers(valid).maybeU8, 1);
+  t.assertThrows(invalid("CheckedIntegers: maybeU8 must be"), () =>
+    identityCheckedIntegers({ ...valid, maybeU8: 300 }),
+  );
+  t.assertThrows(invalid("CheckedIntegers: i32s[0] must be"), () =>
+    identityCheckedIntegers({ ...valid, i32s: [2 ** 31] }),
+  );
+  t.assertThrows(invalid("CheckedIntegers: u16s.get(a) must be"), () =>
+    identityCheckedIntegers({ ...valid, u16s: new Map([["a", 65536]]) }),
+  );
+});
+
 test("well known array buffer returned", (t) => {
   const wellKnown = wellKnownArrayBuffer();
   t.assertEqual(0, wellKnown.byteLength);
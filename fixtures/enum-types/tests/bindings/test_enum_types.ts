@@ -175,6 +175,38 @@ test("Variant naming cam collide with existing types", (t) => {
   }
 });
 
+test("Enums round trip through JSON", (t) => {
+  const values = [
+    CollidingVariants.AnimalRecord.new(AnimalRecord.create({ value: 5 })),
+    CollidingVariants.Animal.new(Animal.Cat),
+    CollidingVariants.CollidingVariants.new(),
+  ];
+  for (const v of values) {
+    const json = JSON.stringify(CollidingVariants.toJSON(v));
+    const copy = CollidingVariants.fromJSON(JSON.parse(json));
+    t.assertEqual(copy.tag, v.tag);
+    t.assertTrue(CollidingVariants.instanceOf(copy));
+    t.assertTrue(CollidingVariants.equals(v, copy));
+    t.assertEqual(identityCollidingVariants(copy), v);
+  }
+
+  t.assertFalse(CollidingVariants.equals(values[0], values[1]));
+  t.assertFalse(
+    CollidingVariants.equals(
+      values[0],
+      CollidingVariants.AnimalRecord.new(AnimalRecord.create({ value: 6 })),
+    ),
+  );
+
+  const dog = CollidingVariants.AnimalObject.new(new AnimalObject(1));
+  // Objects live in Rust, so cannot be written as JSON.
+  t.assertThrows(
+    (e: any) => e instanceof Error,
+    () => CollidingVariants.toJSON(dog),
+  );
+  t.assertTrue(CollidingVariants.equals(dog, dog));
+});
+
 // This tests the generated Typescript and serves as an example of how to
 // to use enums with values.
 //
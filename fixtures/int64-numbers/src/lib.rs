@@ -0,0 +1,78 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::{collections::HashMap, sync::Mutex};
+
+// With `int64 = "number"` in `uniffi.toml`, these are numbers in Typescript, except
+// where `int64Overrides` says otherwise.
+
+#[uniffi::export]
+pub fn add_i64(a: i64, b: i64) -> i64 {
+    a.wrapping_add(b)
+}
+
+#[uniffi::export]
+pub fn identity_u64(value: u64) -> u64 {
+    value
+}
+
+#[uniffi::export]
+pub fn max_u64() -> u64 {
+    u64::MAX
+}
+
+#[uniffi::export]
+pub fn identity_big_u64(value: u64) -> u64 {
+    value
+}
+
+#[derive(uniffi::Record)]
+pub struct Stats {
+    pub count: u64,
+    pub total_bytes: u64,
+    pub offsets: Vec<i64>,
+    pub by_name: HashMap<String, u64>,
+    pub last: Option<i64>,
+}
+
+#[uniffi::export]
+pub fn identity_stats(value: Stats) -> Stats {
+    value
+}
+
+pub struct Nanos(pub u64);
+uniffi::custom_newtype!(Nanos, u64);
+
+#[uniffi::export]
+pub fn identity_nanos(value: Nanos) -> Nanos {
+    value
+}
+
+#[derive(uniffi::Object)]
+pub struct Accumulator {
+    total: Mutex<u64>,
+}
+
+#[uniffi::export]
+impl Accumulator {
+    #[uniffi::constructor]
+    pub fn new(start: u64) -> Self {
+        Self {
+            total: Mutex::new(start),
+        }
+    }
+
+    pub fn add(&self, amount: u64) -> u64 {
+        let mut total = self.total.lock().unwrap();
+        *total += amount;
+        *total
+    }
+
+    pub fn total_big(&self) -> u64 {
+        *self.total.lock().unwrap()
+    }
+}
+
+uniffi::setup_scaffolding!();
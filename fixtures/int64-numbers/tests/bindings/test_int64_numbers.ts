@@ -0,0 +1,85 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import {
+  Accumulator,
+  Stats,
+  addI64,
+  identityBigU64,
+  identityNanos,
+  identityStats,
+  identityU64,
+  maxU64,
+} from "../../generated/uniffi_int64_numbers";
+import { test } from "@/asserts";
+
+// int64 = "number" and checkArguments = true are set in uniffi.toml.
+const precisionLoss = (e: any) =>
+  e instanceof Error && e.message.startsWith("Cannot convert");
+const invalid = (message: string) => (e: any) =>
+  e instanceof Error && e.message.startsWith(message);
+
+test("64-bit integers are numbers", (t) => {
+  t.assertEqual(addI64(1, 2), 3);
+  t.assertEqual(addI64(-1, -2), -3);
+  t.assertEqual(typeof identityU64(42), "number");
+  t.assertEqual(
+    identityU64(Number.MAX_SAFE_INTEGER),
+    Number.MAX_SAFE_INTEGER,
+  );
+});
+
+test("Numbers which are not safe integers are not passed to or from Rust", (t) => {
+  t.assertThrows(precisionLoss, () => maxU64());
+  t.assertThrows(precisionLoss, () => identityU64(2 ** 60));
+  t.assertThrows(precisionLoss, () =>
+    addI64(Number.MAX_SAFE_INTEGER, 1),
+  );
+  t.assertThrows(invalid("identityU64: value must be"), () =>
+    identityU64(-1),
+  );
+  t.assertThrows(invalid("addI64: a must be"), () => addI64(2.5, 1));
+});
+
+test("int64Overrides keeps bigints where they are asked for", (t) => {
+  const big = BigInt("18446744073709551615");
+  t.assertEqual(identityBigU64(big), big);
+  t.assertEqual(identityNanos(big), big);
+
+  const stats = Stats.create({
+    count: 2,
+    totalBytes: big,
+    offsets: [-1, 1],
+    byName: new Map([["a", 1]]),
+    last: undefined,
+  });
+  const copy = identityStats(stats);
+  t.assertEqual(copy.count, 2);
+  t.assertEqual(copy.totalBytes, big);
+  t.assertEqual(copy.offsets, [-1, 1]);
+  t.assertEqual(copy.byName.get("a"), 1);
+  t.assertEqual(copy.last, undefined);
+  t.assertThrows(invalid("Stats: offsets[0] must be"), () =>
+    identityStats({ ...stats, offsets: [0.5] }),
+  );
+
+  const acc = new Accumulator(1);
+  t.assertEqual(acc.add(2), 3);
+  t.assertEqual(acc.totalBig(), BigInt(3));
+});
+
+test("Records with 64-bit integers as numbers round trip through JSON", (t) => {
+  const stats = Stats.create({
+    count: 1,
+    totalBytes: BigInt("18446744073709551615"),
+    offsets: [-3],
+    byName: new Map([["b", 2]]),
+    last: 4,
+  });
+  const json = JSON.stringify(Stats.toJSON(stats));
+  const copy = Stats.fromJSON(JSON.parse(json));
+  t.assertTrue(Stats.equals(stats, copy));
+  t.assertEqual(copy.totalBytes, stats.totalBytes);
+});
@@ -0,0 +1,61 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// With `timestamp = "millis"` and `duration = "object"` in `uniffi.toml`, a `SystemTime`
+// is a number of milliseconds in Typescript, and a `Duration` is seconds and nanoseconds.
+
+#[uniffi::export]
+pub fn after_epoch(seconds: u32, nanos: u32) -> SystemTime {
+    UNIX_EPOCH + Duration::new(seconds.into(), nanos)
+}
+
+#[uniffi::export]
+pub fn before_epoch(seconds: u32, nanos: u32) -> SystemTime {
+    UNIX_EPOCH - Duration::new(seconds.into(), nanos)
+}
+
+/// The timestamp as Rust sees it, as seconds and nanoseconds from the epoch.
+#[uniffi::export]
+pub fn describe_timestamp(value: SystemTime) -> String {
+    match value.duration_since(UNIX_EPOCH) {
+        Ok(d) => format!("{}.{:09}", d.as_secs(), d.subsec_nanos()),
+        Err(e) => {
+            let d = e.duration();
+            format!("-{}.{:09}", d.as_secs(), d.subsec_nanos())
+        }
+    }
+}
+
+#[uniffi::export]
+pub fn identity_timestamp(value: SystemTime) -> SystemTime {
+    value
+}
+
+#[uniffi::export]
+pub fn duration_of(seconds: u32, nanos: u32) -> Duration {
+    Duration::new(seconds.into(), nanos)
+}
+
+/// The duration as Rust sees it, as seconds and nanoseconds.
+#[uniffi::export]
+pub fn describe_duration(value: Duration) -> String {
+    format!("{}.{:09}", value.as_secs(), value.subsec_nanos())
+}
+
+#[derive(uniffi::Record)]
+pub struct Event {
+    pub at: SystemTime,
+    pub took: Duration,
+    pub ended: Option<SystemTime>,
+}
+
+#[uniffi::export]
+pub fn identity_event(value: Event) -> Event {
+    value
+}
+
+uniffi::setup_scaffolding!();
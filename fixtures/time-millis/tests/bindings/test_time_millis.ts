@@ -0,0 +1,54 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import {
+  Event,
+  afterEpoch,
+  beforeEpoch,
+  describeDuration,
+  describeTimestamp,
+  durationOf,
+  identityEvent,
+  identityTimestamp,
+} from "../../generated/uniffi_time_millis";
+import { test } from "@/asserts";
+
+// timestamp = "millis" and duration = "object" are set in uniffi.toml.
+test("Timestamps are milliseconds since the epoch", (t) => {
+  t.assertEqual(afterEpoch(1, 0), 1000);
+  t.assertEqual(beforeEpoch(1, 500000000), -1500);
+  t.assertEqual(identityTimestamp(1234567), 1234567);
+  t.assertEqual(describeTimestamp(-1500), "-1.500000000");
+});
+
+test("Fractions of a millisecond are kept", (t) => {
+  t.assertEqual(afterEpoch(1, 500000), 1000.5);
+  t.assertEqual(describeTimestamp(1000.5), "1.000500000");
+  t.assertEqual(identityTimestamp(1234567.25), 1234567.25);
+});
+
+test("Durations are seconds and nanoseconds", (t) => {
+  const duration = durationOf(2, 5);
+  t.assertEqual(duration.seconds, BigInt(2));
+  t.assertEqual(duration.nanos, 5);
+  t.assertEqual(
+    describeDuration({ seconds: BigInt(3), nanos: 1 }),
+    "3.000000001",
+  );
+});
+
+test("Records with times round trip through Rust and JSON", (t) => {
+  const event = Event.create({
+    at: 1000.5,
+    took: { seconds: BigInt("18446744073709551615"), nanos: 999999999 },
+    ended: undefined,
+  });
+  t.assertTrue(Event.equals(event, identityEvent(event)));
+
+  const json = JSON.stringify(Event.toJSON(event));
+  const copy = Event.fromJSON(JSON.parse(json));
+  t.assertEqual(copy.took.seconds, event.took.seconds);
+  t.assertTrue(Event.equals(event, copy));
+});
@@ -0,0 +1,55 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import {
+  Event,
+  afterEpoch,
+  beforeEpoch,
+  describeTimestamp,
+  durationOf,
+  identityEvent,
+  identityTimestamp,
+} from "../../generated/uniffi_time_objects";
+import { test } from "@/asserts";
+
+// timestamp = "object" is set in uniffi.toml.
+test("Timestamps are seconds and nanoseconds since the epoch", (t) => {
+  const after = afterEpoch(1, 5);
+  t.assertEqual(after.seconds, BigInt(1));
+  t.assertEqual(after.nanos, 5);
+
+  const big = { seconds: BigInt("253402300799"), nanos: 999999999 };
+  const copy = identityTimestamp(big);
+  t.assertEqual(copy.seconds, big.seconds);
+  t.assertEqual(copy.nanos, big.nanos);
+  t.assertEqual(describeTimestamp(big), "253402300799.999999999");
+});
+
+test("Before the epoch, the seconds are rounded down and the nanos added", (t) => {
+  const before = beforeEpoch(1, 500000000);
+  t.assertEqual(before.seconds, BigInt(-2));
+  t.assertEqual(before.nanos, 500000000);
+  t.assertEqual(describeTimestamp(before), "-1.500000000");
+
+  const whole = beforeEpoch(2, 0);
+  t.assertEqual(whole.seconds, BigInt(-2));
+  t.assertEqual(whole.nanos, 0);
+});
+
+test("Durations are still milliseconds", (t) => {
+  t.assertEqual(durationOf(1, 500000), 1000.5);
+});
+
+test("Records with times round trip through Rust and JSON", (t) => {
+  const event = Event.create({
+    at: { seconds: BigInt(-5), nanos: 1 },
+    took: 250,
+    ended: { seconds: BigInt(10), nanos: 0 },
+  });
+  t.assertTrue(Event.equals(event, identityEvent(event)));
+
+  const json = JSON.stringify(Event.toJSON(event));
+  t.assertTrue(Event.equals(event, Event.fromJSON(JSON.parse(json))));
+});
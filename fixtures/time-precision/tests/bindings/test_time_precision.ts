@@ -0,0 +1,43 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import {
+  Event,
+  afterEpoch,
+  beforeEpoch,
+  describeTimestamp,
+  identityEvent,
+  identityTimestamp,
+} from "../../generated/uniffi_time_precision";
+import { test } from "@/asserts";
+
+// timestampPrecision = "error" is set in uniffi.toml.
+const precisionLoss = (e: any) =>
+  e instanceof Error && e.message.startsWith("Cannot convert");
+
+test("Timestamps of whole milliseconds are dates", (t) => {
+  t.assertEqual(afterEpoch(1, 0).getTime(), 1000);
+  t.assertEqual(afterEpoch(1, 2000000).getTime(), 1002);
+  t.assertEqual(beforeEpoch(1, 500000000).getTime(), -1500);
+  t.assertEqual(identityTimestamp(new Date(1500)).getTime(), 1500);
+  t.assertEqual(describeTimestamp(new Date(-1500)), "-1.500000000");
+});
+
+test("Timestamps with fractions of a millisecond throw", (t) => {
+  t.assertThrows(precisionLoss, () => afterEpoch(1, 1));
+  t.assertThrows(precisionLoss, () => beforeEpoch(0, 999999999));
+});
+
+test("Records with dates round trip through Rust and JSON", (t) => {
+  const event = Event.create({
+    at: new Date(1700000000000),
+    took: 1.5,
+    ended: new Date(0),
+  });
+  t.assertTrue(Event.equals(event, identityEvent(event)));
+
+  const json = JSON.stringify(Event.toJSON(event));
+  t.assertTrue(Event.equals(event, Event.fromJSON(JSON.parse(json))));
+});
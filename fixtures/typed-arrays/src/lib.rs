@@ -0,0 +1,89 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+use std::collections::HashMap;
+
+// With `typedArrays = true` in `uniffi.toml`, sequences of fixed-width numbers are typed
+// arrays in Typescript.
+
+#[uniffi::export]
+pub fn sum_f32s(values: Vec<f32>) -> f32 {
+    values.iter().sum()
+}
+
+#[uniffi::export]
+pub fn identity_i8s(values: Vec<i8>) -> Vec<i8> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_u8s(values: Vec<u8>) -> Vec<u8> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_i16s(values: Vec<i16>) -> Vec<i16> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_u16s(values: Vec<u16>) -> Vec<u16> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_i32s(values: Vec<i32>) -> Vec<i32> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_u32s(values: Vec<u32>) -> Vec<u32> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_i64s(values: Vec<i64>) -> Vec<i64> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_u64s(values: Vec<u64>) -> Vec<u64> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_f64s(values: Vec<f64>) -> Vec<f64> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_optional_i32s(values: Option<Vec<i32>>) -> Option<Vec<i32>> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_nested_u16s(values: Vec<Vec<u16>>) -> Vec<Vec<u16>> {
+    values
+}
+
+#[uniffi::export]
+pub fn identity_strings(values: Vec<String>) -> Vec<String> {
+    values
+}
+
+#[derive(uniffi::Record)]
+pub struct Samples {
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+    pub frame_ids: Vec<u64>,
+    pub by_channel: HashMap<String, Vec<i16>>,
+}
+
+#[uniffi::export]
+pub fn identity_samples(value: Samples) -> Samples {
+    value
+}
+
+uniffi::setup_scaffolding!();
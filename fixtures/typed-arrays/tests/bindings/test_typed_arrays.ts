@@ -0,0 +1,107 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import {
+  Samples,
+  identityF64s,
+  identityI16s,
+  identityI32s,
+  identityI64s,
+  identityI8s,
+  identityNestedU16s,
+  identityOptionalI32s,
+  identitySamples,
+  identityStrings,
+  identityU16s,
+  identityU32s,
+  identityU64s,
+  identityU8s,
+  sumF32s,
+} from "../../generated/uniffi_typed_arrays";
+import { test } from "@/asserts";
+
+// typedArrays = true is set in uniffi.toml.
+test("Sequences of fixed-width numbers are typed arrays", (t) => {
+  t.assertEqual(sumF32s(new Float32Array([0.5, 1.5, 2])), 4);
+
+  const i8s = identityI8s(new Int8Array([-128, 0, 127]));
+  t.assertTrue(i8s instanceof Int8Array);
+  t.assertEqual(Array.from(i8s), [-128, 0, 127]);
+
+  const i16s = identityI16s(new Int16Array([-32768, 32767]));
+  t.assertTrue(i16s instanceof Int16Array);
+  t.assertEqual(Array.from(i16s), [-32768, 32767]);
+
+  const u16s = identityU16s(new Uint16Array([0, 65535]));
+  t.assertTrue(u16s instanceof Uint16Array);
+  t.assertEqual(Array.from(u16s), [0, 65535]);
+
+  const i32s = identityI32s(new Int32Array([-1, 2 ** 31 - 1]));
+  t.assertTrue(i32s instanceof Int32Array);
+  t.assertEqual(Array.from(i32s), [-1, 2 ** 31 - 1]);
+
+  const u32s = identityU32s(new Uint32Array([0, 2 ** 32 - 1]));
+  t.assertTrue(u32s instanceof Uint32Array);
+  t.assertEqual(Array.from(u32s), [0, 2 ** 32 - 1]);
+
+  const f64s = identityF64s(new Float64Array([Math.PI, -0.25]));
+  t.assertTrue(f64s instanceof Float64Array);
+  t.assertEqual(Array.from(f64s), [Math.PI, -0.25]);
+});
+
+test("Sequences of 64-bit integers are bigint typed arrays", (t) => {
+  const min = BigInt("-9223372036854775808");
+  const i64s = identityI64s(new BigInt64Array([min, BigInt(1)]));
+  t.assertTrue(i64s instanceof BigInt64Array);
+  t.assertEqual(i64s[0], min);
+
+  const max = BigInt("18446744073709551615");
+  const u64s = identityU64s(new BigUint64Array([max]));
+  t.assertTrue(u64s instanceof BigUint64Array);
+  t.assertEqual(u64s[0], max);
+});
+
+test("Typed arrays can be empty, optional or nested", (t) => {
+  t.assertEqual(identityI32s(new Int32Array()).length, 0);
+  t.assertEqual(identityOptionalI32s(undefined), undefined);
+  t.assertEqual(
+    Array.from(identityOptionalI32s(new Int32Array([3]))!),
+    [3],
+  );
+
+  const nested = identityNestedU16s([
+    new Uint16Array([1, 2]),
+    new Uint16Array([]),
+  ]);
+  t.assertTrue(nested[0] instanceof Uint16Array);
+  t.assertEqual(nested.map((a) => a.length), [2, 0]);
+});
+
+test("Other sequences are still arrays, and bytes an ArrayBuffer", (t) => {
+  t.assertEqual(identityStrings(["a", "b"]), ["a", "b"]);
+
+  const bytes = identityU8s(new Uint8Array([1, 2, 3]).buffer);
+  t.assertTrue(bytes instanceof ArrayBuffer);
+  t.assertEqual(Array.from(new Uint8Array(bytes)), [1, 2, 3]);
+});
+
+test("Records with typed arrays round trip through Rust and JSON", (t) => {
+  const samples = Samples.create({
+    left: new Float32Array([0.25, -0.5]),
+    right: new Float32Array([1]),
+    // int64Overrides makes these numbers, so they stay an Array.
+    frameIds: [1, 2],
+    byChannel: new Map([["mono", new Int16Array([-1, 1])]]),
+  });
+  const copy = identitySamples(samples);
+  t.assertTrue(copy.left instanceof Float32Array);
+  t.assertTrue(Array.isArray(copy.frameIds));
+  t.assertTrue(Samples.equals(samples, copy));
+
+  const json = JSON.stringify(Samples.toJSON(samples));
+  const parsed = Samples.fromJSON(JSON.parse(json));
+  t.assertTrue(parsed.byChannel.get("mono") instanceof Int16Array);
+  t.assertTrue(Samples.equals(samples, parsed));
+});
@@ -84,6 +84,25 @@ export const UniffiInternalError = (() => {
       super("Cannot convert a large BigInt into a number");
     }
   }
+  class NumberPrecisionLoss extends Error {
+    constructor(value: number | bigint | string, typeName: string) {
+      super(`Cannot convert ${value} to ${typeName} without losing precision`);
+    }
+  }
+  class InvalidInteger extends Error {
+    constructor(
+      item: string,
+      name: string,
+      typeName: string,
+      value: any,
+      min: bigint,
+      max: bigint,
+    ) {
+      super(
+        `${item}: ${name} must be an integer from ${min} to ${max}, to be passed to Rust as a ${typeName}, but was ${value}`,
+      );
+    }
+  }
   class DateTimeOverflow extends Error {
     constructor() {
       super("Date overflowed passed maximum number of ms passed the epoch");
@@ -159,6 +178,8 @@ export const UniffiInternalError = (() => {
   return {
     ApiChecksumMismatch,
     NumberOverflow,
+    NumberPrecisionLoss,
+    InvalidInteger,
     DateTimeOverflow,
     BufferOverflow,
     ContractVersionMismatch,
@@ -126,6 +126,137 @@ export const FfiConverterUInt64 = new FfiConverterNumber(
   BigUint64Array.BYTES_PER_ELEMENT,
 );
 
+// 64-bit integers as numbers
+//
+// These are used when uniffi.toml asks for 64-bit integers to be represented as numbers.
+// They still cross the FFI as bigints, but only safe integers can be converted between
+// the two without losing precision; anything else throws.
+class FfiConverterBigIntAsNumber implements FfiConverter<bigint, number> {
+  constructor(
+    private inner: FfiConverter<bigint, bigint>,
+    private typeName: string,
+    private min: number,
+  ) {}
+  lift(value: bigint): number {
+    const num = Number(value);
+    if (!Number.isSafeInteger(num)) {
+      throw new UniffiInternalError.NumberPrecisionLoss(value, "number");
+    }
+    return num;
+  }
+  lower(value: number): bigint {
+    if (!Number.isSafeInteger(value) || value < this.min) {
+      throw new UniffiInternalError.NumberPrecisionLoss(value, this.typeName);
+    }
+    return BigInt(value);
+  }
+  read(from: RustBuffer): number {
+    return this.lift(this.inner.read(from));
+  }
+  write(value: number, into: RustBuffer): void {
+    this.inner.write(this.lower(value), into);
+  }
+  allocationSize(value: number): number {
+    return this.inner.allocationSize(BigInt(0));
+  }
+}
+export const FfiConverterInt64AsNumber = new FfiConverterBigIntAsNumber(
+  FfiConverterInt64,
+  "i64",
+  Number.MIN_SAFE_INTEGER,
+);
+export const FfiConverterUInt64AsNumber = new FfiConverterBigIntAsNumber(
+  FfiConverterUInt64,
+  "u64",
+  0,
+);
+
+// Checking integers
+//
+// DataView silently wraps or truncates numbers which don't fit into the type: e.g. -1 is
+// written as 255 for a u8, and 3.7 as 3. When checkArguments is on in uniffi.toml, the
+// generated code checks each integer argument and record field passed to Rust instead.
+type IntegerTypeName =
+  | "i8"
+  | "i16"
+  | "i32"
+  | "i64"
+  | "u8"
+  | "u16"
+  | "u32"
+  | "u64";
+const integerRanges: Record<IntegerTypeName, [bigint, bigint]> = {
+  i8: [BigInt("-128"), BigInt("127")],
+  i16: [BigInt("-32768"), BigInt("32767")],
+  i32: [BigInt("-2147483648"), BigInt("2147483647")],
+  i64: [BigInt("-9223372036854775808"), BigInt("9223372036854775807")],
+  u8: [BigInt("0"), BigInt("255")],
+  u16: [BigInt("0"), BigInt("65535")],
+  u32: [BigInt("0"), BigInt("4294967295")],
+  u64: [BigInt("0"), BigInt("18446744073709551615")],
+};
+
+/**
+ * Where the integers to check are in a value: either the value is an integer of the
+ * given Rust type, or it is an optional, a sequence or a map containing integers.
+ */
+export type UniffiIntegerCheck =
+  | IntegerTypeName
+  | { optional: UniffiIntegerCheck }
+  | { sequence: UniffiIntegerCheck }
+  | { mapKey?: UniffiIntegerCheck; mapValue?: UniffiIntegerCheck };
+
+/**
+ * Check that the integers in the value fit into their Rust integer types, and throw an
+ * error naming the function or record, `item`, and the argument or field, `name`, if
+ * one does not.
+ *
+ * @returns the value, unchanged.
+ */
+export function uniffiCheckInteger<T>(
+  value: T,
+  check: UniffiIntegerCheck,
+  item: string,
+  name: string,
+): T {
+  if (typeof check === "string") {
+    const [min, max] = integerRanges[check];
+    const n = value as unknown as number | bigint;
+    const isInteger =
+      typeof n === "bigint" ||
+      (typeof n === "number" && Number.isSafeInteger(n));
+    if (!isInteger || n < min || n > max) {
+      throw new UniffiInternalError.InvalidInteger(
+        item,
+        name,
+        check,
+        value,
+        min,
+        max,
+      );
+    }
+  } else if ("optional" in check) {
+    if (value !== undefined) {
+      uniffiCheckInteger(value, check.optional, item, name);
+    }
+  } else if ("sequence" in check) {
+    let i = 0;
+    for (const v of value as Iterable<unknown>) {
+      uniffiCheckInteger(v, check.sequence, item, `${name}[${i++}]`);
+    }
+  } else {
+    for (const [k, v] of value as Map<unknown, unknown>) {
+      if (check.mapKey !== undefined) {
+        uniffiCheckInteger(k, check.mapKey, item, `a key of ${name}`);
+      }
+      if (check.mapValue !== undefined) {
+        uniffiCheckInteger(v, check.mapValue, item, `${name}.get(${k})`);
+      }
+    }
+  }
+  return value;
+}
+
 // Bool
 export const FfiConverterBool = (() => {
   const byteConverter = FfiConverterInt8;
@@ -149,89 +280,148 @@ export const FfiConverterBool = (() => {
   return new FfiConverterBool();
 })();
 
-// Duration
+// Durations and timestamps
 //
-// There is currently no JS API for duration, so we'll make this just milliseconds.
+// Both cross the FFI as a number of seconds and a number of nanoseconds. A timestamp is
+// the time since the epoch: its seconds are negative for a time before the epoch, and its
+// nanos are then subtracted rather than added.
 //
-// Later on we'll need to put a Temporal based converter,
-// and switch on from a config file.
-export type UniffiDuration = number;
-export const FfiConverterDuration = (() => {
-  const secondsConverter = FfiConverterUInt64;
-  const nanosConverter = FfiConverterUInt32;
-  const msPerSecBigInt = BigInt("1000");
-  const nanosPerMs = 1e6;
-  class FFIConverter extends AbstractFfiConverterArrayBuffer<UniffiDuration> {
-    read(from: RustBuffer): UniffiDuration {
-      const secsBigInt = secondsConverter.read(from);
-      const nanos = nanosConverter.read(from);
-      const ms = Number(secsBigInt * msPerSecBigInt);
-      if (ms === Number.POSITIVE_INFINITY || ms === Number.NEGATIVE_INFINITY) {
-        throw new UniffiInternalError.NumberOverflow();
-      }
-      return ms + nanos / nanosPerMs;
-    }
-    write(value: UniffiDuration, into: RustBuffer): void {
-      const ms = value.valueOf();
-      const secsBigInt = BigInt(Math.trunc(ms)) / msPerSecBigInt;
-      const remainingNanos = (ms % 1000) * nanosPerMs;
-      secondsConverter.write(secsBigInt, into);
-      nanosConverter.write(remainingNanos, into);
-    }
-    allocationSize(_value: UniffiDuration): number {
-      return (
-        secondsConverter.allocationSize(msPerSecBigInt) +
-        nanosConverter.allocationSize(0)
-      );
-    }
+// Which representation is used is chosen in uniffi.toml.
+class FfiConverterTime<T> extends AbstractFfiConverterArrayBuffer<T> {
+  private static nanosConverter = FfiConverterUInt32;
+  constructor(
+    private secondsConverter: FfiConverter<bigint, bigint>,
+    private fromRust: (seconds: bigint, nanos: number) => T,
+    private toRust: (value: T) => [bigint, number],
+  ) {
+    super();
   }
-  return new FFIConverter();
-})();
+  read(from: RustBuffer): T {
+    const seconds = this.secondsConverter.read(from);
+    const nanos = FfiConverterTime.nanosConverter.read(from);
+    return this.fromRust(seconds, nanos);
+  }
+  write(value: T, into: RustBuffer): void {
+    const [seconds, nanos] = this.toRust(value);
+    this.secondsConverter.write(seconds, into);
+    FfiConverterTime.nanosConverter.write(nanos, into);
+  }
+  allocationSize(_value: T): number {
+    return (
+      this.secondsConverter.allocationSize(BigInt(0)) +
+      FfiConverterTime.nanosConverter.allocationSize(0)
+    );
+  }
+}
 
-// We'll provide native js Date here; later on we'll need to put a Temporal based converter,
-// and switch on from a config file.
+const msPerSecBigInt = BigInt("1000");
+const msPerSec = 1e3;
+const nanosPerMs = 1e6;
+const nanosPerSec = 1e9;
+
+function millisFromRust(seconds: bigint, nanos: number): number {
+  const ms = Number(seconds * msPerSecBigInt);
+  if (ms === Number.POSITIVE_INFINITY || ms === Number.NEGATIVE_INFINITY) {
+    throw new UniffiInternalError.NumberOverflow();
+  }
+  return ms >= 0 ? ms + nanos / nanosPerMs : ms - nanos / nanosPerMs;
+}
+
+function millisToRust(ms: number): [bigint, number] {
+  const seconds = BigInt(Math.trunc(ms / msPerSec));
+  // Rounding the fraction of a millisecond can make it a whole second.
+  const nanos = Math.round(Math.abs((ms % msPerSec) * nanosPerMs));
+  return [seconds, Math.min(nanos, nanosPerSec - 1)];
+}
+
+// A duration of milliseconds, with any fraction of a millisecond kept as a fraction.
+export type UniffiDuration = number;
+export const FfiConverterDuration = new FfiConverterTime<UniffiDuration>(
+  FfiConverterUInt64,
+  millisFromRust,
+  millisToRust,
+);
+
+// The seconds and nanoseconds, as in Rust, so that nothing is lost.
+export type UniffiDurationObject = { seconds: bigint; nanos: number };
+export const FfiConverterDurationObject =
+  new FfiConverterTime<UniffiDurationObject>(
+    FfiConverterUInt64,
+    (seconds, nanos) => ({ seconds, nanos }),
+    ({ seconds, nanos }) => [seconds, nanos],
+  );
+
+// A native js Date, which only holds whole milliseconds.
 export type UniffiTimestamp = Date;
-export const FfiConverterTimestamp = (() => {
-  const secondsConverter = FfiConverterInt64;
-  const nanosConverter = FfiConverterUInt32;
-  const msPerSecBigInt = BigInt("1000");
-  const nanosPerMs = 1e6;
-  const msPerSec = 1e3;
-  const maxMsFromEpoch = 8.64e15;
-  function safeDate(ms: number) {
-    if (Math.abs(ms) > 8.64e15) {
-      throw new UniffiInternalError.DateTimeOverflow();
-    }
-    return new Date(ms);
-  }
-
-  class FFIConverter extends AbstractFfiConverterArrayBuffer<UniffiTimestamp> {
-    read(from: RustBuffer): UniffiTimestamp {
-      const secsBigInt = secondsConverter.read(from);
-      const nanos = nanosConverter.read(from);
-      const ms = Number(secsBigInt * msPerSecBigInt);
-      if (ms >= 0) {
-        return safeDate(ms + nanos / nanosPerMs);
-      } else {
-        return safeDate(ms - nanos / nanosPerMs);
-      }
-    }
-    write(value: UniffiTimestamp, into: RustBuffer): void {
-      const ms = value.valueOf();
-      const secsBigInt = BigInt(Math.trunc(ms / msPerSec));
-      const remainingNanos = Math.abs((ms % msPerSec) * nanosPerMs);
-      secondsConverter.write(secsBigInt, into);
-      nanosConverter.write(remainingNanos, into);
-    }
-    allocationSize(_value: UniffiTimestamp): number {
-      return (
-        secondsConverter.allocationSize(msPerSecBigInt) +
-        nanosConverter.allocationSize(0)
-      );
-    }
+const maxMsFromEpoch = 8.64e15;
+function safeDate(ms: number) {
+  if (Math.abs(ms) > maxMsFromEpoch) {
+    throw new UniffiInternalError.DateTimeOverflow();
   }
-  return new FFIConverter();
-})();
+  return new Date(ms);
+}
+function dateToRust(value: UniffiTimestamp): [bigint, number] {
+  return millisToRust(value.valueOf());
+}
+
+// Any fraction of a millisecond is dropped, as by `new Date(ms)`.
+export const FfiConverterTimestamp = new FfiConverterTime<UniffiTimestamp>(
+  FfiConverterInt64,
+  (seconds, nanos) => safeDate(millisFromRust(seconds, nanos)),
+  dateToRust,
+);
+
+// Any fraction of a millisecond is rounded to the nearest millisecond.
+export const FfiConverterTimestampRounded =
+  new FfiConverterTime<UniffiTimestamp>(
+    FfiConverterInt64,
+    (seconds, nanos) => safeDate(Math.round(millisFromRust(seconds, nanos))),
+    dateToRust,
+  );
+
+// Any fraction of a millisecond throws, instead of being lost.
+export const FfiConverterTimestampExact =
+  new FfiConverterTime<UniffiTimestamp>(
+    FfiConverterInt64,
+    (seconds, nanos) => {
+      if (nanos % nanosPerMs !== 0) {
+        throw new UniffiInternalError.NumberPrecisionLoss(
+          `${seconds}s ${nanos}ns`,
+          "Date",
+        );
+      }
+      return safeDate(millisFromRust(seconds, nanos));
+    },
+    dateToRust,
+  );
+
+// Milliseconds since the epoch, with any fraction of a millisecond kept as a fraction.
+export type UniffiTimestampMillis = number;
+export const FfiConverterTimestampMillis =
+  new FfiConverterTime<UniffiTimestampMillis>(
+    FfiConverterInt64,
+    millisFromRust,
+    millisToRust,
+  );
+
+// Seconds since the epoch, and the nanoseconds after that, from 0 to 999,999,999, so
+// that nothing is lost. For a time before the epoch, the seconds are rounded down, as for
+// a `Temporal.Instant`, and so the nanos are still added.
+export type UniffiTimestampObject = { seconds: bigint; nanos: number };
+export const FfiConverterTimestampObject =
+  new FfiConverterTime<UniffiTimestampObject>(
+    FfiConverterInt64,
+    (seconds, nanos) =>
+      seconds < 0 && nanos > 0
+        ? { seconds: seconds - BigInt(1), nanos: nanosPerSec - nanos }
+        : { seconds, nanos },
+    // A time less than a second before the epoch has 0 seconds in Rust, so it is read
+    // by Rust as after the epoch.
+    ({ seconds, nanos }) =>
+      seconds < 0 && nanos > 0
+        ? [seconds + BigInt(1), nanosPerSec - nanos]
+        : [seconds, nanos],
+  );
 
 export class FfiConverterOptional<Item> extends AbstractFfiConverterArrayBuffer<
   Item | undefined
@@ -291,6 +481,101 @@ export class FfiConverterArray<Item> extends AbstractFfiConverterArrayBuffer<
   }
 }
 
+// Typed arrays
+//
+// When typedArrays is on in uniffi.toml, sequences of fixed-width numbers are typed arrays,
+// which are copied into and out of the RustBuffer in one go, instead of item by item.
+//
+// Items are big-endian in the RustBuffer, but typed arrays use the platform's byte order,
+// so the bytes of each item are swapped if the two are different.
+type TypedArray =
+  | Int8Array
+  | Uint8Array
+  | Int16Array
+  | Uint16Array
+  | Int32Array
+  | Uint32Array
+  | BigInt64Array
+  | BigUint64Array
+  | Float32Array
+  | Float64Array;
+type TypedArrayConstructor<T extends TypedArray> = {
+  new (buffer: ArrayBuffer): T;
+  new (length: number): T;
+  readonly BYTES_PER_ELEMENT: number;
+};
+
+const platformIsLittleEndian =
+  new Uint8Array(new Uint16Array([1]).buffer)[0] === 1;
+
+function swapBytes(bytes: Uint8Array, itemSize: number) {
+  for (let start = 0; start < bytes.length; start += itemSize) {
+    for (let i = start, j = start + itemSize - 1; i < j; i++, j--) {
+      const b = bytes[i];
+      bytes[i] = bytes[j];
+      bytes[j] = b;
+    }
+  }
+}
+
+class FfiConverterTypedArray<
+  T extends TypedArray,
+> extends AbstractFfiConverterArrayBuffer<T> {
+  private static sizeConverter = FfiConverterInt32;
+  private needsSwap: boolean;
+  constructor(private arrayType: TypedArrayConstructor<T>) {
+    super();
+    this.needsSwap =
+      arrayType.BYTES_PER_ELEMENT > 1 &&
+      littleEndian !== platformIsLittleEndian;
+  }
+  read(from: RustBuffer): T {
+    const size = FfiConverterTypedArray.sizeConverter.read(from);
+    const itemSize = this.arrayType.BYTES_PER_ELEMENT;
+    // readBytes returns a copy, so it can be swapped in place.
+    const bytes = from.readBytes(size * itemSize);
+    if (this.needsSwap) {
+      swapBytes(new Uint8Array(bytes), itemSize);
+    }
+    return new this.arrayType(bytes);
+  }
+  write(array: T, into: RustBuffer): void {
+    FfiConverterTypedArray.sizeConverter.write(array.length, into);
+    const start = array.byteOffset;
+    // Copy the bytes, so that swapping them doesn't change the array.
+    const bytes = array.buffer.slice(start, start + array.byteLength);
+    if (this.needsSwap) {
+      swapBytes(new Uint8Array(bytes), this.arrayType.BYTES_PER_ELEMENT);
+    }
+    into.writeBytes(bytes);
+  }
+  allocationSize(array: T): number {
+    return (
+      FfiConverterTypedArray.sizeConverter.allocationSize(array.length) +
+      array.byteLength
+    );
+  }
+}
+
+export const FfiConverterInt8Array = new FfiConverterTypedArray(Int8Array);
+export const FfiConverterUint8Array = new FfiConverterTypedArray(Uint8Array);
+export const FfiConverterInt16Array = new FfiConverterTypedArray(Int16Array);
+export const FfiConverterUint16Array = new FfiConverterTypedArray(Uint16Array);
+export const FfiConverterInt32Array = new FfiConverterTypedArray(Int32Array);
+export const FfiConverterUint32Array = new FfiConverterTypedArray(Uint32Array);
+export const FfiConverterBigInt64Array = new FfiConverterTypedArray(
+  BigInt64Array,
+);
+export const FfiConverterBigUint64Array = new FfiConverterTypedArray(
+  BigUint64Array,
+);
+export const FfiConverterFloat32Array = new FfiConverterTypedArray(
+  Float32Array,
+);
+export const FfiConverterFloat64Array = new FfiConverterTypedArray(
+  Float64Array,
+);
+
 export class FfiConverterMap<K, V> extends AbstractFfiConverterArrayBuffer<
   Map<K, V>
 > {
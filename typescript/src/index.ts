@@ -14,6 +14,7 @@ export * from "./errors";
 export * from "./ffi-converters";
 export * from "./ffi-types";
 export * from "./handle-map";
+export * from "./json";
 export * from "./objects";
 export * from "./records";
 export * from "./rust-call";
@@ -0,0 +1,224 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import { UniffiInternalError } from "./errors";
+
+/**
+ * Converts values of one type to and from something which `JSON.stringify` can write and
+ * `JSON.parse` can read back, and compares two values of the type.
+ *
+ * Each generated record and tagged enum has one of these: e.g. `MyRecord.toJSON(value)`.
+ * The generated code composes them with the ones below, for the types of the fields.
+ */
+export interface UniffiJsonConverter<T> {
+  toJSON(value: T): any;
+  fromJSON(json: any): T;
+  equals(a: T, b: T): boolean;
+}
+
+type TypedArray =
+  | Int8Array
+  | Uint8Array
+  | Int16Array
+  | Uint16Array
+  | Int32Array
+  | Uint32Array
+  | BigInt64Array
+  | BigUint64Array
+  | Float32Array
+  | Float64Array;
+type TypedArrayConstructor<T extends TypedArray> = {
+  from(items: ArrayLike<any>): T;
+};
+
+// Strings, booleans, integers which fit into a number, and flat enums.
+const identity: UniffiJsonConverter<any> = {
+  toJSON: (value) => value,
+  fromJSON: (json) => json,
+  equals: (a, b) => a === b,
+};
+
+// Floats, which may not be finite, which JSON has no way of writing.
+const number: UniffiJsonConverter<number> = {
+  toJSON: (value) => (Number.isFinite(value) ? value : String(value)),
+  fromJSON: (json) => (typeof json === "string" ? Number(json) : json),
+  equals: (a, b) => a === b || (Number.isNaN(a) && Number.isNaN(b)),
+};
+
+// 64-bit integers, which JSON.stringify throws on, are written as strings.
+const bigint: UniffiJsonConverter<bigint> = {
+  toJSON: (value) => value.toString(),
+  fromJSON: (json) => BigInt(json),
+  equals: (a, b) => a === b,
+};
+
+function sameItems<T>(
+  a: ArrayLike<T>,
+  b: ArrayLike<T>,
+  equals: (a: T, b: T) => boolean,
+): boolean {
+  if (a.length !== b.length) {
+    return false;
+  }
+  for (let i = 0; i < a.length; i++) {
+    if (!equals(a[i], b[i])) {
+      return false;
+    }
+  }
+  return true;
+}
+
+// Bytes are written as an array of numbers.
+const bytes: UniffiJsonConverter<ArrayBuffer> = {
+  toJSON: (value) => Array.from(new Uint8Array(value)),
+  fromJSON: (json) => new Uint8Array(json).buffer,
+  equals: (a, b) =>
+    sameItems(new Uint8Array(a), new Uint8Array(b), identity.equals),
+};
+
+// Dates are written as ISO strings, which hold all of their milliseconds.
+const date: UniffiJsonConverter<Date> = {
+  toJSON: (value) => value.toISOString(),
+  fromJSON: (json) => new Date(json),
+  equals: (a, b) => a.getTime() === b.getTime(),
+};
+
+// Timestamps and durations as objects, with bigint seconds.
+type Time = { seconds: bigint; nanos: number };
+const time: UniffiJsonConverter<Time> = {
+  toJSON: (value) => ({
+    seconds: value.seconds.toString(),
+    nanos: value.nanos,
+  }),
+  fromJSON: (json) => ({ seconds: BigInt(json.seconds), nanos: json.nanos }),
+  equals: (a, b) => a.seconds === b.seconds && a.nanos === b.nanos,
+};
+
+// Optionals are written as null when they are undefined, as JSON has no undefined.
+function optional<T>(
+  inner: UniffiJsonConverter<T>,
+): UniffiJsonConverter<T | undefined> {
+  return {
+    toJSON: (value) => (value === undefined ? null : inner.toJSON(value)),
+    fromJSON: (json) =>
+      json === null || json === undefined ? undefined : inner.fromJSON(json),
+    equals: (a, b) =>
+      a === undefined || b === undefined ? a === b : inner.equals(a, b),
+  };
+}
+
+function array<T>(item: UniffiJsonConverter<T>): UniffiJsonConverter<T[]> {
+  return {
+    toJSON: (value) => value.map((v) => item.toJSON(v)),
+    fromJSON: (json) => (json as any[]).map((v) => item.fromJSON(v)),
+    equals: (a, b) => sameItems(a, b, item.equals),
+  };
+}
+
+function typedArray<T extends TypedArray>(
+  arrayType: TypedArrayConstructor<T>,
+  item: UniffiJsonConverter<any>,
+): UniffiJsonConverter<T> {
+  return {
+    toJSON: (value) => Array.from(value as ArrayLike<any>, item.toJSON),
+    fromJSON: (json) => arrayType.from((json as any[]).map(item.fromJSON)),
+    equals: (a, b) => sameItems<any>(a, b, item.equals),
+  };
+}
+
+// Maps are written as arrays of [key, value] pairs, because the keys may not be strings.
+function map<K, V>(
+  key: UniffiJsonConverter<K>,
+  value: UniffiJsonConverter<V>,
+): UniffiJsonConverter<Map<K, V>> {
+  return {
+    toJSON: (m) =>
+      Array.from(m.entries(), ([k, v]) => [key.toJSON(k), value.toJSON(v)]),
+    fromJSON: (json) =>
+      new Map(
+        (json as [any, any][]).map(([k, v]) => [
+          key.fromJSON(k),
+          value.fromJSON(v),
+        ]),
+      ),
+    equals: (a, b) => {
+      if (a.size !== b.size) {
+        return false;
+      }
+      for (const [k, v] of a.entries()) {
+        if (!b.has(k) || !value.equals(v, b.get(k)!)) {
+          return false;
+        }
+      }
+      return true;
+    },
+  };
+}
+
+// Custom types are written as their builtin type.
+function custom<T, B>(
+  builtin: UniffiJsonConverter<B>,
+  intoCustom: (value: B) => T,
+  fromCustom: (value: T) => B,
+): UniffiJsonConverter<T> {
+  return {
+    toJSON: (value) => builtin.toJSON(fromCustom(value)),
+    fromJSON: (json) => intoCustom(builtin.fromJSON(json)),
+    equals: (a, b) => builtin.equals(fromCustom(a), fromCustom(b)),
+  };
+}
+
+// Enums from another crate: flat enums are Typescript enums, which are written as they
+// are, and tagged enums have converters of their own.
+function enumeration<T>(e: any): UniffiJsonConverter<T> {
+  return typeof e.toJSON === "function" ? e : identity;
+}
+
+// Records and tagged enums, which may be declared after the converters for the fields
+// which hold them, so they are only looked up when they are first needed.
+function lazy<T>(
+  converter: () => UniffiJsonConverter<T>,
+): UniffiJsonConverter<T> {
+  return {
+    toJSON: (value) => converter().toJSON(value),
+    fromJSON: (json) => converter().fromJSON(json),
+    equals: (a, b) => converter().equals(a, b),
+  };
+}
+
+// Objects and callback interfaces live in Rust or in JS, so cannot be written as JSON.
+// They are only equal if they are the same object.
+function unsupported<T>(typeName: string): UniffiJsonConverter<T> {
+  const fail = (): never => {
+    throw new UniffiInternalError.Unimplemented(
+      `${typeName} cannot be converted to or from JSON`,
+    );
+  };
+  return {
+    toJSON: fail,
+    fromJSON: fail,
+    equals: (a, b) => a === b,
+  };
+}
+
+/**
+ * The converters used by the generated `toJSON`, `fromJSON` and `equals` functions.
+ */
+export const uniffiJson = Object.freeze({
+  identity,
+  number,
+  bigint,
+  bytes,
+  date,
+  time,
+  optional,
+  array,
+  typedArray,
+  map,
+  custom,
+  enumeration,
+  lazy,
+  unsupported,
+});
@@ -8,15 +8,29 @@ import {
   FfiConverter,
   FfiConverterArray,
   AbstractFfiConverterArrayBuffer,
+  FfiConverterBigInt64Array,
   FfiConverterBool,
+  FfiConverterDurationObject,
+  FfiConverterFloat32,
+  FfiConverterFloat32Array,
   FfiConverterInt16,
   FfiConverterInt32,
   FfiConverterInt8,
+  FfiConverterInt64AsNumber,
   FfiConverterOptional,
   FfiConverterPrimitive,
+  FfiConverterTimestamp,
+  FfiConverterTimestampExact,
+  FfiConverterTimestampMillis,
+  FfiConverterTimestampObject,
+  FfiConverterTimestampRounded,
   FfiConverterUInt16,
   FfiConverterUInt8,
+  FfiConverterUint8Array,
+  FfiConverterUInt64AsNumber,
+  uniffiCheckInteger,
 } from "../src/ffi-converters";
+import { UniffiInternalError } from "../src/errors";
 import { Asserts, test } from "../testing/asserts";
 
 class TestConverter<
@@ -79,6 +93,106 @@ test("4 byte converter", (t) => {
   testConverter(t, converter, 0x7fffffff);
 });
 
+test("8 byte converter, as a number", (t) => {
+  const converter = new TestConverter(FfiConverterInt64AsNumber);
+  testConverter(t, converter, Number.MIN_SAFE_INTEGER);
+  testConverter(t, converter, 0);
+  testConverter(t, converter, Number.MAX_SAFE_INTEGER);
+
+  const isPrecisionLoss = (e: any) =>
+    e instanceof UniffiInternalError.NumberPrecisionLoss;
+  t.assertThrows(isPrecisionLoss, () =>
+    converter.lower(Number.MAX_SAFE_INTEGER + 1),
+  );
+  t.assertThrows(isPrecisionLoss, () => converter.lower(1.5));
+  t.assertThrows(isPrecisionLoss, () =>
+    FfiConverterInt64AsNumber.lift(BigInt(Number.MAX_SAFE_INTEGER) + BigInt(1)),
+  );
+  t.assertThrows(isPrecisionLoss, () => FfiConverterUInt64AsNumber.lower(-1));
+});
+
+test("Checking integers", (t) => {
+  t.assertEqual(uniffiCheckInteger(255, "u8", "myFunction", "arg"), 255);
+  t.assertEqual(uniffiCheckInteger(-128, "i8", "myFunction", "arg"), -128);
+  t.assertEqual(
+    uniffiCheckInteger(BigInt("18446744073709551615"), "u64", "MyRecord", "f"),
+    BigInt("18446744073709551615"),
+  );
+
+  const isInvalid = (e: any) => e instanceof UniffiInternalError.InvalidInteger;
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(-1, "u8", "myFunction", "arg"),
+  );
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(3.7, "i32", "myFunction", "arg"),
+  );
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(0x80000000, "i32", "myFunction", "arg"),
+  );
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(BigInt(-1), "u64", "MyRecord", "field"),
+  );
+
+  // Integers in optionals, sequences and maps.
+  const optional = { optional: "u8" } as const;
+  t.assertEqual(uniffiCheckInteger(undefined, optional, "f", "arg"), undefined);
+  t.assertEqual(uniffiCheckInteger(255, optional, "f", "arg"), 255);
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(256, optional, "f", "arg"),
+  );
+
+  const sequence = { sequence: { optional: "i32" } } as const;
+  uniffiCheckInteger([1, undefined, -1], sequence, "f", "arg");
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger([1, undefined, 0.5], sequence, "f", "arg"),
+  );
+
+  const map = { mapKey: "u16", mapValue: { sequence: "i8" } } as const;
+  uniffiCheckInteger(new Map([[65535, [127]]]), map, "f", "arg");
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(new Map([[65536, [0]]]), map, "f", "arg"),
+  );
+  t.assertThrows(isInvalid, () =>
+    uniffiCheckInteger(new Map([[1, [128]]]), map, "f", "arg"),
+  );
+});
+
+test("Timestamps and durations", (t) => {
+  testConverter(t, FfiConverterTimestamp, new Date(-2300));
+  testConverter(t, FfiConverterTimestampMillis, 1500.5);
+  testConverter(t, FfiConverterTimestampObject, {
+    seconds: BigInt(-3),
+    nanos: 700000000,
+  });
+  testConverter(t, FfiConverterDurationObject, {
+    seconds: BigInt(1),
+    nanos: 5,
+  });
+
+  // The same time, in different representations, is the same in Rust.
+  t.assertEqual(
+    new Uint8Array(FfiConverterTimestamp.lower(new Date(-2300))),
+    new Uint8Array(
+      FfiConverterTimestampObject.lower({
+        seconds: BigInt(-3),
+        nanos: 700000000,
+      }),
+    ),
+  );
+
+  // A Date only holds whole milliseconds.
+  const almost2s = FfiConverterTimestampObject.lower({
+    seconds: BigInt(1),
+    nanos: 999600000,
+  });
+  t.assertEqual(FfiConverterTimestamp.lift(almost2s), new Date(1999));
+  t.assertEqual(FfiConverterTimestampRounded.lift(almost2s), new Date(2000));
+  t.assertThrows(
+    (e: any) => e instanceof UniffiInternalError.NumberPrecisionLoss,
+    () => FfiConverterTimestampExact.lift(almost2s),
+  );
+});
+
 test("Optional 1 byte converter", (t) => {
   const converter = new FfiConverterOptional(FfiConverterInt8);
   testConverter(t, converter, -0x7f);
@@ -109,6 +223,33 @@ test("Array of shorts", (t) => {
   testConverter(t, converter, new Array(100).fill(128));
 });
 
+test("Typed arrays", (t) => {
+  testConverter(t, FfiConverterUint8Array, new Uint8Array([1, 2, 255]));
+  testConverter(t, FfiConverterFloat32Array, new Float32Array([0.5, -1, 3]));
+  testConverter(
+    t,
+    FfiConverterBigInt64Array,
+    new BigInt64Array([BigInt(-1), BigInt("9007199254740993")]),
+  );
+  testConverter(t, FfiConverterFloat32Array, new Float32Array(100).fill(2));
+
+  // The bytes are the same as for the equivalent array.
+  const floats = [0.5, -1, 3];
+  t.assertEqual(
+    new Uint8Array(FfiConverterFloat32Array.lower(new Float32Array(floats))),
+    new Uint8Array(new FfiConverterArray(FfiConverterFloat32).lower(floats)),
+  );
+
+  // Only the items in view are written, and the array itself is left as it was.
+  const whole = new Float32Array([1, 2, 3, 4]);
+  const part = whole.subarray(1, 3);
+  t.assertEqual(
+    FfiConverterFloat32Array.lift(FfiConverterFloat32Array.lower(part)),
+    new Float32Array([2, 3]),
+  );
+  t.assertEqual(whole, new Float32Array([1, 2, 3, 4]));
+});
+
 test("Array of optional shorts", (t) => {
   const converter = new FfiConverterArray(
     new FfiConverterOptional(FfiConverterUInt16),
@@ -0,0 +1,92 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/
+ */
+import { UniffiJsonConverter, uniffiJson } from "../src/json";
+import { Asserts, test } from "../testing/asserts";
+
+function roundTrip<T>(
+  t: Asserts,
+  converter: UniffiJsonConverter<T>,
+  value: T,
+): T {
+  const json = JSON.stringify(converter.toJSON(value));
+  const copy = converter.fromJSON(JSON.parse(json));
+  t.assertTrue(converter.equals(value, copy), `${json} did not round trip`);
+  return copy;
+}
+
+test("Numbers and bigints", (t) => {
+  roundTrip(t, uniffiJson.number, 1.5);
+  roundTrip(t, uniffiJson.number, NaN);
+  t.assertEqual(roundTrip(t, uniffiJson.number, -Infinity), -Infinity);
+  t.assertEqual(
+    roundTrip(t, uniffiJson.bigint, BigInt("0xffffffffffffffff")),
+    BigInt("0xffffffffffffffff"),
+  );
+});
+
+test("Optionals, arrays and maps", (t) => {
+  const optional = uniffiJson.optional(uniffiJson.bigint);
+  t.assertEqual(roundTrip(t, optional, undefined), undefined);
+  t.assertEqual(roundTrip(t, optional, BigInt(1)), BigInt(1));
+  t.assertFalse(optional.equals(undefined, BigInt(0)));
+
+  const array = uniffiJson.array(uniffiJson.identity);
+  roundTrip(t, array, ["a", "b"]);
+  t.assertFalse(array.equals(["a"], ["a", "b"]));
+
+  const typedArray = uniffiJson.typedArray(
+    BigInt64Array,
+    uniffiJson.bigint,
+  );
+  roundTrip(t, typedArray, BigInt64Array.of(BigInt(-1), BigInt(2)));
+
+  const map = uniffiJson.map(uniffiJson.bigint, array);
+  const copy = roundTrip(
+    t,
+    map,
+    new Map([
+      [BigInt(1), ["one"]],
+      [BigInt(2), ["two", "deux"]],
+    ]),
+  );
+  t.assertEqual(copy.get(BigInt(2))?.length, 2);
+  t.assertFalse(map.equals(copy, new Map([[BigInt(1), ["one"]]])));
+});
+
+test("Bytes, dates and times", (t) => {
+  roundTrip(t, uniffiJson.bytes, Uint8Array.of(1, 2, 255).buffer);
+  roundTrip(t, uniffiJson.date, new Date(1234567890123));
+  roundTrip(t, uniffiJson.time, { seconds: BigInt(-1), nanos: 500 });
+});
+
+test("Custom types", (t) => {
+  const url = uniffiJson.custom(
+    uniffiJson.identity,
+    (s: string) => ({ href: s }),
+    (u: { href: string }) => u.href,
+  );
+  const copy = roundTrip(t, url, { href: "https://example.com" });
+  t.assertEqual(copy.href, "https://example.com");
+});
+
+test("Lazy converters", (t) => {
+  // Used before the converter it refers to is declared.
+  const lazy = uniffiJson.lazy(() => later);
+  const later = uniffiJson.optional(uniffiJson.bigint);
+  t.assertEqual(roundTrip(t, lazy, BigInt(2)), BigInt(2));
+  t.assertEqual(roundTrip(t, lazy, undefined), undefined);
+});
+
+test("Unsupported types", (t) => {
+  const unsupported = uniffiJson.unsupported<object>("MyObject");
+  const obj = {};
+  t.assertThrows(
+    (e: any) => e instanceof Error,
+    () => unsupported.toJSON(obj),
+  );
+  t.assertTrue(unsupported.equals(obj, obj));
+  t.assertFalse(unsupported.equals(obj, {}));
+});
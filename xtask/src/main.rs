@@ -39,6 +39,7 @@ enum Cmd {
 
 fn main() -> Result<()> {
     let args = CliArgs::parse();
+    ubrn_common::init_logging(log::LevelFilter::Info, None)?;
 
     match args.cmd {
         Cmd::Bootstrap(c) => c.run(),
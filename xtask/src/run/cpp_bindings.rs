@@ -7,7 +7,7 @@ use std::{fs, process::Command};
 
 use anyhow::Result;
 use camino::{Utf8Path, Utf8PathBuf};
-use ubrn_common::{rm_dir, run_cmd_quietly, so_extension};
+use ubrn_common::{rm_dir, run_cmd_quietly, so_extension, LibraryType};
 
 use crate::{
     bootstrap::{Bootstrap, HermesCmd, TestRunnerCmd},
@@ -81,7 +81,10 @@ impl CppBindingArg {
         let mut cmd = Command::new("ninja");
         run_cmd_quietly(cmd.current_dir(&build_dir))?;
 
-        Ok(build_dir.join(format!("lib{extension_name}.{}", so_extension(None))))
+        Ok(build_dir.join(format!(
+            "lib{extension_name}.{}",
+            so_extension(None, LibraryType::Shared)
+        )))
     }
 
     pub(crate) fn compile_without_crate(&self, clean: bool) -> Result<Utf8PathBuf> {
@@ -123,6 +126,9 @@ impl CppBindingArg {
         let mut cmd = Command::new("ninja");
         run_cmd_quietly(cmd.current_dir(&build_dir))?;
 
-        Ok(build_dir.join(format!("lib{extension_name}.{}", so_extension(None))))
+        Ok(build_dir.join(format!(
+            "lib{extension_name}.{}",
+            so_extension(None, LibraryType::Shared)
+        )))
     }
 }
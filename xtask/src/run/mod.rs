@@ -12,7 +12,6 @@ use anyhow::{Ok, Result};
 use camino::Utf8PathBuf;
 use clap::Args;
 use generate_bindings::GenerateBindingsArg;
-use ubrn_common::CrateMetadata;
 
 use crate::bootstrap::{Bootstrap, TestRunnerCmd};
 
@@ -51,13 +50,10 @@ impl RunCmd {
 
     fn prepare_library_path(&self) -> Result<Option<Utf8PathBuf>> {
         let clean = self.clean;
-        let (release, info) = if let Some(crate_) = &self.crate_ {
-            (
-                CrateMetadata::profile(crate_.release),
-                Some(crate_.cargo_build(clean)?),
-            )
+        let (profile, info) = if let Some(crate_) = &self.crate_ {
+            (crate_.profile(), Some(crate_.cargo_build(clean)?))
         } else {
-            (CrateMetadata::profile(false), None)
+            ("dev", None)
         };
 
         match (&info, &self.cpp_binding, &self.generate_bindings) {
@@ -74,7 +70,7 @@ impl RunCmd {
                 Ok(Some(so_file))
             }
             (Some(crate_), None, Some(bindings)) => {
-                let crate_lib = crate_.library_path(None, release);
+                let crate_lib = crate_.library_path(None, profile);
                 let target_dir = crate_.target_dir();
                 let lib_name = crate_.library_name();
                 let cpp_files = bindings.generate(&crate_lib)?;
@@ -29,10 +29,18 @@ pub(crate) struct CrateArg {
 }
 
 impl CrateArg {
+    /// The cargo profile the crate is built with.
+    pub(crate) fn profile(&self) -> &'static str {
+        if self.release {
+            "release"
+        } else {
+            "dev"
+        }
+    }
+
     pub(crate) fn cargo_build(&self, clean: bool) -> Result<CrateMetadata> {
         let metadata = CrateMetadata::try_from(self.crate_dir.clone().expect("crate has no path"))?;
-        let profile = CrateMetadata::profile(self.release);
-        let lib_path = metadata.library_path(None, profile);
+        let lib_path = metadata.library_path(None, self.profile());
         if lib_path.exists() && clean {
             metadata.cargo_clean()?;
         }